@@ -1,33 +1,149 @@
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
     fs::{self, File},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
 };
 
-use chrono::{DateTime, Utc};
-use rand::{rng, seq::IndexedRandom};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use notify::RecommendedWatcher;
+use rand::seq::IndexedRandom;
+use rand::{rng, Rng};
+use serde::{Deserialize, Deserializer, Serialize};
 use thiserror::Error;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio::time::{Duration, Instant, MissedTickBehavior};
 
 use tauri::{
     async_runtime::{self, JoinHandle},
     AppHandle, Emitter, Manager, Wry,
 };
-use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_global_shortcut::Shortcut;
+use tauri_plugin_notification::{NotificationExt, PermissionState};
 
 #[cfg(target_os = "linux")]
-use notify_rust::Notification as LinuxNotification;
+use notify_rust::{ActionResponse, CloseReason, Notification as LinuxNotification};
 
-use crate::{events, idle_detection::IdleDetector, tray::TrayState};
+use crate::{
+    bluetooth_call, calendar, display_power, do_not_disturb, env_prefs, events,
+    exercises::{Exercise, EXERCISES},
+    focus_bar::{self, FocusBarPosition},
+    haptics,
+    http_trigger::HttpTrigger,
+    idle_detection::{IdleBackend, IdleDetector, WaylandHealth},
+    lid_state, overlay, power_source, prefs_watch,
+    process_watch::ProcessWatcher,
+    remote_session, screen_lock, shortcuts,
+    sound_uri::SoundCache,
+    stats::{
+        self, DayStats, HourEffectiveness, IntervalSuggestion, SnoozeReason, Stats, StatsEvent,
+        WeeklyReport,
+    },
+    status_feed::StatusFeed,
+    tray::{self, TrayLabels, TrayState},
+};
 
 const PREFERENCES_FILE: &str = "preferences.json";
+/// Left in the config directory by `skip_next_autostart`.
+const SKIP_AUTOSTART_MARKER: &str = "skip-next-autostart";
+/// Scratch file `self_test` writes and removes again.
+const SELF_TEST_FILE: &str = "self-test.json";
+const CONFIG_DIR_ATTEMPTS: u32 = 4;
+const CONFIG_DIR_INITIAL_BACKOFF_MS: u64 = 100;
 const DEFAULT_IDLE_THRESHOLD_MINUTES: u64 = 2;
 const MIN_IDLE_THRESHOLD_MINUTES: u64 = 1;
 const MAX_IDLE_THRESHOLD_MINUTES: u64 = 30;
+const DEFAULT_IDLE_GRACE_SECS: u64 = 10;
+const MAX_IDLE_GRACE_SECS: u64 = 120;
+const DEFAULT_OVERLAY_OPACITY: f32 = 0.85;
+const DEFAULT_OVERLAY_COLOR: &str = "#0b1d14";
+const DEFAULT_MAX_OVERLAYS_PER_HOUR: u8 = 3;
+const MAX_OVERLAYS_PER_HOUR: u8 = 12;
+const DEFAULT_ACTIVE_DEBOUNCE_POLLS: u8 = 2;
+const MAX_ACTIVE_DEBOUNCE_POLLS: u8 = 6;
 const IDLE_POLL_INTERVAL_SECS: u64 = 20;
+/// Idle poll spacing in eco mode, to wake the CPU less on battery.
+const ECO_IDLE_POLL_INTERVAL_SECS: u64 = 60;
 const DEFAULT_INTERVAL_MINUTES: u64 = 30;
+/// Workday length `set_breaks_per_day` spreads breaks over.
+const WORKDAY_MINUTES: u64 = 8 * 60;
+const MIN_INTERVAL_SECONDS: u64 = 10;
+/// Floor on the first reminder after a login autostart, so it doesn't land in
+/// the middle of everything else starting up.
+const LOGIN_MIN_FIRST_REMINDER_SECS: u64 = 5 * 60;
+const SNOOZE_HISTORY_LEN: usize = 10;
+const MIN_SMART_SNOOZE_MINUTES: u64 = 5;
+const MAX_SMART_SNOOZE_MINUTES: u64 = 30;
+const MAX_MESSAGE_IMPORT_BYTES: u64 = 256 * 1024;
+/// Escalating snooze lengths, one per consecutive snooze; the last repeats.
+const ESCALATING_SNOOZE_MINUTES: &[u64] = &[15, 10, 5, 2];
+const DEFAULT_MINI_BREAK_WEIGHT: f64 = 0.5;
+/// Consecutive undeliverable reminders before warning that notifications
+/// don't work in this environment.
+const NOTIFICATION_FAILURE_WARN_AFTER: u32 = 3;
+const NOTIFICATION_HISTORY_LEN: usize = 20;
+const MAX_UPCOMING: usize = 50;
+/// Shown when filtering leaves a kind with no messages at all.
+const FALLBACK_MESSAGE: &str = "Time for a quick reset.";
+/// Steady activity for this long also ends a break that is waiting for "I'm back".
+const RETURN_CONFIRMATION_WINDOW_SECS: u64 = 120;
+/// Longest snooze reason kept, in characters.
+const MAX_SNOOZE_REASON_CHARS: usize = 200;
+/// Longest extra wait allowed after a break, in minutes.
+const MAX_POST_BREAK_COOLDOWN_MINUTES: u64 = 120;
+/// Longest ramp a campaign may have.
+const MAX_CAMPAIGN_DAYS: u32 = 365;
+/// Longest `tray_tooltip_template`; Windows cuts tooltips off at 128.
+const MAX_TOOLTIP_TEMPLATE_CHARS: usize = 128;
+/// Placeholders a `tray_tooltip_template` may use.
+const TOOLTIP_PLACEHOLDERS: &[&str] = &[
+    "{next}", "{status}", "{streak}", "{count}", "{time}", "{breaks}",
+];
+/// Most snoozes `long_break_after_snoozes` can wait for.
+const MAX_LONG_BREAK_AFTER_SNOOZES: u8 = 10;
+/// How long the break offered after repeated snoozes is meant to last.
+const LONG_BREAK_MINUTES: u64 = 15;
+/// Longest break `log_manual_break` accepts.
+const MAX_MANUAL_BREAK_MINUTES: u64 = 8 * 60;
+/// Characters kept from a manual break's note.
+const MAX_BREAK_NOTE_CHARS: usize = 200;
+/// Repeats of the same snooze, skip, or trigger within this window count as one.
+const ACTION_COOLDOWN_MS: u64 = 2000;
+/// Longest an idle-aware snooze is stretched past its original end.
+const IDLE_SNOOZE_MAX_EXTENSION_MINUTES: i64 = 60;
+/// How often a stretched snooze checks whether the user is back.
+const IDLE_SNOOZE_RECHECK_SECS: u64 = 60;
+/// How late a timer can be before `catch_up` calls it overdue; anything
+/// closer is about to fire on its own.
+const CATCH_UP_GRACE_SECS: i64 = 60;
+/// Cap on `snooze_until_free`, so a runaway event can't snooze for days.
+const MAX_CALENDAR_SNOOZE_MINUTES: u64 = 4 * 60;
+/// Longest sequence `queue_actions` accepts.
+const MAX_QUEUED_ACTIONS: usize = 20;
+/// Longest wait `queue_actions` accepts before one action, and for the
+/// whole sequence.
+const MAX_QUEUED_ACTION_DELAY_SECS: u64 = 24 * 60 * 60;
+const MAX_QUEUE_SPAN_SECS: u64 = 7 * 24 * 60 * 60;
+/// The soonest a rescheduled reminder may fire; see `safe_next`.
+const MIN_RESCHEDULE_LEAD_SECS: u64 = 5;
+/// How long a snooze that `idle_aware_snooze` stretched waits once the user
+/// is back, so the reminder isn't the first thing they see.
+const SNOOZE_RETURN_GRACE_SECS: u64 = 30;
+/// Trailing window `adaptive_interval` looks back over.
+const ADAPTIVE_WINDOW_SECS: u64 = 60 * 60;
+/// Bounds on the `adaptive_interval` multiplier.
+const ADAPTIVE_MIN_SCALE: f64 = 0.75;
+const ADAPTIVE_MAX_SCALE: f64 = 1.5;
+/// Share of the window spent away (3 minutes an hour) that keeps the
+/// interval as configured.
+const ADAPTIVE_NEUTRAL_AWAY_SHARE: f64 = 0.05;
+const DEFAULT_MICRO_BREAKS_PER_HOUR: u8 = 2;
+const MAX_MICRO_BREAKS_PER_HOUR: u8 = 6;
+/// Micro-breaks this close to the main reminder are dropped as redundant.
+const MICRO_BREAK_MAIN_GAP_SECS: u64 = 2 * 60;
 
 #[derive(Debug, Error)]
 pub enum AppStateError {
@@ -39,36 +155,472 @@ pub enum AppStateError {
     Serde(#[from] serde_json::Error),
     #[error("task join error: {0}")]
     Join(#[from] tokio::task::JoinError),
+    #[error("one-off reminder time is in the past")]
+    OneOffInPast,
+    #[error("no one-off reminder with id {0}")]
+    OneOffNotFound(u64),
+    #[error("no exercise at index {0}")]
+    ExerciseNotFound(usize),
+    #[error("couldn't read {0:?} as a duration; try something like \"45m\", \"2h\" or \"1h30m\"")]
+    InvalidDuration(String),
+    #[error("invalid hotkey {0:?}")]
+    InvalidHotkey(String),
+    #[error("message file is too large ({0} bytes, limit is {MAX_MESSAGE_IMPORT_BYTES})")]
+    ImportTooLarge(u64),
+    #[error("sound file {0:?} doesn't exist")]
+    SoundNotFound(String),
+    #[error("sound file {0:?} is too large to play")]
+    SoundTooLarge(String),
+    #[error("sound file {0:?} isn't a supported audio format")]
+    UnsupportedSound(String),
+    #[error("no custom sound is set; the bundled chime plays instead")]
+    NoCustomSound,
+    #[error("the config directory is unavailable")]
+    NoConfigDir,
+    #[error("invalid color {0:?}; expected a hex color like \"#1a2b3c\"")]
+    InvalidColor(String),
+    #[error("debug tools are turned off in preferences")]
+    DebugToolsDisabled,
+    #[error("override end time is in the past")]
+    OverrideInPast,
+    #[error("pause end time is in the past")]
+    PauseInPast,
+    #[error("{0:?} reminders aren't scheduled on their own, so there's no next one to skip")]
+    NotScheduled(ReminderKind),
+    #[error("an action queue holds at most {} actions", MAX_QUEUED_ACTIONS)]
+    TooManyQueuedActions,
+    #[error(
+        "queued actions can wait at most {} hours each and {} days in all",
+        MAX_QUEUED_ACTION_DELAY_SECS / 3600,
+        MAX_QUEUE_SPAN_SECS / 86_400
+    )]
+    QueuedDelayTooLong,
+    #[error("calendar file {0:?} doesn't exist")]
+    CalendarNotFound(String),
+    #[error("no calendar file is set in preferences")]
+    NoCalendar,
+    #[error("notification failed: {0}")]
+    Notification(String),
+    #[error("a break has to last between 1 and {MAX_MANUAL_BREAK_MINUTES} minutes, not {0}")]
+    InvalidBreakDuration(u64),
+    #[error("reminders need at least one day of the week")]
+    NoReminderDays,
+    #[error("invalid tray tooltip template: {0}")]
+    InvalidTooltipTemplate(String),
+    #[error("unknown snooze boundary {0:?}; expected afternoon, evening or tomorrow_morning")]
+    UnknownSnoozeBoundary(String),
+    #[error("{0} is set in the environment, so that preference can't be changed here")]
+    EnvOverridden(&'static str),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Preferences {
     pub interval_minutes: u64,
+    /// Overrides `interval_minutes` when set, for sub-minute intervals.
+    #[serde(default)]
+    pub interval_seconds: Option<u64>,
+    /// Delay before the first reminder after launch; `None` waits a full interval.
+    #[serde(default)]
+    pub first_reminder_delay_minutes: Option<u64>,
     pub activity_detection: bool,
     pub sound_enabled: bool,
+    /// Custom reminder sound; `None` plays the bundled chime.
+    #[serde(default)]
+    pub sound_path: Option<String>,
+    /// Per-kind sounds, falling back to `sound_path`.
+    #[serde(default)]
+    pub kind_sounds: HashMap<ReminderKind, String>,
     pub autostart_enabled: bool,
     pub theme: ThemeMode,
     #[serde(default = "default_idle_threshold_minutes")]
     pub idle_threshold_minutes: u64,
+    /// Extra seconds past the idle threshold before the user counts as away,
+    /// so idle blips right at the boundary don't reset the timer.
+    #[serde(default = "default_idle_grace_secs")]
+    pub idle_grace_secs: u64,
+    /// What idle time means inside an RDP or SSH session, where it only
+    /// reflects input at the machine itself.
+    #[serde(default)]
+    pub remote_session_idle: RemoteSessionIdle,
+    #[serde(default = "default_true")]
+    pub playful_labels: bool,
+    #[serde(default = "default_true")]
+    pub replace_notifications: bool,
+    #[serde(default)]
+    pub evening_summary: bool,
+    /// Local time the work day ends, used for the evening summary.
+    #[serde(default)]
+    pub work_end: Option<NaiveTime>,
+    /// Local time "snooze until tomorrow morning" ends.
+    #[serde(default = "default_snooze_morning_at")]
+    pub snooze_morning_at: NaiveTime,
+    /// Local time "snooze until after lunch" ends.
+    #[serde(default = "default_snooze_afternoon_at")]
+    pub snooze_afternoon_at: NaiveTime,
+    /// Local time "snooze until evening" ends.
+    #[serde(default = "default_snooze_evening_at")]
+    pub snooze_evening_at: NaiveTime,
+    /// Port for the read-only local status WebSocket; `None` keeps it off.
+    #[serde(default)]
+    pub ws_port: Option<u16>,
+    /// Port for the local `POST /trigger` endpoint; `None` keeps it off.
+    #[serde(default)]
+    pub http_trigger_port: Option<u16>,
+    /// Secret callers must send in `X-TouchGrass-Token`. Generated when the
+    /// endpoint is first turned on.
+    #[serde(default)]
+    pub http_trigger_token: Option<String>,
+    /// Whether a reminder dropped because the user was idle counts as a skip.
+    #[serde(default)]
+    pub count_idle_skips: bool,
+    /// User-written reminder messages mixed in with each kind's built-in ones.
+    /// Older files stored one flat list; it's read back as posture messages.
+    #[serde(default, deserialize_with = "deserialize_custom_messages")]
+    pub custom_messages: HashMap<ReminderKind, Vec<String>>,
+    #[serde(default)]
+    pub message_rotation: RotationMode,
+    #[serde(default)]
+    pub mood: Mood,
+    /// Global shortcut (e.g. `"CmdOrCtrl+Shift+H"`) that hides every
+    /// TouchGrass window and notification at once.
+    #[serde(default)]
+    pub panic_hotkey: Option<String>,
+    /// Cap on minutes snoozed between breaks; once spent, snoozing fires the
+    /// reminder instead. `None` means unlimited.
+    #[serde(default)]
+    pub max_total_snooze_minutes: Option<u64>,
+    /// After this many snoozes in a row, the next reminder offers a proper
+    /// `LONG_BREAK_MINUTES` break instead. `None` never offers one.
+    #[serde(default)]
+    pub long_break_after_snoozes: Option<u8>,
+    /// Short "look away" prompts at random times between regular reminders.
+    #[serde(default)]
+    pub micro_breaks_enabled: bool,
+    #[serde(default = "default_micro_breaks_per_hour")]
+    pub micro_breaks_per_hour: u8,
+    /// Hold reminders while the monitor is asleep.
+    #[serde(default)]
+    pub suppress_when_display_off: bool,
+    /// Hold reminders while the laptop lid is shut, for clamshell setups
+    /// where the machine stays awake on an external monitor.
+    #[serde(default)]
+    pub suppress_when_lid_closed: bool,
+    /// Hold reminders while a Bluetooth headset is in call mode.
+    #[serde(default)]
+    pub suppress_on_bluetooth_call: bool,
+    /// Hold reminders while a file exists at this path, so a script can
+    /// `touch` it for the length of a long job and remove it afterwards.
+    #[serde(default)]
+    pub busy_flag_path: Option<String>,
+    /// Keep the break going after the user comes back until they confirm it.
+    #[serde(default)]
+    pub require_return_confirmation: bool,
+    /// How much a mini break counts toward the completion rate, from 0 to 1.
+    #[serde(default = "default_mini_break_weight")]
+    pub mini_break_weight: f64,
+    /// Custom or translated tray menu text.
+    #[serde(default)]
+    pub tray_labels: TrayLabels,
+    /// Tray tooltip text with `{next}`, `{status}`, `{streak}` and
+    /// `{count}` filled in; `None` shows when the next break is due.
+    #[serde(default)]
+    pub tray_tooltip_template: Option<String>,
+    #[serde(default = "default_overlay_opacity")]
+    pub overlay_opacity: f32,
+    /// Hex background color of the break overlay.
+    #[serde(default = "default_overlay_color")]
+    pub overlay_color: String,
+    #[serde(default = "default_true")]
+    pub overlay_show_countdown: bool,
+    /// Regular breaks cover the screen with the overlay instead of a toast.
+    #[serde(default)]
+    pub overlay_on_break: bool,
+    /// Stretches the interval after the user took breaks on their own in the
+    /// last hour and shortens it after an hour without any. See
+    /// `adaptive_scale`.
+    #[serde(default)]
+    pub adaptive_interval: bool,
+    /// Regular reminders alternate between "sit" and "stand" for standing
+    /// desks, beginning with `starting_posture`.
+    #[serde(default)]
+    pub desk_alternation: bool,
+    #[serde(default)]
+    pub starting_posture: DeskPosture,
+    /// An `.ics` file (an exported or synced calendar) that
+    /// `snooze_until_free` reads meetings from.
+    #[serde(default)]
+    pub calendar_ics_path: Option<String>,
+    /// Overlays allowed in any trailing hour; breaks past that get a toast.
+    #[serde(default = "default_max_overlays_per_hour")]
+    pub max_overlays_per_hour: u8,
+    /// Consecutive active idle polls needed before an away user counts as
+    /// back, so one noisy near-zero X11 reading doesn't end a break.
+    #[serde(default = "default_active_debounce_polls")]
+    pub active_debounce_polls: u8,
+    /// Show the window when launched at login instead of starting in the tray.
+    #[serde(default)]
+    pub autostart_show_window: bool,
+    /// The tray's smart snooze shrinks with each snooze since the last break.
+    #[serde(default)]
+    pub escalating_snooze: bool,
+    /// Only time spent active counts toward the interval: stretches without
+    /// input push the next reminder back by as long as they lasted.
+    #[serde(default)]
+    pub count_only_active_time: bool,
+    /// Skips and snoozes don't restart the interval: the next reminder comes
+    /// at the next step of the schedule that began at launch. See
+    /// `next_on_rhythm`.
+    #[serde(default)]
+    pub preserve_rhythm: bool,
+    /// Rounds each scheduled reminder up to the next multiple of this many
+    /// minutes on the local clock (15 gives :00, :15, :30, :45). Snoozes are
+    /// left alone.
+    #[serde(default)]
+    pub snap_to_clock: Option<u64>,
+    /// Extra minutes added once to the interval that follows a completed
+    /// break, so the next reminder doesn't come right on its heels.
+    #[serde(default)]
+    pub post_break_cooldown_minutes: Option<u64>,
+    /// Replaces `interval_minutes` with one that widens day by day; see
+    /// `CampaignConfig`.
+    #[serde(default)]
+    pub campaign: Option<CampaignConfig>,
+    /// Local day the campaign began on.
+    #[serde(default)]
+    pub campaign_started: Option<NaiveDate>,
+    /// Weekdays that get reminders, Monday first. The engine sleeps through
+    /// the others and picks up at the start of the next listed day.
+    #[serde(default = "default_reminder_days")]
+    pub reminder_days: Vec<Weekday>,
+    /// On Saturdays and Sundays, switches to the relaxed weekend messages
+    /// and to `weekend_interval_minutes`, if set.
+    #[serde(default)]
+    pub weekend_mode: bool,
+    /// Interval on weekend days while `weekend_mode` is on; `None` keeps the
+    /// usual one. A running campaign still wins.
+    #[serde(default)]
+    pub weekend_interval_minutes: Option<u64>,
+    /// Adds an "Open TouchGrass" action to Linux notifications.
+    #[serde(default)]
+    pub extra_notification_actions: bool,
+    /// A snooze that runs out while the user is away waits for them to come
+    /// back, up to an hour longer.
+    #[serde(default)]
+    pub idle_aware_snooze: bool,
+    /// Tap the trackpad with each reminder, where the hardware can.
+    #[serde(default)]
+    pub haptic_enabled: bool,
+    /// Groups notifications by kind in Notification Center, via a thread id
+    /// per kind. macOS only, and only in a bundled build.
+    #[serde(default)]
+    pub group_notifications: bool,
+    /// Every cue at once for each reminder: notification, sound, a quick
+    /// overlay flash, the tray marker, and haptics where available.
+    #[serde(default)]
+    pub attention_mode: bool,
+    /// Executable names that hold reminders back while any of them runs.
+    #[serde(default)]
+    pub pause_during_processes: Vec<String>,
+    /// Poll less often while running on battery.
+    #[serde(default)]
+    pub eco_on_battery: bool,
+    /// What `catch_up` does with reminders that came due while the machine
+    /// slept.
+    #[serde(default)]
+    pub catch_up_behavior: CatchUpMode,
+    /// Hide to the tray on minimize instead of minimizing to the taskbar.
+    #[serde(default = "default_true")]
+    pub minimize_to_tray: bool,
+    /// Keep a small always-on-top countdown with a snooze button on screen.
+    #[serde(default)]
+    pub focus_bar_enabled: bool,
+    /// Fire reminders strictly on the interval, ignoring every automatic
+    /// hold (idle, locked screen, running apps, calls, days off, the busy
+    /// flag). Only what the user asks for directly still applies: pause,
+    /// snooze, silent mode, and skipping the next reminder.
+    #[serde(default)]
+    pub strict_mode: bool,
+    /// Enables support-only commands like `debug_dump_state`.
+    #[serde(default)]
+    pub debug_tools: bool,
 }
 
 impl Default for Preferences {
     fn default() -> Self {
         Self {
             interval_minutes: DEFAULT_INTERVAL_MINUTES,
+            interval_seconds: None,
+            first_reminder_delay_minutes: None,
             activity_detection: true,
             sound_enabled: true,
+            sound_path: None,
+            kind_sounds: HashMap::new(),
             autostart_enabled: true, // Enable by default for automatic reminders
             theme: ThemeMode::Dark,
             idle_threshold_minutes: DEFAULT_IDLE_THRESHOLD_MINUTES,
+            idle_grace_secs: DEFAULT_IDLE_GRACE_SECS,
+            remote_session_idle: RemoteSessionIdle::AssumeActive,
+            playful_labels: true,
+            replace_notifications: true,
+            evening_summary: false,
+            work_end: None,
+            snooze_morning_at: default_snooze_morning_at(),
+            snooze_afternoon_at: default_snooze_afternoon_at(),
+            snooze_evening_at: default_snooze_evening_at(),
+            ws_port: None,
+            http_trigger_port: None,
+            http_trigger_token: None,
+            count_idle_skips: false,
+            custom_messages: HashMap::new(),
+            message_rotation: RotationMode::Random,
+            mood: Mood::Snarky,
+            panic_hotkey: None,
+            max_total_snooze_minutes: None,
+            long_break_after_snoozes: None,
+            micro_breaks_enabled: false,
+            micro_breaks_per_hour: DEFAULT_MICRO_BREAKS_PER_HOUR,
+            suppress_when_display_off: false,
+            suppress_when_lid_closed: false,
+            suppress_on_bluetooth_call: false,
+            busy_flag_path: None,
+            require_return_confirmation: false,
+            mini_break_weight: DEFAULT_MINI_BREAK_WEIGHT,
+            tray_labels: TrayLabels::default(),
+            tray_tooltip_template: None,
+            overlay_opacity: DEFAULT_OVERLAY_OPACITY,
+            overlay_color: DEFAULT_OVERLAY_COLOR.to_string(),
+            overlay_show_countdown: true,
+            overlay_on_break: false,
+            adaptive_interval: false,
+            desk_alternation: false,
+            starting_posture: DeskPosture::Stand,
+            calendar_ics_path: None,
+            max_overlays_per_hour: DEFAULT_MAX_OVERLAYS_PER_HOUR,
+            active_debounce_polls: DEFAULT_ACTIVE_DEBOUNCE_POLLS,
+            autostart_show_window: false,
+            escalating_snooze: false,
+            count_only_active_time: false,
+            preserve_rhythm: false,
+            snap_to_clock: None,
+            post_break_cooldown_minutes: None,
+            campaign: None,
+            campaign_started: None,
+            reminder_days: default_reminder_days(),
+            weekend_mode: false,
+            weekend_interval_minutes: None,
+            extra_notification_actions: false,
+            idle_aware_snooze: false,
+            haptic_enabled: false,
+            group_notifications: false,
+            attention_mode: false,
+            pause_during_processes: Vec::new(),
+            eco_on_battery: false,
+            catch_up_behavior: CatchUpMode::Reschedule,
+            minimize_to_tray: true,
+            focus_bar_enabled: false,
+            strict_mode: false,
+            debug_tools: false,
         }
     }
 }
 
 impl Preferences {
+    /// Checks the fields that can't simply be clamped into range. Values
+    /// carried over unchanged from `previous` aren't checked again, so e.g. a
+    /// sound file deleted since doesn't block unrelated changes.
+    fn validate(&self, previous: &Preferences) -> Result<(), AppStateError> {
+        if self.sound_path != previous.sound_path {
+            if let Some(path) = self.sound_path.as_deref() {
+                ensure_sound_exists(path)?;
+            }
+        }
+        for (kind, path) in &self.kind_sounds {
+            if previous.kind_sounds.get(kind) != Some(path) {
+                ensure_sound_exists(path)?;
+            }
+        }
+        if self.panic_hotkey != previous.panic_hotkey {
+            if let Some(hotkey) = self.panic_hotkey.as_deref() {
+                hotkey
+                    .parse::<Shortcut>()
+                    .map_err(|_| AppStateError::InvalidHotkey(hotkey.to_string()))?;
+            }
+        }
+        if self.calendar_ics_path != previous.calendar_ics_path {
+            if let Some(path) = self.calendar_ics_path.as_deref() {
+                if !Path::new(path).is_file() {
+                    return Err(AppStateError::CalendarNotFound(path.to_string()));
+                }
+            }
+        }
+        if self.overlay_color != previous.overlay_color && !is_hex_color(&self.overlay_color) {
+            return Err(AppStateError::InvalidColor(self.overlay_color.clone()));
+        }
+        if self.reminder_days.is_empty() {
+            return Err(AppStateError::NoReminderDays);
+        }
+        if self.tray_tooltip_template != previous.tray_tooltip_template {
+            if let Some(template) = self.tray_tooltip_template.as_deref() {
+                validate_tooltip_template(template)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn interval_duration(&self) -> Duration {
-        Duration::from_secs(self.interval_minutes.max(1) * 60)
+        match self.interval_seconds {
+            Some(seconds) => Duration::from_secs(seconds.max(MIN_INTERVAL_SECONDS)),
+            None => Duration::from_secs(self.interval_minutes.max(1) * 60),
+        }
+    }
+
+    /// Where the running campaign is on `today`; `None` without one.
+    pub fn campaign_progress(&self, today: NaiveDate) -> Option<CampaignProgress> {
+        let campaign = self.campaign?;
+        let started = self.campaign_started.unwrap_or(today);
+        let ramp_days = campaign.ramp_days.max(1);
+        let day = (today - started).num_days().clamp(0, i64::from(ramp_days)) as u32;
+        let start = campaign.start_interval_minutes as f64;
+        let end = campaign.end_interval_minutes as f64;
+        let interval_minutes =
+            (start + (end - start) * f64::from(day) / f64::from(ramp_days)).round() as u64;
+        Some(CampaignProgress {
+            interval_minutes,
+            day,
+            days_remaining: ramp_days - day,
+        })
+    }
+
+    pub fn is_reminder_day(&self, date: NaiveDate) -> bool {
+        self.reminder_days.contains(&date.weekday())
+    }
+
+    /// Whether `weekend_mode` applies on `date`.
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend_mode && matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+
+    /// Wait before the first reminder of a session. Login autostarts never go
+    /// below `LOGIN_MIN_FIRST_REMINDER_SECS`.
+    pub fn first_reminder_delay(&self, launched_at_login: bool) -> Duration {
+        let delay = self
+            .first_reminder_delay_minutes
+            .map(|minutes| Duration::from_secs(minutes * 60))
+            .unwrap_or_else(|| self.interval_duration());
+        if launched_at_login {
+            delay.max(Duration::from_secs(LOGIN_MIN_FIRST_REMINDER_SECS))
+        } else {
+            delay
+        }
+    }
+
+    /// The sound for a reminder of `kind`; `None` means the bundled default.
+    pub fn sound_for(&self, kind: ReminderKind) -> Option<&str> {
+        self.kind_sounds
+            .get(&kind)
+            .or(self.sound_path.as_ref())
+            .map(String::as_str)
     }
 
     pub fn idle_threshold_secs(&self) -> u64 {
@@ -76,6 +628,171 @@ impl Preferences {
             .clamp(MIN_IDLE_THRESHOLD_MINUTES, MAX_IDLE_THRESHOLD_MINUTES)
             .saturating_mul(60)
     }
+
+    /// Idle seconds needed to count as away: the threshold plus the grace.
+    /// Applied to the detector's readings, so it behaves the same whether
+    /// they come from Wayland or X11.
+    pub fn away_threshold_secs(&self) -> u64 {
+        self.idle_threshold_secs()
+            .saturating_add(self.idle_grace_secs.min(MAX_IDLE_GRACE_SECS))
+    }
+}
+
+/// Preference fields whose values never appear in logs or diffs.
+const REDACTED_FIELDS: &[&str] = &["httpTriggerToken"];
+
+/// One preference that differs between two versions, keyed by its JSON name.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} \u{2192} {}", self.field, self.old, self.new)
+    }
+}
+
+/// Field-level changes from `old` to `new`, with secrets redacted.
+pub fn diff_preferences(old: &Preferences, new: &Preferences) -> Vec<FieldChange> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(mut new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+
+    let mut changes: Vec<FieldChange> = old
+        .into_iter()
+        .filter_map(|(field, old_value)| {
+            let new_value = new.remove(&field).unwrap_or(serde_json::Value::Null);
+            (old_value != new_value).then_some((field, old_value, new_value))
+        })
+        .map(|(field, old, new)| {
+            if REDACTED_FIELDS.contains(&field.as_str()) {
+                let redact = |value: serde_json::Value| {
+                    if value.is_null() {
+                        value
+                    } else {
+                        serde_json::Value::from("[redacted]")
+                    }
+                };
+                FieldChange {
+                    field,
+                    old: redact(old),
+                    new: redact(new),
+                }
+            } else {
+                FieldChange { field, old, new }
+            }
+        })
+        .collect();
+    changes.sort_by(|a, b| a.field.cmp(&b.field));
+    changes
+}
+
+/// How reminder messages are picked from the pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RotationMode {
+    #[default]
+    Random,
+    /// Random, but never the same message twice in a row.
+    NoRepeat,
+    /// In order, wrapping around.
+    Sequential,
+}
+
+/// Tone of the built-in reminder messages. Custom messages are used in
+/// every mood.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Mood {
+    #[default]
+    Snarky,
+    Kind,
+    Neutral,
+}
+
+/// How idle time is treated while running in a remote session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RemoteSessionIdle {
+    /// Ignore idle time, as if `activity_detection` were off: the machine
+    /// sees no input while the user types into the remote window, so it
+    /// would count every reminder as taken.
+    #[default]
+    AssumeActive,
+    /// Trust idle time as usual, for setups where input does reach it.
+    UseIdleTime,
+}
+
+/// Which way a standing-desk reminder points; see `desk_alternation`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeskPosture {
+    Sit,
+    #[default]
+    Stand,
+}
+
+impl DeskPosture {
+    fn flipped(self) -> Self {
+        match self {
+            Self::Sit => Self::Stand,
+            Self::Stand => Self::Sit,
+        }
+    }
+
+    fn messages(self) -> &'static [&'static str] {
+        match self {
+            Self::Sit => &[
+                "Time to sit. Lower the desk and give your feet a rest.",
+                "You've stood long enough. Take a seat for a while.",
+                "Desk down, chair in. Sitting stretch starts now.",
+            ],
+            Self::Stand => &[
+                "Time to stand. Raise the desk and get off that chair.",
+                "Up you go. Switch the desk to standing.",
+                "You've been sitting a while. Standing stretch starts now.",
+            ],
+        }
+    }
+}
+
+/// A reminder interval that starts short and widens evenly, day by day,
+/// to `end_interval_minutes` over `ramp_days`, then stays there. Meant for
+/// RSI recovery or building a habit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignConfig {
+    pub start_interval_minutes: u64,
+    pub end_interval_minutes: u64,
+    pub ramp_days: u32,
+}
+
+impl CampaignConfig {
+    /// Intervals within the bounds of `interval_minutes`, and at least a day
+    /// of ramp.
+    fn clamped(self) -> Self {
+        Self {
+            start_interval_minutes: self.start_interval_minutes.clamp(2, 240),
+            end_interval_minutes: self.end_interval_minutes.clamp(2, 240),
+            ramp_days: self.ramp_days.clamp(1, MAX_CAMPAIGN_DAYS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignProgress {
+    /// Today's interval.
+    pub interval_minutes: u64,
+    /// Days since the campaign started, up to its `ramp_days`.
+    pub day: u32,
+    pub days_remaining: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,206 +802,3484 @@ pub enum ThemeMode {
     Light,
 }
 
+/// What a reminder is about. Each kind has its own message pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReminderKind {
+    Posture,
+    Water,
+    EyeStrain,
+    /// Quick look-away prompts between regular reminders.
+    MicroBreak,
+}
+
+/// One step of a `queue_actions` sequence.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum QueuedAction {
+    Snooze { minutes: u64 },
+    Pause,
+    Resume,
+    Trigger,
+}
+
+/// `action`, `delay_secs` after the step before it (or after queueing, for
+/// the first).
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedAction {
+    pub delay_secs: u64,
+    pub action: QueuedAction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingAction {
+    pub at: DateTime<Utc>,
+    pub action: QueuedAction,
+}
+
+/// Kinds the engine fires on a timer of their own; the others only appear
+/// as messages.
+const SCHEDULED_KINDS: [ReminderKind; 2] = [ReminderKind::Posture, ReminderKind::MicroBreak];
+/// Kinds with no timer yet. Their messages, built-in and custom, are mixed
+/// into the posture reminder so they still come up.
+const UNSCHEDULED_KINDS: [ReminderKind; 2] = [ReminderKind::Water, ReminderKind::EyeStrain];
+
+/// `pending` in a stable order, for the snapshot.
+fn pending_skips(pending: &HashSet<ReminderKind>) -> Vec<ReminderKind> {
+    SCHEDULED_KINDS
+        .into_iter()
+        .filter(|kind| pending.contains(kind))
+        .collect()
+}
+
+impl ReminderKind {
+    /// Notification thread for `group_notifications`.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn thread_id(self) -> &'static str {
+        match self {
+            Self::Posture => "touchgrass.posture",
+            Self::Water => "touchgrass.water",
+            Self::EyeStrain => "touchgrass.eye-strain",
+            Self::MicroBreak => "touchgrass.micro-break",
+        }
+    }
+
+    /// The kinds whose messages a reminder of this kind draws from.
+    fn message_kinds(self) -> Vec<ReminderKind> {
+        match self {
+            Self::Posture => std::iter::once(self).chain(UNSCHEDULED_KINDS).collect(),
+            _ => vec![self],
+        }
+    }
+
+    /// The `weekend_mode` set, the same whatever the mood: weekend screen
+    /// time is often the fun kind, so these only suggest.
+    fn weekend_messages(self) -> &'static [&'static str] {
+        match self {
+            Self::Posture => &[
+                "Weekend check-in: maybe stretch between episodes?",
+                "No deadlines today. A lap around the room still feels nice.",
+                "Whatever you're into, your legs would enjoy a quick walk.",
+                "Good moment to step outside, if the weather's up for it.",
+                "Lazy weekend approved. A short stretch makes it even better.",
+            ],
+            Self::Water => &[
+                "Weekend hydration: grab something to drink.",
+                "A glass of water between rounds?",
+                "Top up your drink, no rush.",
+            ],
+            Self::EyeStrain => &[
+                "Give your eyes a weekend too: look out the window for a bit.",
+                "Rest your eyes for 20 seconds, then carry on.",
+                "Blink, look far away, back to the fun.",
+            ],
+            Self::MicroBreak => &[
+                "Quick breather, then back to it.",
+                "Roll your shoulders. That's all.",
+                "Thirty easy seconds off-screen.",
+            ],
+        }
+    }
+
+    fn builtin_messages(self, mood: Mood) -> &'static [&'static str] {
+        match (self, mood) {
+            (Self::Posture, Mood::Snarky) => &[
+                "Stand up before you photosynthesize.",
+                "Touch grass (nearby plant also counts).",
+                "Keyboard's hot, legs are not.",
+                "Break speedrun in 30s. Go.",
+                "Free DLC: posture.",
+                "Up. Now. Your chair has attachment issues.",
+                "Stand before you grow roots.",
+                "Walk away like the main character.",
+                "Your spine filed a ticket.",
+                "Walk. The chair will cope.",
+                "Your posture called HR.",
+                "Side quest: 30s breathing.",
+                "Keyboard is not a life partner.",
+                "AFK or AF-ache.",
+                "Load-bearing human requires maintenance.",
+            ],
+            (Self::Water, Mood::Snarky) => &[
+                "Hydrate or diedrate.",
+                "Your water bottle misses you.",
+                "Sip check. Yes, now.",
+                "Coffee is not a water substitute.",
+                "Refill quest unlocked.",
+            ],
+            (Self::EyeStrain, Mood::Snarky) => &[
+                "Blink like you mean it: 10x.",
+                "Blink or become a raisin.",
+                "Stare at something >20ft, not your soul.",
+                "20-20-20: 20ft away, 20 seconds.",
+                "Eyes off the pixels for a bit.",
+            ],
+            (Self::MicroBreak, Mood::Snarky) => &[
+                "Quick one: look out the window for 30 seconds.",
+                "Eyes up. Find the farthest thing in the room.",
+                "Soft focus, slow breath, 30 seconds.",
+                "Micro-break: roll your shoulders, look away.",
+                "Half a minute of not-screen. You've got this.",
+            ],
+            (Self::Posture, Mood::Kind) => &[
+                "You've been at it a while. A short stretch would feel good.",
+                "Nice focus! Treat yourself to a walk around the room.",
+                "Your body's been patient. Stand up and give it a moment.",
+                "A little movement now makes the next hour easier.",
+                "Proud of the work you're doing. Take a breather.",
+                "{breaks} breaks so far today. One more is a good idea.",
+            ],
+            (Self::Water, Mood::Kind) => &[
+                "A glass of water would do you good right now.",
+                "Time for a gentle sip. You deserve it.",
+                "Keep yourself topped up. Grab some water.",
+            ],
+            (Self::EyeStrain, Mood::Kind) => &[
+                "Give your eyes a rest. Look somewhere far away for a bit.",
+                "Your eyes work hard. Treat them to 20 seconds off-screen.",
+                "Soft gaze out the window. You've earned it.",
+            ],
+            (Self::MicroBreak, Mood::Kind) => &[
+                "A quick pause: breathe in, breathe out, look away.",
+                "Thirty gentle seconds away from the screen.",
+                "Relax your shoulders. You're doing great.",
+            ],
+            (Self::Posture, Mood::Neutral) => &[
+                "Time to stand up and stretch.",
+                "Break time: take a short walk.",
+                "Check your posture and move around for a minute.",
+                "Stand up for a few minutes.",
+            ],
+            (Self::Water, Mood::Neutral) => &[
+                "Time to drink some water.",
+                "Water break.",
+                "Refill your water.",
+            ],
+            (Self::EyeStrain, Mood::Neutral) => &[
+                "Look at something 20 feet away for 20 seconds.",
+                "Eye break: look away from the screen.",
+                "Blink a few times and rest your eyes.",
+            ],
+            (Self::MicroBreak, Mood::Neutral) => &[
+                "Micro-break: look away for 30 seconds.",
+                "Short pause: roll your shoulders.",
+                "Take 30 seconds away from the screen.",
+            ],
+        }
+    }
+}
+
+/// Accepts both the per-kind map and the old flat list, which becomes the
+/// posture pool.
+fn deserialize_custom_messages<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<ReminderKind, Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum CustomMessages {
+        Flat(Vec<String>),
+        PerKind(HashMap<ReminderKind, Vec<String>>),
+    }
+
+    Ok(match CustomMessages::deserialize(deserializer)? {
+        CustomMessages::Flat(messages) if messages.is_empty() => HashMap::new(),
+        CustomMessages::Flat(messages) => HashMap::from([(ReminderKind::Posture, messages)]),
+        CustomMessages::PerKind(map) => map,
+    })
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StatusSnapshot {
     pub paused: bool,
+    /// When a timed pause ends; `None` while running or paused for good.
+    pub paused_until: Option<DateTime<Utc>>,
     pub snoozed_until: Option<DateTime<Utc>>,
     pub next_trigger_at: Option<DateTime<Utc>>,
     pub last_notification_at: Option<DateTime<Utc>>,
     pub idle_seconds: Option<u64>,
+    pub phase: EnginePhase,
+    /// Snooze minutes left before the next break; `None` when uncapped.
+    pub snooze_budget_minutes: Option<u64>,
+    /// Snoozes since the last break; each escalating snooze is shorter.
+    pub snooze_escalation: u8,
+    pub screen_locked: bool,
+    pub lid_closed: bool,
+    /// Whether `busy_flag_path` exists; `pause_reason` only shows it when
+    /// nothing ranked higher is holding reminders too.
+    pub busy_flag: bool,
+    /// Why reminders are being held back automatically, apart from the
+    /// user's own pause.
+    pub pause_reason: Option<PauseReason>,
+    /// Schedule and stats keep running, but no reminder is shown.
+    pub silent_mode: bool,
+    /// `Preferences::strict_mode`: automatic holds are ignored.
+    pub strict_mode: bool,
+    /// The `pause_during_processes` entry currently holding reminders back.
+    pub blocking_app: Option<String>,
+    /// End of the running stretch sprint, if any.
+    pub sprint_until: Option<DateTime<Utc>>,
+    pub power_mode: PowerMode,
+    /// Active seconds counted toward the next reminder; `None` unless
+    /// `count_only_active_time` is on.
+    pub active_secs: Option<u64>,
+    /// Overlays left in the trailing hour; `None` unless `overlay_on_break`.
+    pub overlay_budget: Option<u8>,
+    /// The interval actually used for the next reminder, after any sprint
+    /// or `adaptive_interval` adjustment.
+    pub effective_interval_secs: u64,
+    /// Kinds whose next scheduled reminder will be dropped; see `skip_next`.
+    pub skip_next: Vec<ReminderKind>,
+    /// What `queue_actions` still has to do, soonest first.
+    pub queued_actions: Vec<PendingAction>,
+    /// What the next regular reminder will ask for; `None` unless
+    /// `desk_alternation` is on.
+    pub desk_posture: Option<DeskPosture>,
+    pub campaign: Option<CampaignProgress>,
+    /// The last reminder offered a long break; `complete_break` takes it.
+    pub long_break_offered: bool,
+    /// Running over RDP, SSH or xrdp, as of the last idle poll.
+    pub remote_session: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerMode {
+    Normal,
+    /// On battery with `eco_on_battery` set: slower idle polling.
+    Eco,
+}
+
+impl PowerMode {
+    fn idle_poll_interval(self) -> Duration {
+        Duration::from_secs(match self {
+            Self::Normal => IDLE_POLL_INTERVAL_SECS,
+            Self::Eco => ECO_IDLE_POLL_INTERVAL_SECS,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseReason {
+    ScreenLocked,
+    DisplayOff,
+    /// See `StatusSnapshot::blocking_app` for which one.
+    AppRunning,
+    BluetoothCall,
+    /// Today isn't one of the `reminder_days`.
+    DayOff,
+    /// The `busy_flag_path` file exists.
+    BusyFlag,
+    LidClosed,
+}
+
+fn pause_reason(
+    screen_locked: bool,
+    display_off: bool,
+    lid_closed: bool,
+    app_running: bool,
+    on_call: bool,
+    day_off: bool,
+    busy_flag: bool,
+) -> Option<PauseReason> {
+    if day_off {
+        Some(PauseReason::DayOff)
+    } else if busy_flag {
+        Some(PauseReason::BusyFlag)
+    } else if screen_locked {
+        Some(PauseReason::ScreenLocked)
+    } else if display_off {
+        Some(PauseReason::DisplayOff)
+    } else if lid_closed {
+        Some(PauseReason::LidClosed)
+    } else if app_running {
+        Some(PauseReason::AppRunning)
+    } else if on_call {
+        Some(PauseReason::BluetoothCall)
+    } else {
+        None
+    }
 }
 
 impl Default for StatusSnapshot {
     fn default() -> Self {
         Self {
             paused: false,
+            paused_until: None,
             snoozed_until: None,
             next_trigger_at: None,
             last_notification_at: None,
             idle_seconds: None,
+            phase: EnginePhase::Working,
+            snooze_budget_minutes: None,
+            snooze_escalation: 0,
+            screen_locked: false,
+            lid_closed: false,
+            busy_flag: false,
+            pause_reason: None,
+            silent_mode: false,
+            strict_mode: false,
+            blocking_app: None,
+            sprint_until: None,
+            power_mode: PowerMode::Normal,
+            active_secs: None,
+            overlay_budget: None,
+            effective_interval_secs: DEFAULT_INTERVAL_MINUTES * 60,
+            skip_next: Vec::new(),
+            queued_actions: Vec::new(),
+            desk_posture: None,
+            campaign: None,
+            long_break_offered: false,
+            remote_session: false,
         }
     }
 }
 
-#[derive(Clone, Serialize)]
+/// What the engine is doing right now, so the UI doesn't have to piece it
+/// together from `paused`, `snoozed_until`, and `idle_seconds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ReminderPayload {
-    pub message: String,
-    pub sound_enabled: bool,
+pub enum EnginePhase {
+    Working,
+    /// Away after a reminder fired, i.e. actually taking the break.
+    OnBreak,
+    Paused,
+    Snoozed,
+    /// Away without having been reminded.
+    Idle,
 }
 
-pub struct AppState {
-    preferences_path: PathBuf,
-    preferences: Mutex<Preferences>,
-    status: Arc<Mutex<StatusSnapshot>>,
-    control_tx: mpsc::Sender<ControlMessage>,
-    worker_handle: Mutex<Option<JoinHandle<()>>>,
+impl EnginePhase {
+    fn from_state(paused: bool, snoozed: bool, idle: bool, on_break: bool) -> Self {
+        if paused {
+            Self::Paused
+        } else if snoozed {
+            Self::Snoozed
+        } else if idle && on_break {
+            Self::OnBreak
+        } else if idle {
+            Self::Idle
+        } else {
+            Self::Working
+        }
+    }
 }
 
-impl AppState {
-    pub fn initialize(app: &AppHandle<Wry>) -> Result<Arc<Self>, AppStateError> {
-        let config_dir = app.path().app_config_dir()?;
-        fs::create_dir_all(&config_dir)?;
-        let preferences_path = config_dir.join(PREFERENCES_FILE);
-        let preferences = load_preferences(&preferences_path)?;
-
-        let status = Arc::new(Mutex::new(StatusSnapshot::default()));
-
-        let (control_tx, control_rx) = mpsc::channel(16);
-        let state = Arc::new(Self {
-            preferences_path,
-            preferences: Mutex::new(preferences.clone()),
-            status: status.clone(),
-            control_tx,
-            worker_handle: Mutex::new(None),
-        });
-
-        let app_handle = app.clone();
+/// What the user asked for about reminders: a pause, a snooze, or neither.
+/// One value rather than separate flags, so a pause always wins over a
+/// snooze and resuming never brings back a stale one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Suppression {
+    #[default]
+    Active,
+    PausedIndefinite,
+    PausedUntil(DateTime<Utc>),
+    SnoozedUntil(DateTime<Utc>),
+}
 
-        let handle = async_runtime::spawn(async move {
-            run_engine(app_handle, status, preferences, control_rx).await;
-        });
+impl Suppression {
+    fn from_pause(paused: bool) -> Self {
+        if paused {
+            Self::PausedIndefinite
+        } else {
+            Self::Active
+        }
+    }
 
-        *state.worker_handle.lock().unwrap() = Some(handle);
+    fn is_paused(self) -> bool {
+        matches!(self, Self::PausedIndefinite | Self::PausedUntil(_))
+    }
 
-        Ok(state)
+    fn paused_until(self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::PausedUntil(until) => Some(until),
+            _ => None,
+        }
     }
 
-    pub fn preferences(&self) -> Preferences {
-        self.preferences.lock().unwrap().clone()
+    fn snoozed_until(self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::SnoozedUntil(until) => Some(until),
+            _ => None,
+        }
     }
 
-    pub fn status(&self) -> StatusSnapshot {
-        self.status.lock().unwrap().clone()
+    fn is_snoozed(self, now: DateTime<Utc>) -> bool {
+        self.snoozed_until().is_some_and(|until| now < until)
     }
 
-    pub async fn update_preferences(
-        &self,
-        app: &AppHandle<Wry>,
-        update: PreferencesUpdate,
-    ) -> Result<Preferences, AppStateError> {
-        let mut prefs = self.preferences.lock().unwrap().clone();
+    /// Whether a reminder due at `now` is held back. A timed pause holds
+    /// until `expire_pause` ends it, so it's never half over.
+    fn holds(self, now: DateTime<Utc>) -> bool {
+        self.is_paused() || self.is_snoozed(now)
+    }
 
-        if let Some(interval) = update.interval_minutes {
-            prefs.interval_minutes = interval.clamp(2, 240);
-        }
-        if let Some(activity_detection) = update.activity_detection {
-            prefs.activity_detection = activity_detection;
-        }
-        if let Some(sound_enabled) = update.sound_enabled {
-            prefs.sound_enabled = sound_enabled;
+    /// Snoozes until `until`, unless paused; a snooze can't end a pause.
+    /// Returns whether it took.
+    fn snooze(&mut self, until: DateTime<Utc>) -> bool {
+        if self.is_paused() {
+            return false;
         }
-        if let Some(autostart) = update.autostart_enabled {
-            prefs.autostart_enabled = autostart;
-        }
-        if let Some(theme) = update.theme.clone() {
-            prefs.theme = theme;
-        }
-        if let Some(threshold) = update.idle_threshold_minutes {
-            prefs.idle_threshold_minutes = clamp_idle_threshold_minutes(threshold);
-        }
-
-        save_preferences(&self.preferences_path, &prefs)?;
+        *self = Self::SnoozedUntil(until);
+        true
+    }
 
-        {
-            let mut guard = self.preferences.lock().unwrap();
-            *guard = prefs.clone();
+    /// Drops a snooze, leaving any pause alone.
+    fn clear_snooze(&mut self) {
+        if let Self::SnoozedUntil(_) = self {
+            *self = Self::Active;
         }
+    }
 
-        self.control_tx
-            .send(ControlMessage::PreferencesUpdated(prefs.clone()))
-            .await
-            .ok();
-
-        if let Some(autostart) = update.autostart_enabled {
-            apply_autostart(app, autostart);
+    /// Ends a timed pause that's over by `now`. Returns whether it did.
+    fn expire_pause(&mut self, now: DateTime<Utc>) -> bool {
+        match *self {
+            Self::PausedUntil(until) if until <= now => {
+                *self = Self::Active;
+                true
+            }
+            _ => false,
         }
-
-        Ok(prefs)
     }
 
-    pub async fn set_pause(&self, paused: bool) {
-        let _ = self.control_tx.send(ControlMessage::Pause(paused)).await;
+    fn publish(self, snapshot: &mut StatusSnapshot) {
+        snapshot.paused = self.is_paused();
+        snapshot.paused_until = self.paused_until();
+        snapshot.snoozed_until = self.snoozed_until();
     }
+}
 
-    pub async fn snooze(&self, duration_minutes: u64) {
-        let duration = Duration::from_secs(duration_minutes.max(1) * 60);
-        let _ = self.control_tx.send(ControlMessage::Snooze(duration)).await;
-    }
+/// The engine loop's locals as of its last pass, published for
+/// `debug_dump_state`. Timers are kept as deadlines and turned into
+/// remaining seconds when dumped.
+#[derive(Debug, Clone)]
+struct EngineInternals {
+    suppression: Suppression,
+    snoozed_minutes: u64,
+    next_instant: Instant,
+    was_idle: bool,
+    last_idle_secs: Option<u64>,
+    on_break: bool,
+    awaiting_return: bool,
+    screen_locked: bool,
+    display_off: bool,
+    lid_closed: bool,
+    on_call: bool,
+    busy_flag: bool,
+    silent_mode: bool,
+    last_suppression: Option<SuppressionRecord>,
+    micro_due_at: Option<DateTime<Utc>>,
+    phase: EnginePhase,
+    /// Interval in effect, sprint included.
+    interval: Duration,
+    sprint: Option<Sprint>,
+    adaptive_scale: f64,
+    oneoff_at: Option<Instant>,
+    summary_at: Option<Instant>,
+    micro_break_at: Option<Instant>,
+}
 
-    pub async fn clear_snooze(&self) {
-        let _ = self.control_tx.send(ControlMessage::ClearSnooze).await;
+impl Default for EngineInternals {
+    fn default() -> Self {
+        Self {
+            suppression: Suppression::Active,
+            snoozed_minutes: 0,
+            next_instant: Instant::now(),
+            was_idle: false,
+            last_idle_secs: None,
+            on_break: false,
+            awaiting_return: false,
+            screen_locked: false,
+            display_off: false,
+            lid_closed: false,
+            on_call: false,
+            busy_flag: false,
+            silent_mode: false,
+            last_suppression: None,
+            micro_due_at: None,
+            phase: EnginePhase::Working,
+            interval: Preferences::default().interval_duration(),
+            sprint: None,
+            adaptive_scale: 1.0,
+            oneoff_at: None,
+            summary_at: None,
+            micro_break_at: None,
+        }
     }
+}
 
-    pub async fn skip_current_break(&self) {
-        let _ = self.control_tx.send(ControlMessage::SkipCurrent).await;
-    }
+/// Seconds until each armed engine timer fires; `None` when not armed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineTimers {
+    pub reminder_secs: u64,
+    pub oneoff_secs: Option<u64>,
+    pub summary_secs: Option<u64>,
+    pub micro_break_secs: Option<u64>,
+}
 
-    pub async fn trigger_preview(&self) {
-        let _ = self.control_tx.send(ControlMessage::TriggerNow).await;
-    }
+/// A projected reminder for the agenda view.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingReminder {
+    pub at: DateTime<Utc>,
+    pub kind: ReminderKind,
+    /// Scheduled with `schedule_oneoff` rather than by the interval.
+    pub one_off: bool,
 }
 
-impl Drop for AppState {
-    fn drop(&mut self) {
-        if let Some(handle) = self.worker_handle.lock().unwrap().take() {
-            handle.abort();
+/// Everything the engine is tracking, for pasting into bug reports.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineDump {
+    pub captured_at: DateTime<Utc>,
+    pub paused: bool,
+    pub paused_until: Option<DateTime<Utc>>,
+    pub snoozed_until: Option<DateTime<Utc>>,
+    pub snoozed_minutes: u64,
+    pub was_idle: bool,
+    pub last_idle_secs: Option<u64>,
+    pub on_break: bool,
+    pub awaiting_return: bool,
+    pub screen_locked: bool,
+    pub display_off: bool,
+    pub lid_closed: bool,
+    pub on_call: bool,
+    pub busy_flag: bool,
+    pub silent_mode: bool,
+    pub phase: EnginePhase,
+    pub timers: EngineTimers,
+}
+
+impl EngineInternals {
+    fn dump(&self) -> EngineDump {
+        let now = Instant::now();
+        let remaining = |at: Instant| at.saturating_duration_since(now).as_secs();
+        EngineDump {
+            captured_at: Utc::now(),
+            paused: self.suppression.is_paused(),
+            paused_until: self.suppression.paused_until(),
+            snoozed_until: self.suppression.snoozed_until(),
+            snoozed_minutes: self.snoozed_minutes,
+            was_idle: self.was_idle,
+            last_idle_secs: self.last_idle_secs,
+            on_break: self.on_break,
+            awaiting_return: self.awaiting_return,
+            screen_locked: self.screen_locked,
+            display_off: self.display_off,
+            lid_closed: self.lid_closed,
+            on_call: self.on_call,
+            busy_flag: self.busy_flag,
+            silent_mode: self.silent_mode,
+            phase: self.phase,
+            timers: EngineTimers {
+                reminder_secs: remaining(self.next_instant),
+                oneoff_secs: self.oneoff_at.map(remaining),
+                summary_secs: self.summary_at.map(remaining),
+                micro_break_secs: self.micro_break_at.map(remaining),
+            },
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// Automatic suppression conditions that can be set aside for a while with
+/// `AppState::override_suppression`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct PreferencesUpdate {
-    pub interval_minutes: Option<u64>,
-    pub activity_detection: Option<bool>,
-    pub sound_enabled: Option<bool>,
-    pub autostart_enabled: Option<bool>,
-    pub theme: Option<ThemeMode>,
-    pub idle_threshold_minutes: Option<u64>,
+pub enum SuppressionKind {
+    ScreenLocked,
+    DisplayOff,
+    LidClosed,
+    AppRunning,
+    BluetoothCall,
+    BusyFlag,
+    /// Lets reminders through on a day outside `reminder_days`.
+    DayOff,
 }
 
-enum ControlMessage {
-    PreferencesUpdated(Preferences),
-    Pause(bool),
-    Snooze(Duration),
-    ClearSnooze,
-    SkipCurrent,
-    TriggerNow,
+/// A suppression condition that's ignored until `until`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuppressionOverride {
+    pub kind: SuppressionKind,
+    pub until: DateTime<Utc>,
 }
 
-fn load_preferences(path: &Path) -> Result<Preferences, AppStateError> {
-    if !path.exists() {
-        return Ok(Preferences::default());
-    }
+type SuppressionOverrides = HashMap<SuppressionKind, DateTime<Utc>>;
 
-    let contents = fs::read_to_string(path)?;
-    match serde_json::from_str::<Preferences>(&contents) {
-        Ok(prefs) => Ok(prefs),
-        Err(err) => {
-            eprintln!("TouchGrass: preferences.json was invalid ({err}); restoring defaults.");
-            backup_corrupt_preferences(path);
-            let defaults = Preferences::default();
-            save_preferences(path, &defaults)?;
-            Ok(defaults)
-        }
-    }
+fn is_overridden(
+    overrides: &Mutex<SuppressionOverrides>,
+    kind: SuppressionKind,
+    now: DateTime<Utc>,
+) -> bool {
+    overridden_at(&overrides.lock().unwrap(), kind, now)
 }
 
-fn save_preferences(path: &Path, prefs: &Preferences) -> Result<(), AppStateError> {
-    let file = File::create(path)?;
-    serde_json::to_writer_pretty(file, prefs)?;
-    Ok(())
+fn overridden_at(
+    overrides: &SuppressionOverrides,
+    kind: SuppressionKind,
+    at: DateTime<Utc>,
+) -> bool {
+    overrides.get(&kind).is_some_and(|until| *until > at)
 }
 
-fn backup_corrupt_preferences(path: &Path) {
-    let mut backup_path = path.with_extension("json.corrupt");
-    if backup_path.exists() {
-        let mut counter = 1;
-        loop {
-            let candidate = path.with_extension(format!("json.corrupt.{counter}"));
-            if !candidate.exists() {
+/// The engine's view of everything that can hold a scheduled reminder back.
+#[derive(Debug, Clone, Copy)]
+struct Holds {
+    paused: bool,
+    awaiting_return: bool,
+    screen_locked: bool,
+    display_off: bool,
+    lid_closed: bool,
+    app_running: bool,
+    on_call: bool,
+    busy_flag: bool,
+    day_off: bool,
+}
+
+/// Why a scheduled reminder due now is held back, or `None` when it can go
+/// out. Pause and return confirmation outrank `strict_mode`; every other
+/// hold is ignored by it, and the ones that can be overridden are skipped
+/// while `overridden` says so.
+fn hold_reason(
+    prefs: &Preferences,
+    holds: Holds,
+    overridden: impl Fn(SuppressionKind) -> bool,
+) -> Option<SuppressedBy> {
+    if holds.paused {
+        Some(SuppressedBy::Paused)
+    } else if holds.awaiting_return {
+        Some(SuppressedBy::AwaitingReturn)
+    } else if prefs.strict_mode {
+        None
+    } else if holds.screen_locked && !overridden(SuppressionKind::ScreenLocked) {
+        // Nobody to nudge while the screen is locked.
+        Some(SuppressedBy::ScreenLocked)
+    } else if holds.display_off && !overridden(SuppressionKind::DisplayOff) {
+        Some(SuppressedBy::DisplayOff)
+    } else if holds.lid_closed && !overridden(SuppressionKind::LidClosed) {
+        Some(SuppressedBy::LidClosed)
+    } else if holds.app_running && !overridden(SuppressionKind::AppRunning) {
+        Some(SuppressedBy::AppRunning)
+    } else if holds.on_call && !overridden(SuppressionKind::BluetoothCall) {
+        Some(SuppressedBy::BluetoothCall)
+    } else if holds.busy_flag && !overridden(SuppressionKind::BusyFlag) {
+        Some(SuppressedBy::BusyFlag)
+    } else if holds.day_off && !overridden(SuppressionKind::DayOff) {
+        Some(SuppressedBy::DayOff)
+    } else {
+        None
+    }
+}
+
+/// Which suppression conditions would stop a reminder from firing right now.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuppressionStatus {
+    pub suppressed: bool,
+    /// Only `paused` and `snoozed` count toward `suppressed` while set.
+    pub strict_mode: bool,
+    pub paused: bool,
+    pub snoozed: bool,
+    pub idle: bool,
+    pub screen_locked: bool,
+    pub display_off: bool,
+    pub lid_closed: bool,
+    pub blocking_app: Option<String>,
+    pub bluetooth_call: bool,
+    pub busy_flag: bool,
+    /// Not one of `reminder_days`.
+    pub day_off: bool,
+}
+
+/// What `SuppressionStatus` would say at `at`, given `status` as the engine
+/// last published it. Everything tied to the clock is worked out for `at`:
+/// whether a snooze or timed pause would have run out, which overrides would
+/// still hold, and whether `at` falls on a day off. What the machine reports
+/// (idle time, lock, lid, running apps, calls, the busy flag) stays as it is
+/// now, since there's no knowing it ahead of time.
+pub fn evaluate_suppression(
+    prefs: &Preferences,
+    status: &StatusSnapshot,
+    overrides: &SuppressionOverrides,
+    at: DateTime<Utc>,
+) -> SuppressionStatus {
+    let overridden = |kind| overridden_at(overrides, kind, at);
+    let paused = status.paused && status.paused_until.is_none_or(|until| at < until);
+    let snoozed = status.snoozed_until.is_some_and(|until| at < until);
+    let idle = status
+        .idle_seconds
+        .map(|secs| is_idle(prefs, secs))
+        .unwrap_or(false);
+
+    let screen_locked = status.screen_locked && !overridden(SuppressionKind::ScreenLocked);
+    let display_off = status.pause_reason == Some(PauseReason::DisplayOff)
+        && !overridden(SuppressionKind::DisplayOff);
+
+    let blocking_app = status
+        .blocking_app
+        .clone()
+        .filter(|_| !overridden(SuppressionKind::AppRunning));
+    let bluetooth_call = status.pause_reason == Some(PauseReason::BluetoothCall)
+        && !overridden(SuppressionKind::BluetoothCall);
+    let busy_flag = status.busy_flag && !overridden(SuppressionKind::BusyFlag);
+    let lid_closed = status.lid_closed && !overridden(SuppressionKind::LidClosed);
+    let day_off = !prefs.is_reminder_day(at.with_timezone(&Local).date_naive())
+        && !overridden(SuppressionKind::DayOff);
+
+    let held = idle
+        || screen_locked
+        || display_off
+        || lid_closed
+        || blocking_app.is_some()
+        || bluetooth_call
+        || busy_flag
+        || day_off;
+
+    SuppressionStatus {
+        suppressed: paused || snoozed || (held && !prefs.strict_mode),
+        strict_mode: prefs.strict_mode,
+        paused,
+        snoozed,
+        idle,
+        screen_locked,
+        display_off,
+        lid_closed,
+        blocking_app,
+        bluetooth_call,
+        busy_flag,
+        day_off,
+    }
+}
+
+/// What kept a scheduled reminder from being shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuppressedBy {
+    Paused,
+    /// A break was still waiting for the user to confirm they're back.
+    AwaitingReturn,
+    ScreenLocked,
+    DisplayOff,
+    LidClosed,
+    AppRunning,
+    BluetoothCall,
+    DayOff,
+    BusyFlag,
+    /// Away from the keyboard, or not yet confirmed back.
+    Idle,
+    SilentMode,
+    /// Dropped by `skip_next`.
+    SkippedNext,
+}
+
+/// The latest scheduled reminder that was dropped, with the values that
+/// decided it, so it can be explained afterwards.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuppressionRecord {
+    /// When the reminder was due.
+    pub at: DateTime<Utc>,
+    pub reason: SuppressedBy,
+    /// For `Idle`: how long the user had been away, as last measured.
+    pub idle_secs: Option<u64>,
+    /// For `Idle`: how long counts as away.
+    pub away_threshold_secs: Option<u64>,
+    /// For `AppRunning`: the `pause_during_processes` entry that was running.
+    pub blocking_app: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationPath {
+    LinuxActions,
+    OsToast,
+    /// The break overlay, shown instead of a notification.
+    Overlay,
+}
+
+/// Whether a reminder the app handed off was actually seen, as far as the
+/// desktop lets on. Starts out `Unknown` (or `Suppressed`) and is updated
+/// when the desktop reports back: the Linux close reason or action, and
+/// macOS's delivery callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeliveryStatus {
+    /// The desktop confirmed it: delivered on macOS, or on Linux clicked,
+    /// dismissed or timed out on screen. The break overlay always counts.
+    Presented,
+    /// Handed off while Do Not Disturb (or the like) was on and nothing has
+    /// come back since, so it most likely went straight to the list.
+    Suppressed,
+    /// Every notification path failed; that's on the app, not the desktop.
+    Failed,
+    /// Handed off, but the desktop hasn't said whether it was shown.
+    Unknown,
+}
+
+/// One pass through `send_reminder_message`, kept so "did it even try?"
+/// has an answer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationAttempt {
+    /// Lets a late delivery report find its attempt.
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    pub kind: ReminderKind,
+    pub message: String,
+    /// `None` when no icon file was found and the theme icon name was used.
+    pub icon_path: Option<String>,
+    /// Which path showed the notification; `None` if every path failed.
+    pub handled_by: Option<NotificationPath>,
+    pub delivery: DeliveryStatus,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckOutcome {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+/// Result of trying one notification mechanism during a diagnosis.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationCheck {
+    pub mechanism: &'static str,
+    pub outcome: CheckOutcome,
+    pub detail: String,
+}
+
+impl NotificationCheck {
+    fn new(mechanism: &'static str, outcome: CheckOutcome, detail: impl Into<String>) -> Self {
+        Self {
+            mechanism,
+            outcome,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// One subsystem checked by `AppState::self_test`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+    pub detail: String,
+}
+
+impl SelfTestCheck {
+    fn new(name: &'static str, outcome: CheckOutcome, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            outcome,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    pub ran_at: DateTime<Utc>,
+    /// No check failed; skipped ones don't count against it.
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+/// What's wrong with a file or directory `verify_config` looked at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigProblem {
+    Missing,
+    NotADirectory,
+    PermissionDenied,
+    /// There and readable, but not valid JSON of the expected shape.
+    Corrupt,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigEntry {
+    pub path: PathBuf,
+    /// `None` when it's fine.
+    pub problem: Option<ConfigProblem>,
+    pub detail: String,
+}
+
+impl ConfigEntry {
+    fn new(path: &Path, problem: Option<ConfigProblem>, detail: impl Into<String>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            problem,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// The config directory and the files TouchGrass keeps in it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReport {
+    pub checked_at: DateTime<Utc>,
+    pub healthy: bool,
+    pub entries: Vec<ConfigEntry>,
+    /// What `repair_config` changed; empty from `verify_config`.
+    pub repairs: Vec<String>,
+}
+
+/// A reminder scheduled once for a specific time. One-offs live for the
+/// current session only and are not restored after a restart.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OneOffReminder {
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    pub message: Option<String>,
+}
+
+/// What `catch_up` does with reminders whose time passed without them
+/// firing, typically while the machine slept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CatchUpMode {
+    /// One reminder for everything overdue, then the usual schedule. Held
+    /// back by the same things as a scheduled reminder.
+    FireOnce,
+    /// Start the reminder and micro-break schedules over from now without
+    /// showing anything. Overdue one-offs still go out.
+    #[default]
+    Reschedule,
+    /// Leave the timers alone; they fire whenever they get to it.
+    Ignore,
+}
+
+/// A schedule `catch_up` looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CatchUpChannel {
+    Reminder,
+    MicroBreak,
+    OneOff,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatchUpReport {
+    pub mode: CatchUpMode,
+    /// Everything that was overdue, whatever `mode` then did with it.
+    pub overdue: Vec<CatchUpChannel>,
+    /// Whether a reminder was shown for them.
+    pub fired: bool,
+}
+
+/// A point in the day `snooze_to_boundary` can snooze until, each ending at
+/// its anchor time from preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnoozeBoundary {
+    Afternoon,
+    Evening,
+    TomorrowMorning,
+}
+
+impl std::str::FromStr for SnoozeBoundary {
+    type Err = AppStateError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim() {
+            "afternoon" => Ok(Self::Afternoon),
+            "evening" => Ok(Self::Evening),
+            "tomorrow_morning" => Ok(Self::TomorrowMorning),
+            _ => Err(AppStateError::UnknownSnoozeBoundary(name.to_string())),
+        }
+    }
+}
+
+impl SnoozeBoundary {
+    /// When the snooze ends: the next time the anchor comes round, except
+    /// that "tomorrow morning" always means tomorrow, even before today's.
+    fn resolve(self, prefs: &Preferences, now: DateTime<Local>) -> DateTime<Utc> {
+        let anchor = match self {
+            Self::Afternoon => prefs.snooze_afternoon_at,
+            Self::Evening => prefs.snooze_evening_at,
+            Self::TomorrowMorning => prefs.snooze_morning_at,
+        };
+        let at = next_local_occurrence(anchor, now);
+        if self == Self::TomorrowMorning
+            && at.with_timezone(&Local).date_naive() == now.date_naive()
+        {
+            return next_local_occurrence(anchor, at.with_timezone(&Local));
+        }
+        at
+    }
+}
+
+/// Something that changed the interval on its way from the preference to
+/// what the engine uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntervalModifier {
+    /// Today's step of a running campaign.
+    Campaign,
+    /// `weekend_interval_minutes`, on a weekend with `weekend_mode` on.
+    Weekend,
+    /// `adaptive_interval`, from how much of the last hour was spent away.
+    Adaptive,
+    /// A running sprint, which replaces everything before it.
+    Sprint,
+    /// `post_break_cooldown_minutes`, on the reminder right after a break.
+    PostBreakCooldown,
+    /// `snap_to_clock`, rounding the reminder up to the next mark.
+    SnapToClock,
+    /// `reminder_days`, moving a reminder off a day off.
+    DaysOff,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalStep {
+    pub modifier: IntervalModifier,
+    /// The interval once this step applied; for the cooldown, snapping and
+    /// days off, the wait until the reminder instead.
+    pub interval_secs: u64,
+    /// How much longer (or, negative, shorter) this step made it.
+    pub change_secs: i64,
+}
+
+/// How the interval in effect came about, for working out why reminders
+/// come when they do.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveInterval {
+    /// `interval_minutes` or `interval_seconds` as configured.
+    pub base_secs: u64,
+    /// Each modifier that changed the interval, in the order applied, then
+    /// whatever snapping and days off would do to a reminder scheduled now.
+    pub steps: Vec<IntervalStep>,
+    pub interval_secs: u64,
+    /// `snap_to_clock`, which rounds when the next reminder fires rather
+    /// than changing the interval.
+    pub snap_to_clock_minutes: Option<u64>,
+    /// Added once, to the reminder right after a completed break.
+    pub post_break_cooldown_minutes: Option<u64>,
+    pub next_trigger_at: Option<DateTime<Utc>>,
+}
+
+/// How long the engine has been running, and how many times it was started.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineUptime {
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: u64,
+    /// More than one means the engine was restarted since launch.
+    pub spawn_count: u32,
+}
+
+/// Platform features that actually work on this machine, so the UI can hide
+/// settings that would do nothing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub idle_detection: bool,
+    pub idle_backend: IdleBackend,
+    /// Snooze and skip buttons on the notification itself.
+    pub notification_actions: bool,
+    pub screen_lock_detection: bool,
+    pub display_power_detection: bool,
+    /// Needed for `eco_on_battery`.
+    pub power_source_detection: bool,
+    pub haptics: bool,
+    /// Needed for `pause_during_processes`.
+    pub process_detection: bool,
+    /// Needed for `suppress_on_bluetooth_call`.
+    pub bluetooth_call_detection: bool,
+}
+
+impl Capabilities {
+    /// Asks each subsystem once. Some of these talk to D-Bus or the display
+    /// server, so keep it off the async runtime.
+    fn probe(idle_detector: &IdleDetector) -> Self {
+        Self {
+            idle_detection: idle_detector.get_idle_time().is_ok(),
+            idle_backend: idle_detector.backend(),
+            notification_actions: cfg!(target_os = "linux"),
+            screen_lock_detection: screen_lock::is_screen_locked().is_some(),
+            display_power_detection: display_power::is_display_off().is_some(),
+            power_source_detection: power_source::is_on_battery().is_some(),
+            haptics: haptics::supported(),
+            process_detection: sysinfo::IS_SUPPORTED_SYSTEM,
+            bluetooth_call_detection: bluetooth_call::is_on_call().is_some(),
+        }
+    }
+}
+
+/// A live idle reading next to the thresholds it's judged against.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleProbe {
+    /// `false` when `activity_detection` is off; nothing is read then.
+    pub enabled: bool,
+    pub idle_seconds: Option<u64>,
+    pub threshold_secs: u64,
+    /// The detector counts the reading as idle, before any grace.
+    pub past_threshold: bool,
+    /// The threshold plus `idle_grace_secs`, which is what counts as away.
+    pub away_threshold_secs: u64,
+    pub is_idle: bool,
+    pub backend: IdleBackend,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderPayload {
+    pub message: String,
+    pub kind: ReminderKind,
+    pub sound_enabled: bool,
+    /// File to play; `None` plays the bundled chime.
+    pub sound_path: Option<String>,
+    /// Set on `desk_alternation` reminders.
+    pub posture: Option<DeskPosture>,
+    /// Offers a longer break after repeated snoozes; accepting it goes
+    /// through `complete_break`.
+    pub suggest_long_break: bool,
+}
+
+pub struct AppState {
+    /// `None` when the config directory couldn't be set up; preferences then
+    /// live in memory only for this session.
+    preferences_path: Option<PathBuf>,
+    /// What's in effect: `file_preferences` with any `TOUCHGRASS_*`
+    /// environment overrides on top.
+    preferences: Mutex<Preferences>,
+    /// What `preferences.json` holds. Only this is ever saved, so an
+    /// environment variable never ends up in the file.
+    file_preferences: Mutex<Preferences>,
+    status: Arc<Mutex<StatusSnapshot>>,
+    control_tx: mpsc::Sender<ControlMessage>,
+    worker_handle: Mutex<Option<JoinHandle<()>>>,
+    snooze_history: Mutex<VecDeque<u64>>,
+    oneoffs: Arc<Mutex<Vec<OneOffReminder>>>,
+    suppression_overrides: Arc<Mutex<SuppressionOverrides>>,
+    next_oneoff_id: AtomicU64,
+    next_attempt_id: AtomicU64,
+    stats: Arc<Stats>,
+    /// Id of the last Linux notification of each kind, reused as the
+    /// replace-id so a new reminder updates the previous toast in place
+    /// instead of stacking, and a micro-break never replaces a break.
+    #[cfg(target_os = "linux")]
+    last_notification_ids: Mutex<HashMap<ReminderKind, u32>>,
+    notification_failures: AtomicU32,
+    notification_history: Mutex<VecDeque<NotificationAttempt>>,
+    engine_internals: Arc<Mutex<EngineInternals>>,
+    /// Shared with the engine so commands can read idle time between polls.
+    idle_detector: Arc<IdleDetector>,
+    /// The reminder `skip_break` last skipped, by when it was shown.
+    last_skipped_reminder: Mutex<Option<DateTime<Utc>>>,
+    /// End of the last snooze started by `snooze_default`, covering the gap
+    /// before the engine reports it in the status.
+    default_snooze_until: Mutex<Option<DateTime<Utc>>>,
+    /// Mirrors `Preferences::minimize_to_tray` for the synchronous window
+    /// event handler.
+    minimize_to_tray: AtomicBool,
+    /// Cleared when the tray icon couldn't be created, so windows stay
+    /// reachable instead of hiding into nothing.
+    tray_available: AtomicBool,
+    /// The running `queue_actions` sequence, if any.
+    action_queue: Mutex<Option<JoinHandle<()>>>,
+    sound_cache: SoundCache,
+    /// Probed once shortly after startup; see `capabilities`.
+    capabilities: OnceLock<Capabilities>,
+    /// When the current engine task was spawned.
+    engine_started: Mutex<(DateTime<Utc>, Instant)>,
+    engine_spawns: AtomicU32,
+    /// When each debounced action was last let through.
+    last_actions: Mutex<HashMap<ControlAction, Instant>>,
+    /// Serializes preference changes from commands, the tray, and reloads.
+    preferences_transaction: tokio::sync::Mutex<()>,
+    /// Picks up hand edits to `preferences.json`; dropping it stops watching.
+    _preferences_watcher: Option<RecommendedWatcher>,
+}
+
+impl AppState {
+    pub fn initialize(
+        app: &AppHandle<Wry>,
+        launched_at_login: bool,
+    ) -> Result<Arc<Self>, AppStateError> {
+        let config_dir = match resolve_config_dir(app) {
+            Ok(dir) => Some(dir),
+            Err(err) => {
+                eprintln!(
+                    "TouchGrass: config directory unavailable ({err}); preferences and stats won't be saved this session."
+                );
+                None
+            }
+        };
+        let preferences_path = config_dir.as_ref().map(|dir| dir.join(PREFERENCES_FILE));
+        let file_preferences = match &preferences_path {
+            Some(path) => load_preferences(path)?,
+            None => Preferences::default(),
+        };
+        let preferences = env_prefs::apply(file_preferences.clone());
+
+        let status = Arc::new(Mutex::new(StatusSnapshot::default()));
+        let oneoffs = Arc::new(Mutex::new(Vec::new()));
+        let suppression_overrides = Arc::new(Mutex::new(HashMap::new()));
+        let engine_internals = Arc::new(Mutex::new(EngineInternals::default()));
+        let idle_detector = Arc::new(IdleDetector::new(preferences.idle_threshold_secs()));
+        let stats = Arc::new(match &config_dir {
+            Some(dir) => Stats::load(dir.join(stats::STATS_FILE)),
+            None => Stats::in_memory(),
+        });
+
+        let status_feed = StatusFeed::new();
+        status_feed.configure(app, preferences.ws_port);
+        app.manage(status_feed);
+
+        let http_trigger = HttpTrigger::new();
+        http_trigger.configure(
+            app,
+            preferences.http_trigger_port,
+            preferences.http_trigger_token.as_deref(),
+        );
+        app.manage(http_trigger);
+
+        let preferences_watcher = preferences_path
+            .as_deref()
+            .and_then(|path| prefs_watch::watch(app, path));
+
+        let (control_tx, control_rx) = mpsc::channel(16);
+        let state = Arc::new(Self {
+            preferences_path,
+            preferences: Mutex::new(preferences.clone()),
+            file_preferences: Mutex::new(file_preferences),
+            status: status.clone(),
+            control_tx,
+            worker_handle: Mutex::new(None),
+            snooze_history: Mutex::new(VecDeque::with_capacity(SNOOZE_HISTORY_LEN)),
+            oneoffs: oneoffs.clone(),
+            suppression_overrides: suppression_overrides.clone(),
+            next_oneoff_id: AtomicU64::new(1),
+            next_attempt_id: AtomicU64::new(1),
+            stats: stats.clone(),
+            #[cfg(target_os = "linux")]
+            last_notification_ids: Mutex::new(HashMap::new()),
+            notification_failures: AtomicU32::new(0),
+            notification_history: Mutex::new(VecDeque::with_capacity(NOTIFICATION_HISTORY_LEN)),
+            engine_internals: engine_internals.clone(),
+            idle_detector: idle_detector.clone(),
+            last_skipped_reminder: Mutex::new(None),
+            default_snooze_until: Mutex::new(None),
+            minimize_to_tray: AtomicBool::new(preferences.minimize_to_tray),
+            tray_available: AtomicBool::new(true),
+            action_queue: Mutex::new(None),
+            sound_cache: SoundCache::new(),
+            preferences_transaction: tokio::sync::Mutex::new(()),
+            last_actions: Mutex::new(HashMap::new()),
+            capabilities: OnceLock::new(),
+            engine_started: Mutex::new((Utc::now(), Instant::now())),
+            engine_spawns: AtomicU32::new(0),
+            _preferences_watcher: preferences_watcher,
+        });
+
+        let app_handle = app.clone();
+
+        *state.engine_started.lock().unwrap() = (Utc::now(), Instant::now());
+        state.engine_spawns.fetch_add(1, Ordering::SeqCst);
+        let handle = async_runtime::spawn(async move {
+            run_engine(
+                app_handle,
+                status,
+                engine_internals,
+                oneoffs,
+                suppression_overrides,
+                stats,
+                idle_detector,
+                preferences,
+                launched_at_login,
+                control_rx,
+            )
+            .await;
+        });
+
+        *state.worker_handle.lock().unwrap() = Some(handle);
+
+        let probing = state.clone();
+        async_runtime::spawn_blocking(move || {
+            probing.capabilities();
+        });
+
+        Ok(state)
+    }
+
+    pub fn preferences(&self) -> Preferences {
+        self.preferences.lock().unwrap().clone()
+    }
+
+    pub fn status(&self) -> StatusSnapshot {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// The engine's internals as of its last pass through the loop.
+    pub fn debug_dump_state(&self) -> Result<EngineDump, AppStateError> {
+        if !self.preferences().debug_tools {
+            return Err(AppStateError::DebugToolsDisabled);
+        }
+        Ok(self.engine_internals.lock().unwrap().dump())
+    }
+
+    /// Idle time read right now rather than at the last poll; `None` with
+    /// activity detection off or when it can't be read.
+    pub fn current_idle_seconds(&self) -> Option<u64> {
+        if !self.preferences().activity_detection {
+            return None;
+        }
+        self.idle_detector.get_idle_time().ok()
+    }
+
+    /// What this machine supports. Blocks for the probe if it hasn't
+    /// finished yet.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+            .get_or_init(|| Capabilities::probe(&self.idle_detector))
+            .clone()
+    }
+
+    pub fn engine_uptime(&self) -> EngineUptime {
+        let (started_at, started) = *self.engine_started.lock().unwrap();
+        EngineUptime {
+            started_at,
+            uptime_secs: started.elapsed().as_secs(),
+            spawn_count: self.engine_spawns.load(Ordering::SeqCst),
+        }
+    }
+
+    pub fn idle_probe(&self) -> IdleProbe {
+        let prefs = self.preferences();
+        let idle_seconds = self.current_idle_seconds();
+        IdleProbe {
+            enabled: prefs.activity_detection,
+            idle_seconds,
+            threshold_secs: self.idle_detector.threshold_secs(),
+            past_threshold: idle_seconds
+                .is_some_and(|secs| self.idle_detector.is_past_threshold(secs)),
+            away_threshold_secs: prefs.away_threshold_secs(),
+            is_idle: idle_seconds.is_some_and(|secs| is_idle(&prefs, secs)),
+            backend: self.idle_detector.backend(),
+        }
+    }
+
+    pub fn wayland_health(&self) -> WaylandHealth {
+        self.idle_detector.wayland_health()
+    }
+
+    /// Opens or closes the focus bar to match `focus_bar_enabled`.
+    pub fn sync_focus_bar(&self, app: &AppHandle<Wry>, enabled: bool) {
+        let result = if enabled {
+            focus_bar::show(app, self.stats.focus_bar_position())
+        } else {
+            focus_bar::hide(app)
+        };
+        if let Err(err) = result {
+            let _ = app.emit(
+                events::LOG_EVENT,
+                events::LogPayload {
+                    level: "error".into(),
+                    message: format!("focus bar update failed: {err}"),
+                },
+            );
+        }
+    }
+
+    /// Turns `focus_bar_enabled` on or off, which opens or closes the bar.
+    pub async fn set_focus_bar(
+        &self,
+        app: &AppHandle<Wry>,
+        enabled: bool,
+    ) -> Result<Preferences, AppStateError> {
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                focus_bar_enabled: Some(enabled),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Turns `strict_mode` on or off.
+    pub async fn set_strict_mode(
+        &self,
+        app: &AppHandle<Wry>,
+        enabled: bool,
+    ) -> Result<Preferences, AppStateError> {
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                strict_mode: Some(enabled),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    pub fn set_focus_bar_position(&self, position: FocusBarPosition) {
+        self.stats.set_focus_bar_position(position);
+    }
+
+    /// Whether minimizing should hide the window; never without a tray to
+    /// bring it back from.
+    pub fn minimize_to_tray(&self) -> bool {
+        self.tray_available() && self.minimize_to_tray.load(Ordering::Relaxed)
+    }
+
+    pub fn tray_available(&self) -> bool {
+        self.tray_available.load(Ordering::Relaxed)
+    }
+
+    pub fn mark_tray_unavailable(&self) {
+        self.tray_available.store(false, Ordering::Relaxed);
+    }
+
+    /// `format_next` for the current status.
+    pub fn next_reminder_label(&self) -> String {
+        tray::format_next(&self.status(), Utc::now())
+    }
+
+    pub fn engine_phase(&self) -> EnginePhase {
+        self.status.lock().unwrap().phase
+    }
+
+    pub fn today_stats(&self) -> DayStats {
+        self.stats.today()
+    }
+
+    pub fn idle_histogram(&self, date: NaiveDate) -> Vec<(u64, u64)> {
+        self.stats.idle_histogram(date)
+    }
+
+    /// An interval suggestion from the last week of stats.
+    pub fn suggest_interval(&self) -> IntervalSuggestion {
+        let days = self.stats.recent_days(stats::SUGGESTION_WINDOW_DAYS);
+        let current_minutes = (self.preferences().interval_duration().as_secs() / 60).max(1);
+        stats::suggest_interval(&days, current_minutes)
+    }
+
+    /// This week against the one before, from the stats file.
+    pub fn weekly_report(&self) -> WeeklyReport {
+        let today = stats::today();
+        let start = today
+            .checked_sub_days(Days::new(2 * stats::REPORT_WEEK_DAYS - 1))
+            .unwrap_or(NaiveDate::MIN);
+        let days = self.stats.days_between(start, today);
+        stats::weekly_report(&days, today, self.preferences().mini_break_weight)
+    }
+
+    /// Completion rate for each hour of the day, over all the stats kept.
+    pub fn effectiveness_by_hour(&self) -> Vec<HourEffectiveness> {
+        let days = self.stats.days_between(NaiveDate::MIN, stats::today());
+        stats::effectiveness_by_hour(
+            days.iter().map(|(_, day)| day),
+            self.preferences().mini_break_weight,
+        )
+    }
+
+    pub fn today_completion_rate(&self) -> Option<f64> {
+        self.stats
+            .today()
+            .completion_rate(self.preferences().mini_break_weight)
+    }
+
+    pub fn suppression_status(&self) -> SuppressionStatus {
+        self.suppression_at(Utc::now())
+    }
+
+    /// `suppression_status` as if it were `at`, for previewing schedules;
+    /// see `evaluate_suppression` for what moves with the time.
+    pub fn evaluate_at(&self, at: DateTime<Utc>) -> Result<SuppressionStatus, AppStateError> {
+        if !self.preferences().debug_tools {
+            return Err(AppStateError::DebugToolsDisabled);
+        }
+        Ok(self.suppression_at(at))
+    }
+
+    fn suppression_at(&self, at: DateTime<Utc>) -> SuppressionStatus {
+        let overrides = self.suppression_overrides.lock().unwrap().clone();
+        evaluate_suppression(&self.preferences(), &self.status(), &overrides, at)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn last_notification_id(&self, kind: ReminderKind) -> Option<u32> {
+        self.last_notification_ids
+            .lock()
+            .unwrap()
+            .get(&kind)
+            .copied()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set_last_notification_id(&self, kind: ReminderKind, id: u32) {
+        self.last_notification_ids.lock().unwrap().insert(kind, id);
+    }
+
+    pub async fn update_preferences(
+        &self,
+        app: &AppHandle<Wry>,
+        update: PreferencesUpdate,
+    ) -> Result<Preferences, AppStateError> {
+        // Held until the result is applied, so concurrent updates can't base
+        // themselves on the same snapshot and drop each other's changes.
+        let _transaction = self.preferences_transaction.lock().await;
+        let current = self.file_preferences.lock().unwrap().clone();
+        let prefs = merge_preferences(current.clone(), &current, update)?;
+        let prefs = env_prefs::keep_overridden(&current, &self.preferences(), prefs)
+            .map_err(AppStateError::EnvOverridden)?;
+
+        if let Some(path) = &self.preferences_path {
+            save_preferences(path, &prefs)?;
+        }
+
+        *self.file_preferences.lock().unwrap() = prefs.clone();
+        self.apply_preferences(app, env_prefs::apply(prefs)).await
+    }
+
+    /// Re-reads `preferences.json` and applies it, for edits made outside the
+    /// app. A file that doesn't parse or holds an invalid value is left alone
+    /// and reported, rather than reset to defaults like at startup; the
+    /// preferences in effect stay as they were.
+    pub async fn reload_preferences(
+        &self,
+        app: &AppHandle<Wry>,
+    ) -> Result<Preferences, AppStateError> {
+        let _transaction = self.preferences_transaction.lock().await;
+        let Some(path) = &self.preferences_path else {
+            return Ok(self.preferences());
+        };
+        let contents = fs::read_to_string(path)?;
+        let edited: Preferences = serde_json::from_str(&contents)?;
+        // Replayed as an update so a hand edit gets the same clamps and
+        // checks as one made in the app; anything invalid keeps what's
+        // current.
+        let update: PreferencesUpdate = serde_json::from_value(serde_json::to_value(&edited)?)?;
+        let current = self.file_preferences.lock().unwrap().clone();
+        let file_prefs = merge_preferences(edited, &current, update)?;
+        let prefs = env_prefs::apply(file_prefs.clone());
+        *self.file_preferences.lock().unwrap() = file_prefs;
+        // Our own saves land here too; nothing to do for those.
+        if serde_json::to_value(&prefs)? == serde_json::to_value(self.preferences())? {
+            return Ok(prefs);
+        }
+        self.apply_preferences(app, prefs).await
+    }
+
+    /// Makes `prefs` current: hands it to the engine and brings the tray,
+    /// hotkey, autostart, and local servers in line with it.
+    async fn apply_preferences(
+        &self,
+        app: &AppHandle<Wry>,
+        prefs: Preferences,
+    ) -> Result<Preferences, AppStateError> {
+        let previous = {
+            let mut guard = self.preferences.lock().unwrap();
+            std::mem::replace(&mut *guard, prefs.clone())
+        };
+        let changes = diff_preferences(&previous, &prefs);
+        if !changes.is_empty() {
+            let summary: Vec<String> = changes.iter().map(ToString::to_string).collect();
+            let _ = app.emit(
+                events::LOG_EVENT,
+                events::LogPayload {
+                    level: "info".into(),
+                    message: format!("preferences changed: {}", summary.join(", ")),
+                },
+            );
+        }
+        self.minimize_to_tray
+            .store(prefs.minimize_to_tray, Ordering::Relaxed);
+        if prefs.idle_threshold_minutes != previous.idle_threshold_minutes {
+            self.idle_detector
+                .set_threshold(prefs.idle_threshold_secs());
+        }
+
+        self.control_tx
+            .send(ControlMessage::PreferencesUpdated(Box::new(prefs.clone())))
+            .await
+            .ok();
+
+        if prefs.autostart_enabled != previous.autostart_enabled {
+            apply_autostart(app, prefs.autostart_enabled);
+        }
+
+        if let Some(status_feed) = app.try_state::<StatusFeed>() {
+            status_feed.configure(app, prefs.ws_port);
+        }
+        if let Some(http_trigger) = app.try_state::<HttpTrigger>() {
+            http_trigger.configure(
+                app,
+                prefs.http_trigger_port,
+                prefs.http_trigger_token.as_deref(),
+            );
+        }
+
+        if prefs.tray_labels != previous.tray_labels {
+            if let Err(err) = tray::rebuild_menu(app, &prefs.tray_labels, &self.status()) {
+                let _ = app.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "error".into(),
+                        message: format!("tray menu rebuild failed: {err}"),
+                    },
+                );
+            }
+        }
+
+        if prefs.panic_hotkey != previous.panic_hotkey {
+            shortcuts::apply_panic_hotkey(app, prefs.panic_hotkey.as_deref());
+        }
+
+        if prefs.focus_bar_enabled != previous.focus_bar_enabled {
+            self.sync_focus_bar(app, prefs.focus_bar_enabled);
+        }
+
+        if prefs.tray_tooltip_template != previous.tray_tooltip_template {
+            if let Some(tray_state) = app.try_state::<TrayState>() {
+                tray_state.sync(&self.status());
+            }
+        }
+
+        Ok(prefs)
+    }
+
+    /// Appends one message per non-empty line of a UTF-8 text file to
+    /// `kind`'s custom messages, skipping duplicates. Returns how many were added.
+    pub async fn import_messages_from_file(
+        &self,
+        app: &AppHandle<Wry>,
+        path: &Path,
+        kind: ReminderKind,
+    ) -> Result<usize, AppStateError> {
+        let size = fs::metadata(path)?.len();
+        if size > MAX_MESSAGE_IMPORT_BYTES {
+            return Err(AppStateError::ImportTooLarge(size));
+        }
+
+        let contents = fs::read_to_string(path)?;
+        let mut messages = self
+            .preferences()
+            .custom_messages
+            .remove(&kind)
+            .unwrap_or_default();
+        let before = messages.len();
+        messages.extend(contents.lines().map(str::to_string));
+        let messages = normalize_messages(messages);
+        let imported = messages.len().saturating_sub(before);
+
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                custom_messages: Some(HashMap::from([(kind, messages)])),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(imported)
+    }
+
+    /// Sets the interval that fits about `count` breaks into a workday,
+    /// returning the interval in minutes. Only the end of the workday is
+    /// configurable, so the day is taken to be `WORKDAY_MINUTES` long.
+    pub async fn set_breaks_per_day(
+        &self,
+        app: &AppHandle<Wry>,
+        count: u32,
+    ) -> Result<u64, AppStateError> {
+        let minutes = (WORKDAY_MINUTES as f64 / count.max(1) as f64).round() as u64;
+        let prefs = self
+            .update_preferences(
+                app,
+                PreferencesUpdate {
+                    interval_minutes: Some(minutes),
+                    interval_seconds: Some(None),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(prefs.interval_minutes)
+    }
+
+    /// Sets how long without input counts as being away, returning the
+    /// clamped value in minutes. The idle backend picks it up immediately.
+    pub async fn set_idle_threshold(
+        &self,
+        app: &AppHandle<Wry>,
+        minutes: u64,
+    ) -> Result<u64, AppStateError> {
+        let prefs = self
+            .update_preferences(
+                app,
+                PreferencesUpdate {
+                    idle_threshold_minutes: Some(minutes),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(prefs.idle_threshold_minutes)
+    }
+
+    /// Starts a campaign from today, replacing any running one.
+    pub async fn start_campaign(
+        &self,
+        app: &AppHandle<Wry>,
+        config: CampaignConfig,
+    ) -> Result<Preferences, AppStateError> {
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                campaign: Some(Some(config)),
+                campaign_started: Some(Some(stats::today())),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Ends the campaign; `interval_minutes` applies again.
+    pub async fn stop_campaign(&self, app: &AppHandle<Wry>) -> Result<Preferences, AppStateError> {
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                campaign: Some(None),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Switches the tone of the built-in messages.
+    pub async fn set_mood(
+        &self,
+        app: &AppHandle<Wry>,
+        mood: Mood,
+    ) -> Result<Preferences, AppStateError> {
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                mood: Some(mood),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Most recent reminder delivery attempts, oldest first.
+    pub fn notification_history(&self) -> Vec<NotificationAttempt> {
+        self.notification_history
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// The interval the engine is using and how each modifier got it there.
+    pub fn effective_interval(&self) -> EffectiveInterval {
+        let prefs = self.preferences();
+        let (sprint, scale) = {
+            let internals = self.engine_internals.lock().unwrap();
+            (internals.sprint, internals.adaptive_scale)
+        };
+        let resolution = resolve_interval(&prefs, sprint, scale, Local::now(), false);
+        EffectiveInterval {
+            base_secs: resolution.base.as_secs(),
+            steps: resolution.steps,
+            interval_secs: resolution.interval.as_secs(),
+            snap_to_clock_minutes: prefs.snap_to_clock.filter(|minutes| *minutes > 0),
+            post_break_cooldown_minutes: prefs.post_break_cooldown_minutes,
+            next_trigger_at: self.status().next_trigger_at,
+        }
+    }
+
+    /// The latest scheduled reminder that was held back, and why. `None`
+    /// until one is; cleared on restart.
+    pub fn last_suppression(&self) -> Option<SuppressionRecord> {
+        self.engine_internals
+            .lock()
+            .unwrap()
+            .last_suppression
+            .clone()
+    }
+
+    /// How the most recent posture reminder fared; `Unknown` before the
+    /// first one. Micro-breaks and the other kinds don't count.
+    pub fn last_delivery_status(&self) -> DeliveryStatus {
+        self.notification_history
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|attempt| attempt.kind == ReminderKind::Posture)
+            .map_or(DeliveryStatus::Unknown, |attempt| attempt.delivery)
+    }
+
+    fn next_attempt_id(&self) -> u64 {
+        self.next_attempt_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Records what the desktop said about attempt `id` once it says it.
+    /// A failed attempt stays failed, and one that has left the history is
+    /// simply gone.
+    #[cfg(target_os = "linux")]
+    fn set_delivery(&self, id: u64, delivery: DeliveryStatus) {
+        let mut history = self.notification_history.lock().unwrap();
+        if let Some(attempt) = history
+            .iter_mut()
+            .find(|attempt| attempt.id == id && attempt.delivery != DeliveryStatus::Failed)
+        {
+            attempt.delivery = delivery;
+        }
+    }
+
+    pub fn clear_notification_history(&self) {
+        self.notification_history.lock().unwrap().clear();
+    }
+
+    fn push_notification_attempt(&self, attempt: NotificationAttempt) {
+        let mut history = self.notification_history.lock().unwrap();
+        if history.len() == NOTIFICATION_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(attempt);
+    }
+
+    /// Shows a sample reminder with its action buttons, returning the button
+    /// labels it got. Pressing them only logs which one was pressed. Only
+    /// Linux notifications carry buttons; elsewhere a plain toast is shown
+    /// and no labels come back.
+    pub fn preview_notification(&self, app: &AppHandle<Wry>) -> Result<Vec<String>, AppStateError> {
+        const MESSAGE: &str = "TouchGrass preview - try the buttons, nothing will change.";
+
+        let prefs = self.preferences();
+        let icon_path = find_notification_icon(app).unwrap_or_else(|| "touchgrass".to_string());
+
+        #[cfg(target_os = "linux")]
+        {
+            show_linux_notification_with_actions(
+                app,
+                MESSAGE,
+                &icon_path,
+                &prefs,
+                ReminderKind::Posture,
+                None,
+                0,
+            )
+            .map_err(|err| AppStateError::Notification(err.to_string()))
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = prefs;
+            app.notification()
+                .builder()
+                .title("TouchGrass")
+                .body(MESSAGE)
+                .icon(icon_path)
+                .show()
+                .map_err(|err| AppStateError::Notification(err.to_string()))?;
+            Ok(Vec::new())
+        }
+    }
+
+    /// Checks each subsystem once, for "is anything broken?" after an update
+    /// or before filing a bug. Only probes: nothing is shown, and
+    /// `preferences.json` itself isn't touched.
+    pub fn self_test(&self, app: &AppHandle<Wry>) -> SelfTestReport {
+        let checks = vec![
+            check_config_round_trip(self.preferences_path.as_deref(), &self.preferences()),
+            check_idle_detector(&self.idle_detector),
+            check_notification_permission(app),
+            check_tray(app, self.tray_available()),
+            check_autostart(app),
+        ];
+        SelfTestReport {
+            ran_at: Utc::now(),
+            passed: !checks
+                .iter()
+                .any(|check| matches!(check.outcome, CheckOutcome::Failed)),
+            checks,
+        }
+    }
+
+    /// Checks that the config directory, `preferences.json`, and
+    /// `stats.json` can all be read and written, for "my settings won't
+    /// save". A file that doesn't exist yet is fine; defaults apply.
+    pub fn verify_config(&self, app: &AppHandle<Wry>) -> Result<ConfigReport, AppStateError> {
+        let dir = self.config_dir(app)?;
+        Ok(config_report(&dir, Vec::new()))
+    }
+
+    /// Fixes what `verify_config` finds where it safely can: creates a
+    /// missing directory, moves broken files aside as `*.corrupt`, and
+    /// writes the settings and stats as they are now in their place.
+    /// Returns the report from afterwards, with anything that still failed
+    /// listed among the repairs.
+    pub async fn repair_config(&self, app: &AppHandle<Wry>) -> Result<ConfigReport, AppStateError> {
+        let _transaction = self.preferences_transaction.lock().await;
+        let dir = self.config_dir(app)?;
+        let mut repairs = Vec::new();
+
+        if check_config_dir(&dir).problem == Some(ConfigProblem::Missing) {
+            fs::create_dir_all(&dir)?;
+            repairs.push(format!("created {}", dir.display()));
+        }
+        if self.preferences_path.is_none() {
+            repairs.push(
+                "the config directory was unavailable at startup; restart TouchGrass to save there"
+                    .to_string(),
+            );
+        }
+
+        let preferences = dir.join(PREFERENCES_FILE);
+        if let Some(problem) = check_preferences_file(&preferences).problem {
+            if problem != ConfigProblem::Missing {
+                if let Some(backup) = backup_corrupt_file(&preferences) {
+                    repairs.push(format!("moved the old preferences to {}", backup.display()));
+                }
+            }
+            let saved = self.file_preferences.lock().unwrap().clone();
+            repairs.push(match save_preferences(&preferences, &saved) {
+                Ok(()) => "saved the current preferences".to_string(),
+                Err(err) => format!("couldn't save the preferences: {err}"),
+            });
+        }
+
+        let stats_path = dir.join(stats::STATS_FILE);
+        if let Some(problem) = check_stats_file(&stats_path).problem {
+            if problem != ConfigProblem::Missing {
+                if let Some(backup) = backup_corrupt_file(&stats_path) {
+                    repairs.push(format!("moved the old stats to {}", backup.display()));
+                }
+            }
+            if self.stats.path().is_some() {
+                repairs.push(match self.stats.save() {
+                    Ok(()) => "saved the current stats".to_string(),
+                    Err(err) => format!("couldn't save the stats: {err}"),
+                });
+            }
+        }
+
+        Ok(config_report(&dir, repairs))
+    }
+
+    fn config_dir(&self, app: &AppHandle<Wry>) -> Result<PathBuf, AppStateError> {
+        match self.preferences_path.as_deref().and_then(Path::parent) {
+            Some(dir) => Ok(dir.to_path_buf()),
+            None => Ok(app.path().app_config_dir()?),
+        }
+    }
+
+    /// Runs each notification path in turn and reports what worked, for
+    /// "I never get reminders" support cases. Talks to the notification
+    /// backends directly, so the engine and timer never see it.
+    pub async fn diagnose_notifications(&self, app: &AppHandle<Wry>) -> Vec<NotificationCheck> {
+        const MESSAGE: &str = "TouchGrass notification test - you can ignore this.";
+        const STEP_DELAY: Duration = Duration::from_millis(1500);
+
+        let prefs = self.preferences();
+        let mut checks = Vec::new();
+
+        let icon_path = match find_notification_icon(app) {
+            Some(path) => {
+                checks.push(NotificationCheck::new(
+                    "icon",
+                    CheckOutcome::Ok,
+                    path.clone(),
+                ));
+                path
+            }
+            None => {
+                checks.push(NotificationCheck::new(
+                    "icon",
+                    CheckOutcome::Failed,
+                    "no icons/128x128.png found; notifications use the 'touchgrass' theme icon",
+                ));
+                "touchgrass".to_string()
+            }
+        };
+
+        checks.push(match app.notification().permission_state() {
+            Ok(state) => {
+                NotificationCheck::new("permission", CheckOutcome::Ok, format!("{state:?}"))
+            }
+            Err(err) => NotificationCheck::new("permission", CheckOutcome::Failed, err.to_string()),
+        });
+
+        let toast = app
+            .notification()
+            .builder()
+            .title("TouchGrass")
+            .body(MESSAGE)
+            .icon(icon_path.clone())
+            .show();
+        checks.push(match toast {
+            Ok(()) => NotificationCheck::new("osToast", CheckOutcome::Ok, "shown"),
+            Err(err) => NotificationCheck::new("osToast", CheckOutcome::Failed, err.to_string()),
+        });
+
+        #[cfg(target_os = "linux")]
+        {
+            tokio::time::sleep(STEP_DELAY).await;
+            // No state handle, so pressing the test's buttons does nothing.
+            let actions = show_linux_notification_with_actions(
+                app,
+                MESSAGE,
+                &icon_path,
+                &prefs,
+                ReminderKind::Posture,
+                None,
+                0,
+            );
+            checks.push(match actions {
+                Ok(_) => NotificationCheck::new(
+                    "linuxActions",
+                    CheckOutcome::Ok,
+                    "shown with action buttons",
+                ),
+                Err(err) => {
+                    NotificationCheck::new("linuxActions", CheckOutcome::Failed, err.to_string())
+                }
+            });
+        }
+        #[cfg(not(target_os = "linux"))]
+        checks.push(NotificationCheck::new(
+            "linuxActions",
+            CheckOutcome::Skipped,
+            "only used on Linux",
+        ));
+
+        tokio::time::sleep(STEP_DELAY).await;
+        // Sound is played by the UI when it receives the reminder event.
+        checks.push(if !prefs.sound_enabled {
+            NotificationCheck::new("sound", CheckOutcome::Skipped, "sound is turned off")
+        } else {
+            match app.emit(
+                events::REMINDER_EVENT,
+                ReminderPayload {
+                    message: MESSAGE.to_string(),
+                    kind: ReminderKind::Posture,
+                    sound_enabled: true,
+                    sound_path: prefs.sound_for(ReminderKind::Posture).map(str::to_string),
+                    posture: None,
+                    suggest_long_break: false,
+                },
+            ) {
+                Ok(()) => NotificationCheck::new(
+                    "sound",
+                    CheckOutcome::Ok,
+                    "reminder event sent to the UI",
+                ),
+                Err(err) => NotificationCheck::new("sound", CheckOutcome::Failed, err.to_string()),
+            }
+        });
+
+        checks.push(if prefs.overlay_on_break {
+            NotificationCheck::new(
+                "overlay",
+                CheckOutcome::Ok,
+                "breaks use the overlay; try preview_overlay to see it",
+            )
+        } else {
+            NotificationCheck::new(
+                "overlay",
+                CheckOutcome::Skipped,
+                "break overlay is turned off",
+            )
+        });
+
+        checks
+    }
+
+    /// Sets the interval from a typed duration such as `"1h30m"` or `"45"`
+    /// (plain numbers are minutes), then applies it like any other update.
+    pub async fn set_interval_from_string(
+        &self,
+        app: &AppHandle<Wry>,
+        input: &str,
+    ) -> Result<Preferences, AppStateError> {
+        let minutes = parse_duration_minutes(input)
+            .ok_or_else(|| AppStateError::InvalidDuration(input.to_string()))?;
+        self.update_preferences(
+            app,
+            PreferencesUpdate {
+                interval_minutes: Some(minutes),
+                // A typed interval should win over a leftover seconds override.
+                interval_seconds: Some(None),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Gets everything off screen at once: closes secondary windows, dismisses
+    /// the current notification, and hides the main window.
+    pub fn panic_hide(&self, app: &AppHandle<Wry>) {
+        for (label, window) in app.webview_windows() {
+            if label != "main" {
+                let _ = window.close();
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let ids: Vec<u32> = self
+                .last_notification_ids
+                .lock()
+                .unwrap()
+                .drain()
+                .map(|(_, id)| id)
+                .collect();
+            if !ids.is_empty() {
+                async_runtime::spawn_blocking(move || {
+                    for id in ids {
+                        if let Err(err) = close_linux_notification(id) {
+                            eprintln!("TouchGrass: failed to dismiss notification {id}: {err}");
+                        }
+                    }
+                });
+            }
+        }
+
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+
+    pub async fn set_pause(&self, paused: bool) {
+        let _ = self.control_tx.send(ControlMessage::Pause(paused)).await;
+    }
+
+    /// Pauses until `until`, then starts the interval over from there.
+    /// Replaces a pause or snooze that's already running.
+    pub async fn pause_until(&self, until: DateTime<Utc>) -> Result<(), AppStateError> {
+        if until <= Utc::now() {
+            return Err(AppStateError::PauseInPast);
+        }
+        let _ = self
+            .control_tx
+            .send(ControlMessage::PauseUntil(until))
+            .await;
+        Ok(())
+    }
+
+    /// Reminds every `interval_minutes` for the next `duration_minutes`, then
+    /// goes back to the configured interval. Kept apart from the preferences,
+    /// so saving settings mid-sprint doesn't end it.
+    pub async fn start_sprint(&self, interval_minutes: u64, duration_minutes: u64) {
+        let sprint = Sprint {
+            interval: Duration::from_secs(interval_minutes.clamp(1, 60) * 60),
+            until: Utc::now() + chrono::Duration::minutes(duration_minutes.clamp(5, 240) as i64),
+        };
+        let _ = self
+            .control_tx
+            .send(ControlMessage::Sprint(Some(sprint)))
+            .await;
+    }
+
+    pub async fn end_sprint(&self) {
+        let _ = self.control_tx.send(ControlMessage::Sprint(None)).await;
+    }
+
+    /// Unlike pausing, silent mode keeps the countdown and idle tracking
+    /// going and only drops the reminders themselves.
+    pub async fn set_silent_mode(&self, enabled: bool) {
+        let _ = self
+            .control_tx
+            .send(ControlMessage::SilentMode(enabled))
+            .await;
+    }
+
+    /// Returns `false` when the snooze budget is spent; the engine then fires
+    /// the reminder instead of snoozing.
+    pub async fn snooze(&self, duration_minutes: u64) -> bool {
+        let allowed = self.status().snooze_budget_minutes != Some(0);
+        // A repeat of the snooze that just went through is already in
+        // effect, so it reports the same as that one did.
+        if !self.accept_action(ControlAction::Snooze(duration_minutes.max(1))) {
+            return allowed;
+        }
+        if allowed {
+            let mut history = self.snooze_history.lock().unwrap();
+            if history.len() == SNOOZE_HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(duration_minutes.max(1));
+        }
+
+        let duration = Duration::from_secs(duration_minutes.max(1) * 60);
+        let _ = self.control_tx.send(ControlMessage::Snooze(duration)).await;
+        allowed
+    }
+
+    /// Snoozes until the meeting under way ends, counting the ones right
+    /// after it as part of the same block, but never past
+    /// `MAX_CALENDAR_SNOOZE_MINUTES`. Returns when the snooze ends, or `None`
+    /// without snoozing when the calendar shows nothing going on now.
+    pub async fn snooze_until_free(&self) -> Result<Option<DateTime<Utc>>, AppStateError> {
+        let path = self
+            .preferences()
+            .calendar_ics_path
+            .ok_or(AppStateError::NoCalendar)?;
+        let now = Utc::now();
+        let Some(free_at) = calendar::busy_until(Path::new(&path), now)? else {
+            return Ok(None);
+        };
+        let minutes = ((free_at - now).num_seconds().max(0) as u64)
+            .div_ceil(60)
+            .clamp(1, MAX_CALENDAR_SNOOZE_MINUTES);
+        self.snooze(minutes).await;
+        Ok(Some(now + chrono::Duration::minutes(minutes as i64)))
+    }
+
+    /// Snoozes until the boundary's anchor time. Returns when the snooze
+    /// ends, or `None` while paused.
+    pub async fn snooze_to_boundary(&self, boundary: SnoozeBoundary) -> Option<DateTime<Utc>> {
+        let until = boundary.resolve(&self.preferences(), Local::now());
+        self.snooze_until(until).await.then_some(until)
+    }
+
+    /// Snoozes until exactly `until`. Unlike `snooze` it stays out of the
+    /// snooze history, budget and escalation: putting reminders off until
+    /// the evening is a plan for the day, not a run of "not yet"s. Returns
+    /// `false` while paused, since a snooze can't end a pause.
+    pub async fn snooze_until(&self, until: DateTime<Utc>) -> bool {
+        if self.status().paused {
+            return false;
+        }
+        let _ = self
+            .control_tx
+            .send(ControlMessage::SnoozeUntil(until))
+            .await;
+        true
+    }
+
+    /// `snooze`, noting why in the stats. A blank reason is a plain snooze.
+    pub async fn snooze_with_reason(&self, duration_minutes: u64, reason: &str) -> bool {
+        let allowed = self.snooze(duration_minutes).await;
+        let reason: String = reason
+            .trim()
+            .chars()
+            .take(MAX_SNOOZE_REASON_CHARS)
+            .collect();
+        if allowed && !reason.is_empty() {
+            self.stats
+                .record_snooze_reason(duration_minutes.max(1), reason);
+        }
+        allowed
+    }
+
+    pub fn snooze_reasons(&self) -> Vec<SnoozeReason> {
+        self.stats.snooze_reasons()
+    }
+
+    /// Snooze length for the one-click "smart" snooze, based on the local
+    /// time of day and the lengths of recent snoozes this session.
+    pub fn smart_default_snooze(&self) -> u64 {
+        let history: Vec<u64> = self
+            .snooze_history
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .collect();
+        smart_snooze_minutes(Local::now().hour(), &history)
+    }
+
+    pub async fn clear_snooze(&self) {
+        let _ = self.control_tx.send(ControlMessage::ClearSnooze).await;
+    }
+
+    /// Acknowledges the reminder with a short in-place break: the streak
+    /// survives and the timer restarts, but it's counted as a mini break.
+    pub async fn mini_break(&self) {
+        let _ = self.control_tx.send(ControlMessage::MiniBreak).await;
+    }
+
+    /// Counts a break taken without a reminder, like a walk or lunch. It
+    /// keeps the streak going and restarts the timer as a real break would.
+    pub async fn log_manual_break(
+        &self,
+        duration_minutes: u64,
+        note: Option<String>,
+    ) -> Result<(), AppStateError> {
+        if !(1..=MAX_MANUAL_BREAK_MINUTES).contains(&duration_minutes) {
+            return Err(AppStateError::InvalidBreakDuration(duration_minutes));
+        }
+        let note = note
+            .map(|note| {
+                note.trim()
+                    .chars()
+                    .take(MAX_BREAK_NOTE_CHARS)
+                    .collect::<String>()
+            })
+            .filter(|note| !note.is_empty());
+        let _ = self
+            .control_tx
+            .send(ControlMessage::ManualBreak(duration_minutes, note))
+            .await;
+        Ok(())
+    }
+
+    /// Makes the next login launch quit straight away, once, without
+    /// touching `autostart_enabled`.
+    pub fn skip_next_autostart(&self) -> Result<(), AppStateError> {
+        let dir = self
+            .preferences_path
+            .as_deref()
+            .and_then(Path::parent)
+            .ok_or(AppStateError::NoConfigDir)?;
+        File::create(dir.join(SKIP_AUTOSTART_MARKER))?;
+        Ok(())
+    }
+
+    /// Deletes recorded stats and this session's snooze and notification
+    /// history. The engine does the stats part between reminders, so one
+    /// firing meanwhile can't bring back the old counters.
+    pub async fn clear_stats(&self) {
+        self.snooze_history.lock().unwrap().clear();
+        self.clear_notification_history();
+        let _ = self.control_tx.send(ControlMessage::ClearStats).await;
+    }
+
+    /// The user's "I'm back" for a break held by `require_return_confirmation`,
+    /// or their yes to a long break the last reminder offered.
+    pub async fn complete_break(&self) {
+        let _ = self.control_tx.send(ControlMessage::CompleteBreak).await;
+    }
+
+    /// Skips the reminder that's showing. Further calls for the same reminder
+    /// do nothing, so key repeat can't rack up skips. Returns whether
+    /// anything was skipped.
+    pub async fn skip_break(&self) -> bool {
+        let shown = self.status().last_notification_at;
+        {
+            let mut skipped = self.last_skipped_reminder.lock().unwrap();
+            if shown.is_none() || *skipped == shown {
+                return false;
+            }
+            *skipped = shown;
+        }
+        if !self.accept_action(ControlAction::Skip) {
+            return false;
+        }
+        let _ = self.control_tx.send(ControlMessage::SkipCurrent).await;
+        true
+    }
+
+    /// Current values for message placeholders. While a reminder is going
+    /// out the engine hasn't rescheduled yet, so a deadline that's already
+    /// due is replaced by one interval from now.
+    pub fn message_context(&self) -> MessageContext {
+        self.message_context_for(&self.status())
+    }
+
+    /// `message_context` for a snapshot already in hand.
+    pub fn message_context_for(&self, status: &StatusSnapshot) -> MessageContext {
+        let today = self.stats.today();
+        let now = Utc::now();
+        let next = match status.next_trigger_at {
+            _ if status.paused => None,
+            Some(at) if at > now => Some(at),
+            _ => chrono::Duration::from_std(Duration::from_secs(status.effective_interval_secs))
+                .ok()
+                .map(|interval| now + interval),
+        };
+        MessageContext {
+            now: now.with_timezone(&Local),
+            breaks_today: today.breaks_taken + today.mini_breaks,
+            streak: today.current_streak,
+            next: next.map(|at| at.with_timezone(&Local)),
+        }
+    }
+
+    pub fn tray_tooltip_template(&self) -> Option<String> {
+        self.preferences
+            .lock()
+            .unwrap()
+            .tray_tooltip_template
+            .clone()
+    }
+
+    /// Sets the tray tooltip template; `None` goes back to the default.
+    pub async fn set_tray_tooltip_template(
+        &self,
+        app: &AppHandle<Wry>,
+        template: Option<String>,
+    ) -> Result<Option<String>, AppStateError> {
+        let prefs = self
+            .update_preferences(
+                app,
+                PreferencesUpdate {
+                    tray_tooltip_template: Some(template),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(prefs.tray_tooltip_template)
+    }
+
+    /// The custom sound for `kind` as a `data:` URI the UI can play.
+    pub fn load_sound(&self, kind: ReminderKind) -> Result<String, AppStateError> {
+        let prefs = self.preferences();
+        let path = prefs.sound_for(kind).ok_or(AppStateError::NoCustomSound)?;
+        self.sound_cache.load(path)
+    }
+
+    /// Drops the next scheduled `kind` reminder only, leaving the timers and
+    /// every other kind alone.
+    pub async fn skip_next(&self, kind: ReminderKind) -> Result<(), AppStateError> {
+        if !SCHEDULED_KINDS.contains(&kind) {
+            return Err(AppStateError::NotScheduled(kind));
+        }
+        let _ = self.control_tx.send(ControlMessage::SkipNext(kind)).await;
+        Ok(())
+    }
+
+    /// Runs `actions` one after another, each `delay_secs` after the last,
+    /// through the same paths as the tray and commands. Replaces any queue
+    /// still running.
+    pub fn queue_actions(
+        &self,
+        app: &AppHandle<Wry>,
+        actions: Vec<TimedAction>,
+    ) -> Result<Vec<PendingAction>, AppStateError> {
+        if actions.len() > MAX_QUEUED_ACTIONS {
+            return Err(AppStateError::TooManyQueuedActions);
+        }
+        let mut span: u64 = 0;
+        for step in &actions {
+            if step.delay_secs > MAX_QUEUED_ACTION_DELAY_SECS {
+                return Err(AppStateError::QueuedDelayTooLong);
+            }
+            span = span.saturating_add(step.delay_secs);
+            if span > MAX_QUEUE_SPAN_SECS {
+                return Err(AppStateError::QueuedDelayTooLong);
+            }
+        }
+        let mut at = Utc::now();
+        let pending: Vec<PendingAction> = actions
+            .into_iter()
+            .map(|step| {
+                at += chrono::Duration::seconds(step.delay_secs as i64);
+                PendingAction {
+                    at,
+                    action: step.action,
+                }
+            })
+            .collect();
+
+        let mut queue = self.action_queue.lock().unwrap();
+        if let Some(handle) = queue.take() {
+            handle.abort();
+        }
+        update_status(app, &self.status, |snapshot| {
+            snapshot.queued_actions = pending.clone();
+        });
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+
+        let app = app.clone();
+        let steps = pending.clone();
+        *queue = Some(async_runtime::spawn(async move {
+            for step in steps {
+                tokio::time::sleep_until(instant_from_timestamp(step.at)).await;
+                let Some(state) = app.try_state::<Arc<AppState>>() else {
+                    return;
+                };
+                match step.action {
+                    QueuedAction::Snooze { minutes } => {
+                        state.snooze(minutes).await;
+                    }
+                    QueuedAction::Pause => state.set_pause(true).await,
+                    QueuedAction::Resume => state.set_pause(false).await,
+                    QueuedAction::Trigger => state.trigger_preview().await,
+                }
+                update_status(&app, &state.status, |snapshot| {
+                    if !snapshot.queued_actions.is_empty() {
+                        snapshot.queued_actions.remove(0);
+                    }
+                });
+            }
+        }));
+        Ok(pending)
+    }
+
+    pub fn clear_action_queue(&self, app: &AppHandle<Wry>) {
+        if let Some(handle) = self.action_queue.lock().unwrap().take() {
+            handle.abort();
+        }
+        update_status(app, &self.status, |snapshot| {
+            snapshot.queued_actions.clear();
+        });
+    }
+
+    /// Whether `action` is far enough from the last accepted identical one
+    /// to go through. A lingering notification clicked twice or a double
+    /// click in the tray would otherwise reset the timer twice.
+    fn accept_action(&self, action: ControlAction) -> bool {
+        let now = Instant::now();
+        let cooldown = Duration::from_millis(ACTION_COOLDOWN_MS);
+        let mut last = self.last_actions.lock().unwrap();
+        last.retain(|_, at| now.duration_since(*at) < cooldown);
+        let cooling_down = last.contains_key(&action);
+        if !cooling_down {
+            last.insert(action, now);
+        }
+        !cooling_down
+    }
+
+    /// Snoozes for a length that shrinks with each snooze since the last
+    /// break. Returns the minutes snoozed, or `None` if the budget is spent.
+    pub async fn snooze_escalating(&self) -> Option<u64> {
+        let level = usize::from(self.status().snooze_escalation);
+        let minutes = ESCALATING_SNOOZE_MINUTES[level.min(ESCALATING_SNOOZE_MINUTES.len() - 1)];
+        self.snooze(minutes).await.then_some(minutes)
+    }
+
+    /// The smart snooze, unless one is already running: repeated calls don't
+    /// stack or eat into the snooze budget. Returns when the snooze ends, or
+    /// `None` if the budget is spent and the reminder fired instead.
+    pub async fn snooze_default(&self) -> Option<DateTime<Utc>> {
+        let now = Utc::now();
+        let running = {
+            let requested = *self.default_snooze_until.lock().unwrap();
+            self.status().snoozed_until.max(requested)
+        };
+        if let Some(until) = running.filter(|until| *until > now) {
+            return Some(until);
+        }
+
+        let minutes = self.smart_default_snooze();
+        if !self.snooze(minutes).await {
+            return None;
+        }
+        let until = now + chrono::Duration::minutes(minutes as i64);
+        *self.default_snooze_until.lock().unwrap() = Some(until);
+        Some(until)
+    }
+
+    pub async fn trigger_preview(&self) {
+        self.trigger_with_message(None).await;
+    }
+
+    /// Fires a reminder now, with `message` instead of one from the pool.
+    /// Restarts the interval like any other reminder.
+    pub async fn trigger_with_message(&self, message: Option<String>) {
+        if !self.accept_action(ControlAction::Trigger) {
+            return;
+        }
+        let _ = self
+            .control_tx
+            .send(ControlMessage::TriggerNow(message))
+            .await;
+    }
+
+    /// Fires a reminder only if the user is at the machine right now, going
+    /// around the engine so the timer keeps running. `false` when nothing
+    /// was sent: away, activity detection off, or the idle time unreadable.
+    pub async fn trigger_if_active(&self, app: &AppHandle<Wry>) -> bool {
+        let prefs = self.preferences();
+        let Some(idle_secs) = self.current_idle_seconds() else {
+            return false;
+        };
+        if is_idle(&prefs, idle_secs) || !self.accept_action(ControlAction::Trigger) {
+            return false;
+        }
+        let message = MessagePicker::default().pick(&prefs, ReminderKind::Posture);
+        send_reminder_message(app, &prefs, ReminderKind::Posture, message).await;
+        true
+    }
+
+    /// Fires a reminder for one exercise straight away. Goes around the engine,
+    /// so the regular timer keeps running untouched.
+    pub async fn trigger_exercise(
+        &self,
+        app: &AppHandle<Wry>,
+        index: usize,
+    ) -> Result<Exercise, AppStateError> {
+        let exercise = *EXERCISES
+            .get(index)
+            .ok_or(AppStateError::ExerciseNotFound(index))?;
+        send_reminder_message(
+            app,
+            &self.preferences(),
+            ReminderKind::Posture,
+            exercise.reminder_message(),
+        )
+        .await;
+        Ok(exercise)
+    }
+
+    /// Ignores `kind` until `until`, after which it applies again. Replaces
+    /// any override already set for it.
+    pub fn override_suppression(
+        &self,
+        kind: SuppressionKind,
+        until: DateTime<Utc>,
+    ) -> Result<SuppressionOverride, AppStateError> {
+        if until <= Utc::now() {
+            return Err(AppStateError::OverrideInPast);
+        }
+        self.suppression_overrides
+            .lock()
+            .unwrap()
+            .insert(kind, until);
+        Ok(SuppressionOverride { kind, until })
+    }
+
+    /// Overrides still in effect, soonest to expire first.
+    pub fn list_overrides(&self) -> Vec<SuppressionOverride> {
+        let now = Utc::now();
+        let mut overrides = self.suppression_overrides.lock().unwrap();
+        overrides.retain(|_, until| *until > now);
+        let mut active: Vec<SuppressionOverride> = overrides
+            .iter()
+            .map(|(kind, until)| SuppressionOverride {
+                kind: *kind,
+                until: *until,
+            })
+            .collect();
+        active.sort_by_key(|active| active.until);
+        active
+    }
+
+    /// Lets `kind` apply again straight away. Returns whether an override
+    /// was set.
+    pub fn clear_override(&self, kind: SuppressionKind) -> bool {
+        self.suppression_overrides
+            .lock()
+            .unwrap()
+            .remove(&kind)
+            .is_some()
+    }
+
+    pub async fn schedule_oneoff(
+        &self,
+        at: DateTime<Utc>,
+        message: Option<String>,
+    ) -> Result<OneOffReminder, AppStateError> {
+        if at <= Utc::now() {
+            return Err(AppStateError::OneOffInPast);
+        }
+
+        let oneoff = OneOffReminder {
+            id: self.next_oneoff_id.fetch_add(1, Ordering::Relaxed),
+            at,
+            message: message
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty()),
+        };
+
+        {
+            let mut oneoffs = self.oneoffs.lock().unwrap();
+            oneoffs.push(oneoff.clone());
+            oneoffs.sort_by_key(|pending| pending.at);
+        }
+
+        let _ = self.control_tx.send(ControlMessage::OneOffsChanged).await;
+        Ok(oneoff)
+    }
+
+    /// The next `count` reminders, projected from the engine's timers. The
+    /// interval is assumed to keep running unchanged; micro-breaks are random,
+    /// so only the next one is known. Empty while paused.
+    pub fn upcoming(&self, count: usize) -> Vec<UpcomingReminder> {
+        let engine = self.engine_internals.lock().unwrap().clone();
+        if engine.suppression.is_paused() {
+            return Vec::new();
+        }
+        let count = count.min(MAX_UPCOMING);
+
+        let mut upcoming: Vec<UpcomingReminder> = Vec::with_capacity(count + 1);
+        if !engine.awaiting_return {
+            // A snooze already moved `next_instant` to when it ends.
+            let first = timestamp_from_instant(engine.next_instant);
+            let step = chrono::Duration::from_std(engine.interval).unwrap_or_default();
+            upcoming.extend((0..count as i32).map(|n| UpcomingReminder {
+                at: first + step * n,
+                kind: ReminderKind::Posture,
+                one_off: false,
+            }));
+        }
+        upcoming.extend(
+            self.list_oneoffs()
+                .into_iter()
+                .map(|oneoff| UpcomingReminder {
+                    at: oneoff.at,
+                    kind: ReminderKind::Posture,
+                    one_off: true,
+                }),
+        );
+        if let Some(at) = engine.micro_break_at {
+            upcoming.push(UpcomingReminder {
+                at: timestamp_from_instant(at),
+                kind: ReminderKind::MicroBreak,
+                one_off: false,
+            });
+        }
+
+        upcoming.sort_by_key(|reminder| reminder.at);
+        upcoming.truncate(count);
+        upcoming
+    }
+
+    pub fn list_oneoffs(&self) -> Vec<OneOffReminder> {
+        self.oneoffs.lock().unwrap().clone()
+    }
+
+    pub async fn cancel_oneoff(&self, id: u64) -> Result<(), AppStateError> {
+        {
+            let mut oneoffs = self.oneoffs.lock().unwrap();
+            let before = oneoffs.len();
+            oneoffs.retain(|pending| pending.id != id);
+            if oneoffs.len() == before {
+                return Err(AppStateError::OneOffNotFound(id));
+            }
+        }
+
+        let _ = self.control_tx.send(ControlMessage::OneOffsChanged).await;
+        Ok(())
+    }
+
+    /// Finds the schedules that should have fired by now but haven't, as
+    /// after waking from sleep, and deals with them as `catch_up_behavior`
+    /// says. Nothing is done when none are overdue.
+    pub async fn catch_up(&self) -> CatchUpReport {
+        let mode = self.preferences().catch_up_behavior;
+        let cutoff = Utc::now() - chrono::Duration::seconds(CATCH_UP_GRACE_SECS);
+        let status = self.status();
+        let micro_due_at = self.engine_internals.lock().unwrap().micro_due_at;
+
+        let mut overdue = Vec::new();
+        // No next reminder is shown while paused, so this skips that too.
+        if status.next_trigger_at.is_some_and(|at| at <= cutoff) {
+            overdue.push(CatchUpChannel::Reminder);
+        }
+        if !status.paused && micro_due_at.is_some_and(|at| at <= cutoff) {
+            overdue.push(CatchUpChannel::MicroBreak);
+        }
+        if self
+            .oneoffs
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|oneoff| oneoff.at <= cutoff)
+        {
+            overdue.push(CatchUpChannel::OneOff);
+        }
+
+        if overdue.is_empty() || mode == CatchUpMode::Ignore {
+            return CatchUpReport {
+                mode,
+                overdue,
+                fired: false,
+            };
+        }
+        // The engine knows what's holding reminders back, so it says
+        // whether one was shown.
+        let (reply, fired) = oneshot::channel();
+        let _ = self
+            .control_tx
+            .send(ControlMessage::CatchUp(mode, overdue.clone(), reply))
+            .await;
+        CatchUpReport {
+            mode,
+            overdue,
+            fired: fired.await.unwrap_or(false),
+        }
+    }
+}
+
+impl Drop for AppState {
+    fn drop(&mut self) {
+        if let Some(handle) = self.worker_handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferencesUpdate {
+    pub interval_minutes: Option<u64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub interval_seconds: Option<Option<u64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub first_reminder_delay_minutes: Option<Option<u64>>,
+    pub activity_detection: Option<bool>,
+    pub sound_enabled: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub sound_path: Option<Option<String>>,
+    pub kind_sounds: Option<HashMap<ReminderKind, String>>,
+    pub autostart_enabled: Option<bool>,
+    pub theme: Option<ThemeMode>,
+    pub idle_threshold_minutes: Option<u64>,
+    pub idle_grace_secs: Option<u64>,
+    pub remote_session_idle: Option<RemoteSessionIdle>,
+    pub playful_labels: Option<bool>,
+    pub replace_notifications: Option<bool>,
+    pub evening_summary: Option<bool>,
+    /// `null` clears the work end time; omitting the field leaves it unchanged.
+    #[serde(default, deserialize_with = "nullable")]
+    pub work_end: Option<Option<NaiveTime>>,
+    pub snooze_morning_at: Option<NaiveTime>,
+    pub snooze_afternoon_at: Option<NaiveTime>,
+    pub snooze_evening_at: Option<NaiveTime>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub ws_port: Option<Option<u16>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub http_trigger_port: Option<Option<u16>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub http_trigger_token: Option<Option<String>>,
+    pub count_idle_skips: Option<bool>,
+    pub custom_messages: Option<HashMap<ReminderKind, Vec<String>>>,
+    pub message_rotation: Option<RotationMode>,
+    pub mood: Option<Mood>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub panic_hotkey: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub max_total_snooze_minutes: Option<Option<u64>>,
+    pub micro_breaks_enabled: Option<bool>,
+    pub micro_breaks_per_hour: Option<u8>,
+    pub suppress_when_display_off: Option<bool>,
+    pub suppress_when_lid_closed: Option<bool>,
+    pub suppress_on_bluetooth_call: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub busy_flag_path: Option<Option<String>>,
+    pub require_return_confirmation: Option<bool>,
+    pub mini_break_weight: Option<f64>,
+    pub tray_labels: Option<TrayLabels>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tray_tooltip_template: Option<Option<String>>,
+    pub overlay_opacity: Option<f32>,
+    pub overlay_color: Option<String>,
+    pub overlay_show_countdown: Option<bool>,
+    pub overlay_on_break: Option<bool>,
+    pub adaptive_interval: Option<bool>,
+    pub desk_alternation: Option<bool>,
+    pub starting_posture: Option<DeskPosture>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub calendar_ics_path: Option<Option<String>>,
+    pub max_overlays_per_hour: Option<u8>,
+    pub active_debounce_polls: Option<u8>,
+    pub autostart_show_window: Option<bool>,
+    pub escalating_snooze: Option<bool>,
+    pub count_only_active_time: Option<bool>,
+    pub preserve_rhythm: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub snap_to_clock: Option<Option<u64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub post_break_cooldown_minutes: Option<Option<u64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub long_break_after_snoozes: Option<Option<u8>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub campaign: Option<Option<CampaignConfig>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub campaign_started: Option<Option<NaiveDate>>,
+    pub reminder_days: Option<Vec<Weekday>>,
+    pub weekend_mode: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub weekend_interval_minutes: Option<Option<u64>>,
+    pub extra_notification_actions: Option<bool>,
+    pub idle_aware_snooze: Option<bool>,
+    pub haptic_enabled: Option<bool>,
+    pub group_notifications: Option<bool>,
+    pub attention_mode: Option<bool>,
+    pub pause_during_processes: Option<Vec<String>>,
+    pub eco_on_battery: Option<bool>,
+    pub catch_up_behavior: Option<CatchUpMode>,
+    pub minimize_to_tray: Option<bool>,
+    pub focus_bar_enabled: Option<bool>,
+    pub strict_mode: Option<bool>,
+    pub debug_tools: Option<bool>,
+}
+
+/// Lets `PreferencesUpdate` tell an explicit `null` (clear the value) apart
+/// from a missing field (leave it alone).
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// User actions debounced by `AppState::accept_action`. Arguments are part
+/// of the key, so only a true repeat is dropped: a 15-minute snooze right
+/// after a 5-minute one still goes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ControlAction {
+    /// Minutes snoozed.
+    Snooze(u64),
+    Skip,
+    Trigger,
+}
+
+enum ControlMessage {
+    PreferencesUpdated(Box<Preferences>),
+    Pause(bool),
+    PauseUntil(DateTime<Utc>),
+    SilentMode(bool),
+    Snooze(Duration),
+    SnoozeUntil(DateTime<Utc>),
+    ClearSnooze,
+    SkipCurrent,
+    SkipNext(ReminderKind),
+    MiniBreak,
+    ManualBreak(u64, Option<String>),
+    CompleteBreak,
+    ClearStats,
+    TriggerNow(Option<String>),
+    Sprint(Option<Sprint>),
+    OneOffsChanged,
+    /// Answers whether a reminder actually went out.
+    CatchUp(CatchUpMode, Vec<CatchUpChannel>, oneshot::Sender<bool>),
+}
+
+/// A temporary, shorter interval; see `AppState::start_sprint`.
+#[derive(Debug, Clone, Copy)]
+struct Sprint {
+    interval: Duration,
+    until: DateTime<Utc>,
+}
+
+/// The interval in force; see `resolve_interval`.
+fn effective_interval(prefs: &Preferences, sprint: Option<Sprint>, scale: f64) -> Duration {
+    resolve_interval(prefs, sprint, scale, Local::now(), false).interval
+}
+
+/// Builds the interval up from the preference, in this order: today's
+/// campaign step replaces it, or else the weekend interval does on a
+/// weekend; `adaptive_scale` then stretches or shrinks it; and a running
+/// sprint replaces the lot. Then works out the wait for a reminder
+/// scheduled at `now`: the post-break cooldown when `after_break`, then
+/// `snap_to_clock`, then a move past days off. Records each step that
+/// changed something.
+fn resolve_interval(
+    prefs: &Preferences,
+    sprint: Option<Sprint>,
+    scale: f64,
+    now: DateTime<Local>,
+    after_break: bool,
+) -> IntervalResolution {
+    let today = now.date_naive();
+    let base = prefs.interval_duration();
+    let mut resolution = IntervalResolution {
+        base,
+        interval: base,
+        wait: base,
+        steps: Vec::new(),
+    };
+    match (
+        prefs.campaign_progress(today),
+        prefs.weekend_interval_minutes,
+    ) {
+        (Some(progress), _) => resolution.apply(
+            IntervalModifier::Campaign,
+            Duration::from_secs(progress.interval_minutes * 60),
+        ),
+        (None, Some(minutes)) if prefs.is_weekend(today) => {
+            resolution.apply(IntervalModifier::Weekend, Duration::from_secs(minutes * 60))
+        }
+        _ => {}
+    }
+    if scale != 1.0 {
+        let scaled = resolution.interval.mul_f64(scale);
+        resolution.apply(IntervalModifier::Adaptive, scaled);
+    }
+    if let Some(sprint) = sprint {
+        resolution.apply(IntervalModifier::Sprint, sprint.interval);
+    }
+
+    // The cooldown goes in before snapping so the snapped time still lands
+    // on a clock mark.
+    let cooldown = prefs.post_break_cooldown_minutes.unwrap_or(0);
+    if after_break && cooldown > 0 {
+        let wait = resolution.wait + Duration::from_secs(cooldown * 60);
+        resolution.delay(IntervalModifier::PostBreakCooldown, wait);
+    }
+    if let Some(minutes) = prefs.snap_to_clock.filter(|minutes| *minutes > 0) {
+        let wait = resolution.wait + till_clock_mark(now + resolution.wait, minutes);
+        resolution.delay(IntervalModifier::SnapToClock, wait);
+    }
+    if let Some(start) = next_reminder_day_start(prefs, now + resolution.wait) {
+        let wait = (start - now.with_timezone(&Utc))
+            .to_std()
+            .unwrap_or_default()
+            + resolution.interval;
+        resolution.delay(IntervalModifier::DaysOff, wait);
+    }
+    resolution
+}
+
+struct IntervalResolution {
+    base: Duration,
+    interval: Duration,
+    /// From the moment it was resolved for until the reminder is due.
+    wait: Duration,
+    steps: Vec<IntervalStep>,
+}
+
+impl IntervalResolution {
+    fn apply(&mut self, modifier: IntervalModifier, interval: Duration) {
+        self.steps.push(IntervalStep {
+            modifier,
+            interval_secs: interval.as_secs(),
+            change_secs: interval.as_secs() as i64 - self.interval.as_secs() as i64,
+        });
+        self.interval = interval;
+        self.wait = interval;
+    }
+
+    /// Like `apply`, but moves only the reminder, not the interval.
+    fn delay(&mut self, modifier: IntervalModifier, wait: Duration) {
+        if wait == self.wait {
+            return;
+        }
+        self.steps.push(IntervalStep {
+            modifier,
+            interval_secs: wait.as_secs(),
+            change_secs: wait.as_secs() as i64 - self.wait.as_secs() as i64,
+        });
+        self.wait = wait;
+    }
+}
+
+/// `next_interval_instant` with `post_break_cooldown_minutes` folded in, for
+/// the reminder right after a completed break. Only that one reschedule adds
+/// it; the ones after use the plain interval again.
+fn after_break_instant(prefs: &Preferences, sprint: Option<Sprint>, scale: f64) -> Instant {
+    let wait = resolve_interval(prefs, sprint, scale, Local::now(), true).wait;
+    safe_next(Instant::now() + wait)
+}
+
+/// When a reminder scheduled now is due: one interval out, snapped to the
+/// clock and moved past days off as `resolve_interval` works it out.
+fn next_interval_instant(prefs: &Preferences, sprint: Option<Sprint>, scale: f64) -> Instant {
+    let wait = resolve_interval(prefs, sprint, scale, Local::now(), false).wait;
+    safe_next(Instant::now() + wait)
+}
+
+/// Moves a reminder that would land outside `reminder_days` to one
+/// `interval` into the next day that's in them.
+fn skip_days_off(prefs: &Preferences, next: Instant, interval: Duration) -> Instant {
+    let at = Local::now() + next.saturating_duration_since(Instant::now());
+    match next_reminder_day_start(prefs, at) {
+        Some(start) => safe_next(instant_from_timestamp(start) + interval),
+        None => next,
+    }
+}
+
+/// Local midnight of the first day in `reminder_days` after `at`, or `None`
+/// when `at` already falls on one (or `strict_mode` ignores days off).
+fn next_reminder_day_start(prefs: &Preferences, at: DateTime<Local>) -> Option<DateTime<Utc>> {
+    let mut date = at.date_naive();
+    if prefs.strict_mode || prefs.is_reminder_day(date) {
+        return None;
+    }
+    for _ in 0..7 {
+        date = date.succ_opt()?;
+        if prefs.is_reminder_day(date) {
+            return date
+                .and_time(NaiveTime::MIN)
+                .and_local_timezone(Local)
+                .earliest()
+                .map(|start| start.with_timezone(&Utc));
+        }
+    }
+    None
+}
+
+/// Multiplier on the interval for `adaptive_interval`, from the share of the
+/// last hour spent away: none pulls the next reminder in to
+/// `ADAPTIVE_MIN_SCALE`, `ADAPTIVE_NEUTRAL_AWAY_SHARE` leaves it alone, and a
+/// proper break pushes it out up to `ADAPTIVE_MAX_SCALE`. Stays at 1 until
+/// the engine has a full hour to judge by. Drops episodes that have aged out
+/// of `recent_idle` (end time, seconds away).
+fn adaptive_scale(
+    prefs: &Preferences,
+    recent_idle: &mut VecDeque<(Instant, u64)>,
+    started: Instant,
+    now: Instant,
+) -> f64 {
+    let window = Duration::from_secs(ADAPTIVE_WINDOW_SECS);
+    while recent_idle
+        .front()
+        .is_some_and(|(ended, _)| now.saturating_duration_since(*ended) > window)
+    {
+        recent_idle.pop_front();
+    }
+    if !prefs.adaptive_interval || now.saturating_duration_since(started) < window {
+        return 1.0;
+    }
+    let away: u64 = recent_idle.iter().map(|(_, secs)| secs).sum();
+    let share = (away as f64 / window.as_secs_f64()).min(1.0);
+    let scale =
+        ADAPTIVE_MIN_SCALE + share * (1.0 - ADAPTIVE_MIN_SCALE) / ADAPTIVE_NEUTRAL_AWAY_SHARE;
+    scale.clamp(ADAPTIVE_MIN_SCALE, ADAPTIVE_MAX_SCALE)
+}
+
+/// `next`, but never sooner than `MIN_RESCHEDULE_LEAD_SECS` from now. Every
+/// reschedule goes through this so a deadline that rounding or a nearly
+/// finished snooze put in the past can't fire the moment it's set; only an
+/// explicit trigger sets the timer to now.
+fn safe_next(next: Instant) -> Instant {
+    next.max(Instant::now() + Duration::from_secs(MIN_RESCHEDULE_LEAD_SECS))
+}
+
+/// How much of a poll `since_poll` long was spent active, for
+/// `count_only_active_time`: none of it while idle, and otherwise all but the
+/// `idle_secs` since the last input. Wayland only reports idle once the
+/// threshold passes, so there every poll short of that counts in full.
+fn active_part(since_poll: Duration, idle_secs: u64, idle: bool) -> Duration {
+    if idle {
+        return Duration::ZERO;
+    }
+    since_poll.saturating_sub(Duration::from_secs(idle_secs))
+}
+
+/// Active time still owed before the reminder fires, after `active` more of
+/// it at `now`. Starts from the wait until `next` whenever something else
+/// rescheduled the reminder since `previous` (remaining, deadline) was
+/// worked out; the reminder fires when this reaches zero.
+fn remaining_active_time(
+    previous: Option<(Duration, Instant)>,
+    next: Instant,
+    now: Instant,
+    active: Duration,
+) -> Duration {
+    match previous {
+        Some((remaining, deadline)) if deadline == next => remaining.saturating_sub(active),
+        _ => next.saturating_duration_since(now),
+    }
+}
+
+/// When a stretched snooze fires after the user comes back: not on the
+/// spot, but `SNOOZE_RETURN_GRACE_SECS` later.
+fn after_return_instant() -> Instant {
+    safe_next(Instant::now() + Duration::from_secs(SNOOZE_RETURN_GRACE_SECS))
+}
+
+/// Rounds `instant` up to the next multiple of `snap_to_clock` minutes past
+/// local midnight, or returns it unchanged when snapping is off.
+fn snap_to_clock(prefs: &Preferences, instant: Instant) -> Instant {
+    let Some(minutes) = prefs.snap_to_clock.filter(|minutes| *minutes > 0) else {
+        return instant;
+    };
+    let local = Local::now() + (instant.saturating_duration_since(Instant::now()));
+    instant + till_clock_mark(local, minutes)
+}
+
+/// How far `at` is from the next multiple of `minutes` past local midnight;
+/// zero when it's right on one.
+fn till_clock_mark(at: DateTime<Local>, minutes: u64) -> Duration {
+    let step = minutes * 60;
+    let secs_into_day = at.num_seconds_from_midnight() as u64;
+    let nanos = at.nanosecond() as u64 % 1_000_000_000;
+    let past = secs_into_day % step;
+    if past == 0 && nanos == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs(step - past) - Duration::from_nanos(nanos)
+}
+
+/// The first step of the schedule `anchor`, `anchor + interval`, ... that is
+/// still ahead of `now`. The steps count from launch, not from the clock, so
+/// an interval that doesn't divide the hour drifts across it (a 25-minute
+/// rhythm started at 9:10 lands on 9:35, 10:00, 10:25, ...).
+fn next_on_rhythm(anchor: Instant, interval: Duration, now: Instant) -> Instant {
+    if now < anchor || interval.is_zero() {
+        return anchor.max(now);
+    }
+    let steps = (now - anchor).as_nanos() / interval.as_nanos() + 1;
+    anchor + interval * u32::try_from(steps).unwrap_or(u32::MAX)
+}
+
+/// How long to wait for the next reminder right after preferences change,
+/// along with the snooze that is still running, if any. A running snooze
+/// always wins over the new interval, shorter or longer, until it runs out;
+/// otherwise the new interval starts over from now.
+fn wait_after_preferences_change(
+    snoozed_until: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    interval: Duration,
+) -> (Duration, Option<DateTime<Utc>>) {
+    match snoozed_until.and_then(|until| (until - now).to_std().ok().map(|wait| (wait, until))) {
+        Some((wait, until)) if !wait.is_zero() => (wait, Some(until)),
+        _ => (interval, None),
+    }
+}
+
+/// Whether `skip_next_autostart` asked for this launch to be skipped,
+/// removing the request so it only applies once. Cheap enough to check
+/// before anything else starts.
+pub fn take_skip_autostart_marker(app: &AppHandle<Wry>) -> bool {
+    let Ok(dir) = app.path().app_config_dir() else {
+        return false;
+    };
+    fs::remove_file(dir.join(SKIP_AUTOSTART_MARKER)).is_ok()
+}
+
+/// Resolves and creates the config directory, retrying with backoff since
+/// network-mounted home directories sometimes aren't ready right at login.
+fn resolve_config_dir(app: &AppHandle<Wry>) -> Result<PathBuf, AppStateError> {
+    let mut backoff = Duration::from_millis(CONFIG_DIR_INITIAL_BACKOFF_MS);
+    let mut attempt = 1;
+    loop {
+        let result = app
+            .path()
+            .app_config_dir()
+            .map_err(AppStateError::from)
+            .and_then(|dir| {
+                fs::create_dir_all(&dir)?;
+                Ok(dir)
+            });
+
+        match result {
+            Ok(dir) => return Ok(dir),
+            Err(err) if attempt < CONFIG_DIR_ATTEMPTS => {
+                eprintln!("TouchGrass: config directory not ready ({err}); retrying.");
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn load_preferences(path: &Path) -> Result<Preferences, AppStateError> {
+    if !path.exists() {
+        return Ok(Preferences::default());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    match serde_json::from_str::<Preferences>(&contents) {
+        Ok(prefs) => Ok(prefs),
+        Err(err) => {
+            eprintln!("TouchGrass: preferences.json was invalid ({err}); restoring defaults.");
+            backup_corrupt_file(path);
+            let defaults = Preferences::default();
+            save_preferences(path, &defaults)?;
+            Ok(defaults)
+        }
+    }
+}
+
+/// Saves `prefs` to a scratch file next to `preferences.json` and reads it
+/// back, the way real saves and loads go.
+fn check_config_round_trip(path: Option<&Path>, prefs: &Preferences) -> SelfTestCheck {
+    const NAME: &str = "config";
+    let Some(dir) = path.and_then(Path::parent) else {
+        return SelfTestCheck::new(NAME, CheckOutcome::Skipped, "no config directory");
+    };
+    let scratch = dir.join(SELF_TEST_FILE);
+    let result = save_preferences(&scratch, prefs).and_then(|()| {
+        let loaded: Preferences = serde_json::from_str(&fs::read_to_string(&scratch)?)?;
+        Ok(serde_json::to_value(&loaded)? == serde_json::to_value(prefs)?)
+    });
+    let _ = fs::remove_file(&scratch);
+    match result {
+        Ok(true) => SelfTestCheck::new(NAME, CheckOutcome::Ok, dir.display().to_string()),
+        Ok(false) => SelfTestCheck::new(
+            NAME,
+            CheckOutcome::Failed,
+            "preferences read back differently than they were written",
+        ),
+        Err(err) => SelfTestCheck::new(NAME, CheckOutcome::Failed, err.to_string()),
+    }
+}
+
+fn check_idle_detector(idle_detector: &IdleDetector) -> SelfTestCheck {
+    const NAME: &str = "idleDetection";
+    match idle_detector.get_idle_time() {
+        Ok(secs) => SelfTestCheck::new(
+            NAME,
+            CheckOutcome::Ok,
+            format!("{:?}, idle {secs}s", idle_detector.backend()),
+        ),
+        Err(err) => SelfTestCheck::new(NAME, CheckOutcome::Failed, err.to_string()),
+    }
+}
+
+fn check_notification_permission(app: &AppHandle<Wry>) -> SelfTestCheck {
+    const NAME: &str = "notificationPermission";
+    match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => SelfTestCheck::new(NAME, CheckOutcome::Ok, "granted"),
+        Ok(state) => SelfTestCheck::new(NAME, CheckOutcome::Failed, format!("{state:?}")),
+        Err(err) => SelfTestCheck::new(NAME, CheckOutcome::Failed, err.to_string()),
+    }
+}
+
+fn check_tray(app: &AppHandle<Wry>, available: bool) -> SelfTestCheck {
+    const NAME: &str = "tray";
+    if available && tray::exists(app) {
+        SelfTestCheck::new(NAME, CheckOutcome::Ok, "tray icon is up")
+    } else {
+        SelfTestCheck::new(
+            NAME,
+            CheckOutcome::Failed,
+            "no tray icon; closing the window quits instead of hiding",
+        )
+    }
+}
+
+fn check_autostart(app: &AppHandle<Wry>) -> SelfTestCheck {
+    use tauri_plugin_autostart::ManagerExt;
+
+    const NAME: &str = "autostart";
+    match app.autolaunch().is_enabled() {
+        Ok(enabled) => SelfTestCheck::new(
+            NAME,
+            CheckOutcome::Ok,
+            if enabled { "enabled" } else { "disabled" },
+        ),
+        Err(err) => SelfTestCheck::new(NAME, CheckOutcome::Failed, err.to_string()),
+    }
+}
+
+fn config_report(dir: &Path, repairs: Vec<String>) -> ConfigReport {
+    let entries = vec![
+        check_config_dir(dir),
+        check_preferences_file(&dir.join(PREFERENCES_FILE)),
+        check_stats_file(&dir.join(stats::STATS_FILE)),
+    ];
+    ConfigReport {
+        checked_at: Utc::now(),
+        healthy: entries.iter().all(|entry| entry.problem.is_none()),
+        entries,
+        repairs,
+    }
+}
+
+fn config_problem(err: &std::io::Error) -> ConfigProblem {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => ConfigProblem::Missing,
+        std::io::ErrorKind::PermissionDenied => ConfigProblem::PermissionDenied,
+        _ => ConfigProblem::Unreadable,
+    }
+}
+
+/// Writes and removes a scratch file to see that saves can land here.
+fn check_config_dir(dir: &Path) -> ConfigEntry {
+    match fs::metadata(dir) {
+        Ok(metadata) if !metadata.is_dir() => ConfigEntry::new(
+            dir,
+            Some(ConfigProblem::NotADirectory),
+            "exists, but isn't a directory",
+        ),
+        Ok(_) => {
+            let scratch = dir.join(SELF_TEST_FILE);
+            let written = fs::write(&scratch, b"{}");
+            let _ = fs::remove_file(&scratch);
+            match written {
+                Ok(()) => ConfigEntry::new(dir, None, "readable and writable"),
+                Err(err) => ConfigEntry::new(
+                    dir,
+                    Some(config_problem(&err)),
+                    format!("can't write here: {err}"),
+                ),
+            }
+        }
+        Err(err) => ConfigEntry::new(dir, Some(config_problem(&err)), err.to_string()),
+    }
+}
+
+fn check_preferences_file(path: &Path) -> ConfigEntry {
+    check_config_file(path, |contents| {
+        serde_json::from_str::<Preferences>(contents).is_ok()
+    })
+}
+
+fn check_stats_file(path: &Path) -> ConfigEntry {
+    check_config_file(path, stats::parses)
+}
+
+/// Reads `path` and opens it for writing without changing it. Not there
+/// yet only means nothing was saved so far.
+fn check_config_file(path: &Path, parses: impl FnOnce(&str) -> bool) -> ConfigEntry {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return ConfigEntry::new(path, None, "not saved yet; defaults apply");
+        }
+        Err(err) => return ConfigEntry::new(path, Some(config_problem(&err)), err.to_string()),
+    };
+    if !parses(&contents) {
+        return ConfigEntry::new(path, Some(ConfigProblem::Corrupt), "doesn't parse");
+    }
+    match fs::OpenOptions::new().append(true).open(path) {
+        Ok(_) => ConfigEntry::new(path, None, "readable and writable"),
+        Err(err) => ConfigEntry::new(
+            path,
+            Some(config_problem(&err)),
+            format!("read-only: {err}"),
+        ),
+    }
+}
+
+/// Writes to a sibling file first and renames it over `path`, so a failed
+/// write never leaves a half-written `preferences.json` behind.
+fn save_preferences(path: &Path, prefs: &Preferences) -> Result<(), AppStateError> {
+    let temp_path = path.with_extension("json.tmp");
+    let result = File::create(&temp_path)
+        .map_err(AppStateError::from)
+        .and_then(|file| serde_json::to_writer_pretty(file, prefs).map_err(AppStateError::from))
+        .and_then(|()| fs::rename(&temp_path, path).map_err(AppStateError::from));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Moves `path` aside as `*.json.corrupt` (numbered if that's taken),
+/// removing it if it can't be moved. Returns where it went.
+fn backup_corrupt_file(path: &Path) -> Option<PathBuf> {
+    let mut backup_path = path.with_extension("json.corrupt");
+    if backup_path.exists() {
+        let mut counter = 1;
+        loop {
+            let candidate = path.with_extension(format!("json.corrupt.{counter}"));
+            if !candidate.exists() {
                 backup_path = candidate;
                 break;
             }
@@ -292,26 +4287,495 @@ fn backup_corrupt_preferences(path: &Path) {
         }
     }
 
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
     match fs::rename(path, &backup_path) {
-        Ok(_) => eprintln!(
-            "TouchGrass: moved corrupt preferences to {}",
-            backup_path.display()
-        ),
+        Ok(_) => {
+            eprintln!(
+                "TouchGrass: moved corrupt {name} to {}",
+                backup_path.display()
+            );
+            Some(backup_path)
+        }
         Err(err) => {
-            eprintln!("TouchGrass: failed to backup corrupt preferences ({err}); removing file.");
+            eprintln!("TouchGrass: failed to backup corrupt {name} ({err}); removing file.");
             let _ = fs::remove_file(path);
+            None
+        }
+    }
+}
+
+/// Rejects tooltip templates that are too long or use a placeholder
+/// `TrayState` wouldn't fill in.
+/// `base` with `update` laid over it, every value clamped to its range and
+/// the result checked against `current`, the preferences in effect before.
+/// Both `update_preferences` and hand edits picked up by
+/// `reload_preferences` go through here.
+fn merge_preferences(
+    base: Preferences,
+    current: &Preferences,
+    update: PreferencesUpdate,
+) -> Result<Preferences, AppStateError> {
+    let mut prefs = base;
+
+    if let Some(interval) = update.interval_minutes {
+        prefs.interval_minutes = interval.clamp(2, 240);
+    }
+    if let Some(first_delay) = update.first_reminder_delay_minutes {
+        prefs.first_reminder_delay_minutes = first_delay.map(|minutes| minutes.clamp(1, 240));
+    }
+    if let Some(interval_seconds) = update.interval_seconds {
+        prefs.interval_seconds = interval_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| seconds.clamp(MIN_INTERVAL_SECONDS, 240 * 60));
+    }
+    if let Some(activity_detection) = update.activity_detection {
+        prefs.activity_detection = activity_detection;
+    }
+    if let Some(sound_enabled) = update.sound_enabled {
+        prefs.sound_enabled = sound_enabled;
+    }
+    if let Some(sound_path) = update.sound_path {
+        prefs.sound_path = sound_path.filter(|path| !path.trim().is_empty());
+    }
+    if let Some(kind_sounds) = update.kind_sounds {
+        prefs.kind_sounds = kind_sounds
+            .into_iter()
+            .filter(|(_, path)| !path.trim().is_empty())
+            .collect();
+    }
+    if let Some(autostart) = update.autostart_enabled {
+        prefs.autostart_enabled = autostart;
+    }
+    if let Some(theme) = update.theme.clone() {
+        prefs.theme = theme;
+    }
+    if let Some(threshold) = update.idle_threshold_minutes {
+        prefs.idle_threshold_minutes = clamp_idle_threshold_minutes(threshold);
+    }
+    if let Some(grace) = update.idle_grace_secs {
+        prefs.idle_grace_secs = grace.min(MAX_IDLE_GRACE_SECS);
+    }
+    if let Some(remote) = update.remote_session_idle {
+        prefs.remote_session_idle = remote;
+    }
+    if let Some(playful_labels) = update.playful_labels {
+        prefs.playful_labels = playful_labels;
+    }
+    if let Some(replace_notifications) = update.replace_notifications {
+        prefs.replace_notifications = replace_notifications;
+    }
+    if let Some(evening_summary) = update.evening_summary {
+        prefs.evening_summary = evening_summary;
+    }
+    if let Some(work_end) = update.work_end {
+        prefs.work_end = work_end;
+    }
+    if let Some(snooze_morning_at) = update.snooze_morning_at {
+        prefs.snooze_morning_at = snooze_morning_at;
+    }
+    if let Some(snooze_afternoon_at) = update.snooze_afternoon_at {
+        prefs.snooze_afternoon_at = snooze_afternoon_at;
+    }
+    if let Some(snooze_evening_at) = update.snooze_evening_at {
+        prefs.snooze_evening_at = snooze_evening_at;
+    }
+    if let Some(ws_port) = update.ws_port {
+        prefs.ws_port = ws_port.filter(|port| *port != 0);
+    }
+    if let Some(port) = update.http_trigger_port {
+        prefs.http_trigger_port = port.filter(|port| *port != 0);
+    }
+    if let Some(token) = update.http_trigger_token.clone() {
+        prefs.http_trigger_token = token
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+    }
+    if prefs.http_trigger_port.is_some() && prefs.http_trigger_token.is_none() {
+        prefs.http_trigger_token = Some(generate_token());
+    }
+    if let Some(count_idle_skips) = update.count_idle_skips {
+        prefs.count_idle_skips = count_idle_skips;
+    }
+    if let Some(custom_messages) = update.custom_messages {
+        // Only the kinds present in the update are replaced.
+        for (kind, messages) in custom_messages {
+            prefs
+                .custom_messages
+                .insert(kind, normalize_messages(messages));
+        }
+        prefs
+            .custom_messages
+            .retain(|_, messages| !messages.is_empty());
+    }
+    if let Some(rotation) = update.message_rotation {
+        prefs.message_rotation = rotation;
+    }
+    if let Some(mood) = update.mood {
+        prefs.mood = mood;
+    }
+    if let Some(panic_hotkey) = update.panic_hotkey.clone() {
+        prefs.panic_hotkey = panic_hotkey
+            .map(|hotkey| hotkey.trim().to_string())
+            .filter(|hotkey| !hotkey.is_empty());
+    }
+    if let Some(enabled) = update.micro_breaks_enabled {
+        prefs.micro_breaks_enabled = enabled;
+    }
+    if let Some(per_hour) = update.micro_breaks_per_hour {
+        prefs.micro_breaks_per_hour = per_hour.clamp(1, MAX_MICRO_BREAKS_PER_HOUR);
+    }
+    if let Some(suppress) = update.suppress_when_display_off {
+        prefs.suppress_when_display_off = suppress;
+    }
+    if let Some(suppress) = update.suppress_when_lid_closed {
+        prefs.suppress_when_lid_closed = suppress;
+    }
+    if let Some(suppress) = update.suppress_on_bluetooth_call {
+        prefs.suppress_on_bluetooth_call = suppress;
+    }
+    if let Some(path) = update.busy_flag_path.clone() {
+        prefs.busy_flag_path = path
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty());
+    }
+    if let Some(require) = update.require_return_confirmation {
+        prefs.require_return_confirmation = require;
+    }
+    if let Some(weight) = update.mini_break_weight {
+        if weight.is_finite() {
+            prefs.mini_break_weight = weight.clamp(0.0, 1.0);
+        }
+    }
+    if let Some(tray_labels) = update.tray_labels.clone() {
+        prefs.tray_labels = tray_labels;
+    }
+    if let Some(template) = update.tray_tooltip_template.clone() {
+        prefs.tray_tooltip_template = template
+            .map(|template| template.trim().to_string())
+            .filter(|template| !template.is_empty());
+    }
+    if let Some(opacity) = update.overlay_opacity {
+        if opacity.is_finite() {
+            prefs.overlay_opacity = opacity.clamp(0.1, 1.0);
+        }
+    }
+    if let Some(color) = update.overlay_color.clone() {
+        prefs.overlay_color = color.trim().to_ascii_lowercase();
+    }
+    if let Some(show_countdown) = update.overlay_show_countdown {
+        prefs.overlay_show_countdown = show_countdown;
+    }
+    if let Some(on_break) = update.overlay_on_break {
+        prefs.overlay_on_break = on_break;
+    }
+    if let Some(adaptive) = update.adaptive_interval {
+        prefs.adaptive_interval = adaptive;
+    }
+    if let Some(alternate) = update.desk_alternation {
+        prefs.desk_alternation = alternate;
+    }
+    if let Some(posture) = update.starting_posture {
+        prefs.starting_posture = posture;
+    }
+    if let Some(path) = update.calendar_ics_path.clone() {
+        prefs.calendar_ics_path = path
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty());
+    }
+    if let Some(per_hour) = update.max_overlays_per_hour {
+        prefs.max_overlays_per_hour = per_hour.clamp(1, MAX_OVERLAYS_PER_HOUR);
+    }
+    if let Some(polls) = update.active_debounce_polls {
+        prefs.active_debounce_polls = polls.clamp(1, MAX_ACTIVE_DEBOUNCE_POLLS);
+    }
+    if let Some(show_window) = update.autostart_show_window {
+        prefs.autostart_show_window = show_window;
+    }
+    if let Some(escalating) = update.escalating_snooze {
+        prefs.escalating_snooze = escalating;
+    }
+    if let Some(active_only) = update.count_only_active_time {
+        prefs.count_only_active_time = active_only;
+    }
+    if let Some(preserve) = update.preserve_rhythm {
+        prefs.preserve_rhythm = preserve;
+    }
+    if let Some(snap) = update.snap_to_clock {
+        prefs.snap_to_clock = snap.map(|minutes| minutes.clamp(1, 60));
+    }
+    if let Some(extra_actions) = update.extra_notification_actions {
+        prefs.extra_notification_actions = extra_actions;
+    }
+    if let Some(idle_aware) = update.idle_aware_snooze {
+        prefs.idle_aware_snooze = idle_aware;
+    }
+    if let Some(haptic) = update.haptic_enabled {
+        prefs.haptic_enabled = haptic;
+    }
+    if let Some(group) = update.group_notifications {
+        prefs.group_notifications = group;
+    }
+    if let Some(attention) = update.attention_mode {
+        prefs.attention_mode = attention;
+    }
+    if let Some(processes) = update.pause_during_processes.clone() {
+        prefs.pause_during_processes = normalize_messages(processes);
+    }
+    if let Some(eco) = update.eco_on_battery {
+        prefs.eco_on_battery = eco;
+    }
+    if let Some(mode) = update.catch_up_behavior {
+        prefs.catch_up_behavior = mode;
+    }
+    if let Some(minimize_to_tray) = update.minimize_to_tray {
+        prefs.minimize_to_tray = minimize_to_tray;
+    }
+    if let Some(enabled) = update.focus_bar_enabled {
+        prefs.focus_bar_enabled = enabled;
+    }
+    if let Some(strict) = update.strict_mode {
+        prefs.strict_mode = strict;
+    }
+    if let Some(debug_tools) = update.debug_tools {
+        prefs.debug_tools = debug_tools;
+    }
+    if let Some(max_total_snooze) = update.max_total_snooze_minutes {
+        prefs.max_total_snooze_minutes = max_total_snooze.map(|minutes| minutes.min(24 * 60));
+    }
+    if let Some(after) = update.long_break_after_snoozes {
+        prefs.long_break_after_snoozes = after
+            .filter(|snoozes| *snoozes > 0)
+            .map(|snoozes| snoozes.min(MAX_LONG_BREAK_AFTER_SNOOZES));
+    }
+    if let Some(cooldown) = update.post_break_cooldown_minutes {
+        prefs.post_break_cooldown_minutes = cooldown
+            .filter(|minutes| *minutes > 0)
+            .map(|minutes| minutes.min(MAX_POST_BREAK_COOLDOWN_MINUTES));
+    }
+    if let Some(campaign) = update.campaign {
+        prefs.campaign = campaign.map(CampaignConfig::clamped);
+    }
+    if let Some(started) = update.campaign_started {
+        prefs.campaign_started = started;
+    }
+    if let Some(mut days) = update.reminder_days.clone() {
+        days.sort_by_key(Weekday::num_days_from_monday);
+        days.dedup();
+        prefs.reminder_days = days;
+    }
+    if let Some(enabled) = update.weekend_mode {
+        prefs.weekend_mode = enabled;
+    }
+    if let Some(minutes) = update.weekend_interval_minutes {
+        prefs.weekend_interval_minutes = minutes.map(|minutes| minutes.clamp(2, 240));
+    }
+    // A campaign always has a first day, and only a campaign does.
+    prefs.campaign_started = match prefs.campaign {
+        Some(_) => prefs.campaign_started.or_else(|| Some(stats::today())),
+        None => None,
+    };
+
+    // All or nothing: any invalid field leaves the stored preferences as
+    // they were.
+    prefs.validate(current)?;
+    Ok(prefs)
+}
+
+fn validate_tooltip_template(template: &str) -> Result<(), AppStateError> {
+    if template.chars().count() > MAX_TOOLTIP_TEMPLATE_CHARS {
+        return Err(AppStateError::InvalidTooltipTemplate(format!(
+            "longer than {MAX_TOOLTIP_TEMPLATE_CHARS} characters"
+        )));
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return Err(AppStateError::InvalidTooltipTemplate(
+                "unclosed \"{\"".into(),
+            ));
+        };
+        let placeholder = &rest[start..=start + len];
+        if !TOOLTIP_PLACEHOLDERS.contains(&placeholder) {
+            return Err(AppStateError::InvalidTooltipTemplate(format!(
+                "unknown placeholder {placeholder}"
+            )));
         }
+        rest = &rest[start + len + 1..];
     }
+    Ok(())
+}
+
+fn default_reminder_days() -> Vec<Weekday> {
+    (0..7)
+        .filter_map(|day| Weekday::try_from(day).ok())
+        .collect()
 }
 
 fn default_idle_threshold_minutes() -> u64 {
     DEFAULT_IDLE_THRESHOLD_MINUTES
 }
 
+fn default_micro_breaks_per_hour() -> u8 {
+    DEFAULT_MICRO_BREAKS_PER_HOUR
+}
+
+fn default_mini_break_weight() -> f64 {
+    DEFAULT_MINI_BREAK_WEIGHT
+}
+
+fn default_idle_grace_secs() -> u64 {
+    DEFAULT_IDLE_GRACE_SECS
+}
+
+/// Parses `"90"`, `"45m"`, `"2h"`, `"1h30"`, `"1h 30min"` and the like into
+/// whole minutes. Returns `None` for anything else, including zero.
+fn parse_duration_minutes(input: &str) -> Option<u64> {
+    let input = input.trim().to_ascii_lowercase();
+    if let Ok(minutes) = input.parse::<u64>() {
+        return (minutes > 0).then_some(minutes);
+    }
+
+    let mut total: u64 = 0;
+    let mut rest = input.as_str();
+    while !rest.is_empty() {
+        rest = rest.trim_start();
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let value: u64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let multiplier = match &rest[..unit_len] {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60,
+            "m" | "min" | "mins" | "minute" | "minutes" => 1,
+            // A trailing bare number, as in "1h30", is minutes.
+            "" if rest.is_empty() => 1,
+            _ => return None,
+        };
+        total = total.checked_add(value.checked_mul(multiplier)?)?;
+        rest = &rest[unit_len..];
+    }
+
+    (total > 0).then_some(total)
+}
+
+/// Trims messages and drops blanks and duplicates, keeping first occurrences in order.
+fn normalize_messages(messages: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    messages
+        .into_iter()
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty() && seen.insert(message.clone()))
+        .collect()
+}
+
+/// Random hex secret for the HTTP trigger.
+fn generate_token() -> String {
+    let bytes: [u8; 16] = rng().random();
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn default_overlay_opacity() -> f32 {
+    DEFAULT_OVERLAY_OPACITY
+}
+
+fn default_max_overlays_per_hour() -> u8 {
+    DEFAULT_MAX_OVERLAYS_PER_HOUR
+}
+
+fn default_overlay_color() -> String {
+    DEFAULT_OVERLAY_COLOR.to_string()
+}
+
+/// `#rgb` or `#rrggbb`.
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|digits| {
+        matches!(digits.len(), 3 | 6) && digits.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+fn default_active_debounce_polls() -> u8 {
+    DEFAULT_ACTIVE_DEBOUNCE_POLLS
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_snooze_morning_at() -> NaiveTime {
+    NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default()
+}
+
+fn default_snooze_afternoon_at() -> NaiveTime {
+    NaiveTime::from_hms_opt(13, 0, 0).unwrap_or_default()
+}
+
+fn default_snooze_evening_at() -> NaiveTime {
+    NaiveTime::from_hms_opt(18, 0, 0).unwrap_or_default()
+}
+
 fn clamp_idle_threshold_minutes(minutes: u64) -> u64 {
     minutes.clamp(MIN_IDLE_THRESHOLD_MINUTES, MAX_IDLE_THRESHOLD_MINUTES)
 }
 
+/// Overlays still allowed this hour, after dropping shows older than an hour
+/// from `shown`. `None` when breaks don't use the overlay.
+fn overlay_budget(prefs: &Preferences, shown: &mut VecDeque<Instant>) -> Option<u8> {
+    let hour_ago = Instant::now().checked_sub(Duration::from_secs(60 * 60));
+    while shown
+        .front()
+        .is_some_and(|at| hour_ago.is_some_and(|hour_ago| *at <= hour_ago))
+    {
+        shown.pop_front();
+    }
+    prefs.overlay_on_break.then(|| {
+        let used = u8::try_from(shown.len()).unwrap_or(u8::MAX);
+        prefs.max_overlays_per_hour.saturating_sub(used)
+    })
+}
+
+fn snooze_budget(prefs: &Preferences, snoozed_minutes: u64) -> Option<u64> {
+    prefs
+        .max_total_snooze_minutes
+        .map(|cap| cap.saturating_sub(snoozed_minutes))
+}
+
+/// Whether idle time should be read at all: activity detection is on and a
+/// remote session isn't making it meaningless.
+fn tracks_idle(prefs: &Preferences, remote_session: bool) -> bool {
+    prefs.activity_detection
+        && !(remote_session && prefs.remote_session_idle == RemoteSessionIdle::AssumeActive)
+}
+
+fn is_idle(prefs: &Preferences, idle_secs: u64) -> bool {
+    prefs.activity_detection && idle_secs >= prefs.away_threshold_secs()
+}
+
+/// Later in the day defaults to longer snoozes; recent snooze lengths pull the
+/// result toward what the user actually picks.
+fn smart_snooze_minutes(local_hour: u32, recent_snoozes: &[u64]) -> u64 {
+    let by_time_of_day = match local_hour {
+        0..=11 => 5,
+        12..=16 => 10,
+        _ => 15,
+    };
+
+    if recent_snoozes.is_empty() {
+        return by_time_of_day;
+    }
+
+    let average = recent_snoozes.iter().sum::<u64>() / recent_snoozes.len() as u64;
+    let blended = (by_time_of_day + average) / 2;
+    // Round to the nearest five minutes so the tray shows tidy values.
+    (((blended + 2) / 5) * 5).clamp(MIN_SMART_SNOOZE_MINUTES, MAX_SMART_SNOOZE_MINUTES)
+}
+
 fn apply_autostart(app: &AppHandle<Wry>, enable: bool) {
     use tauri_plugin_autostart::ManagerExt;
 
@@ -337,120 +4801,731 @@ fn apply_autostart(app: &AppHandle<Wry>, enable: bool) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_engine(
     app: AppHandle<Wry>,
     status: Arc<Mutex<StatusSnapshot>>,
+    internals: Arc<Mutex<EngineInternals>>,
+    oneoffs: Arc<Mutex<Vec<OneOffReminder>>>,
+    overrides: Arc<Mutex<SuppressionOverrides>>,
+    stats: Arc<Stats>,
+    idle_detector: Arc<IdleDetector>,
     mut prefs: Preferences,
+    launched_at_login: bool,
     mut control_rx: mpsc::Receiver<ControlMessage>,
 ) {
     apply_autostart(&app, prefs.autostart_enabled);
 
-    let idle_detector = IdleDetector::new(prefs.idle_threshold_secs());
-
-    let mut paused = false;
-    let mut snoozed_until: Option<DateTime<Utc>> = None;
-    let mut next_instant = Instant::now() + prefs.interval_duration();
+    let mut suppression = Suppression::Active;
+    let mut next_instant = skip_days_off(
+        &prefs,
+        safe_next(snap_to_clock(
+            &prefs,
+            Instant::now() + prefs.first_reminder_delay(launched_at_login),
+        )),
+        effective_interval(&prefs, None, 1.0),
+    );
+    // Where the fixed schedule for `preserve_rhythm` starts.
+    let rhythm_anchor = next_instant;
     let sleep = tokio::time::sleep_until(next_instant);
     tokio::pin!(sleep);
-    let mut idle_poll = tokio::time::interval(Duration::from_secs(IDLE_POLL_INTERVAL_SECS));
+    let mut power_mode = PowerMode::Normal;
+    let mut idle_poll = tokio::time::interval(power_mode.idle_poll_interval());
     idle_poll.set_missed_tick_behavior(MissedTickBehavior::Skip);
     let mut was_idle = false;
     let mut last_idle_secs: Option<u64> = None;
+    // Active readings in a row since the user went idle; see
+    // `active_debounce_polls`.
+    let mut active_polls: u8 = 0;
+    // When the current idle stretch began, backdated by the first idle reading.
+    let mut idle_since: Option<Instant> = None;
+    // Previous idle reading, for `count_only_active_time`.
+    let mut last_poll_at: Option<Instant> = None;
+    // Active time still owed before the reminder, and the deadline that left,
+    // as of the last poll; see `remaining_active_time`.
+    let mut active_budget: Option<(Duration, Instant)> = None;
+    // When the last reminder went out, cleared by the user's first reaction.
+    // Stepping away while it's set counts as taking the break.
+    let mut reminder_sent_at: Option<Instant> = None;
+    // The reminder a stretch away answered, for filing the break that ends it.
+    let mut answered_reminder: Option<Instant> = None;
+    let mut on_break = false;
+    // Set when the user came back from a break that still needs confirming;
+    // holds when activity resumed, for the confirmation window.
+    let mut awaiting_return: Option<Instant> = None;
+    let mut phase = EnginePhase::Working;
+    // Minutes snoozed since the last break, counted against the snooze budget.
+    let mut snoozed_minutes: u64 = 0;
+    // Snoozes since the last break, for `snooze_escalating`.
+    let mut consecutive_snoozes: u8 = 0;
+    // The last reminder offered a long break, which `CompleteBreak` accepts.
+    let mut long_break_offered = false;
+    // Original end of a snooze being stretched while the user is away; see
+    // `idle_aware_snooze`.
+    let mut snooze_extended_from: Option<DateTime<Utc>> = None;
+    let mut screen_locked = false;
+    let mut display_off = false;
+    // Last `lid_state` probe, refreshed each idle poll.
+    let mut lid_closed = false;
+    // Last `bluetooth_call` probe, refreshed each idle poll.
+    let mut on_call = false;
+    // Set by the first idle poll, like the other pause reasons.
+    let mut day_off = false;
+    // Whether the `busy_flag_path` file was there at the last idle poll.
+    let mut busy_flag = false;
+    let mut last_suppression: Option<SuppressionRecord> = None;
+    // Refreshed each idle poll; an RDP session can connect while we run.
+    let mut remote_session = remote_session::is_remote().unwrap_or(false);
+    // Whether `weekend_mode` applied at the last idle poll.
+    let mut weekend = prefs.is_weekend(stats::today());
+    let mut blocking_app: Option<String> = None;
+    let process_watcher = Arc::new(ProcessWatcher::new());
+    let mut silent_mode = false;
+    let oneoff_sleep = tokio::time::sleep_until(Instant::now());
+    tokio::pin!(oneoff_sleep);
+    let mut oneoff_armed = false;
+    let summary_sleep = tokio::time::sleep_until(Instant::now());
+    tokio::pin!(summary_sleep);
+    let mut summary_armed = rearm_summary(&prefs, summary_sleep.as_mut());
+    let micro_sleep = tokio::time::sleep_until(Instant::now());
+    tokio::pin!(micro_sleep);
+    let mut micro_armed = rearm_micro_break(&prefs, micro_sleep.as_mut());
+    // When the micro break is due by the wall clock, taken as it's armed:
+    // on some platforms the timer itself doesn't count time spent asleep.
+    let mut micro_due_at = micro_armed.then(|| timestamp_from_instant(micro_sleep.deadline()));
+    let mut sprint: Option<Sprint> = None;
+    let mut picker = MessagePicker::default();
+    // When break overlays were shown, for `max_overlays_per_hour`.
+    let mut overlays_shown: VecDeque<Instant> = VecDeque::new();
+    // Idle episodes of the last hour, and the interval multiplier they give;
+    // see `adaptive_interval`.
+    let mut recent_idle: VecDeque<(Instant, u64)> = VecDeque::new();
+    let engine_started = Instant::now();
+    let mut adapt = 1.0;
+    // Kinds whose next scheduled reminder is dropped; see `skip_next`.
+    let mut skip_once: HashSet<ReminderKind> = HashSet::new();
+    // Where `desk_alternation` is up to, kept in the stats file so it carries
+    // over restarts.
+    let mut desk_posture = stats.desk_posture().unwrap_or(prefs.starting_posture);
+    let sprint_sleep = tokio::time::sleep_until(Instant::now());
+    tokio::pin!(sprint_sleep);
 
     update_status(&app, &status, |snapshot| {
-        snapshot.paused = paused;
-        snapshot.snoozed_until = snoozed_until;
+        suppression.publish(snapshot);
         snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+        snapshot.effective_interval_secs = effective_interval(&prefs, sprint, adapt).as_secs();
+        snapshot.campaign = prefs.campaign_progress(stats::today());
+        snapshot.desk_posture = prefs.desk_alternation.then_some(desk_posture);
         snapshot.idle_seconds = last_idle_secs;
+        snapshot.remote_session = remote_session;
+        snapshot.strict_mode = prefs.strict_mode;
+        snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+        snapshot.snooze_escalation = consecutive_snoozes;
+        snapshot.overlay_budget = prefs
+            .overlay_on_break
+            .then_some(prefs.max_overlays_per_hour);
     });
 
-    loop {
-        tokio::select! {
-            _ = &mut sleep => {
-                let now = Utc::now();
-                let mut notify_user = !paused;
-                let idle_threshold_secs = prefs.idle_threshold_secs();
+    loop {
+        tokio::select! {
+            _ = &mut sleep => {
+                let now = Utc::now();
+                let overridden = |kind| is_overridden(&overrides, kind, now);
+                // A timed pause wakes the engine when it ends; that wakeup
+                // just starts the interval over.
+                let resumed = suppression.expire_pause(now);
+                let mut dropped = if resumed {
+                    None
+                } else {
+                    hold_reason(
+                        &prefs,
+                        Holds {
+                            paused: suppression.is_paused(),
+                            awaiting_return: awaiting_return.is_some(),
+                            screen_locked,
+                            display_off,
+                            lid_closed,
+                            app_running: blocking_app.is_some(),
+                            on_call,
+                            busy_flag,
+                            // A snooze can still run out on a day off.
+                            day_off: !prefs.is_reminder_day(stats::today()),
+                        },
+                        overridden,
+                    )
+                };
+                let mut notify_user = !resumed && dropped.is_none();
+
+                let mut extended = false;
+                if notify_user && suppression.snoozed_until().is_some() {
+                    if suppression.is_snoozed(now) {
+                        notify_user = false;
+                    } else if prefs.idle_aware_snooze
+                        && !prefs.strict_mode
+                        && tracks_idle(&prefs, remote_session)
+                        && (was_idle
+                            || idle_detector
+                                .get_idle_time()
+                                .is_ok_and(|secs| is_idle(&prefs, secs)))
+                    {
+                        let limit = *snooze_extended_from.get_or_insert(now)
+                            + chrono::Duration::minutes(IDLE_SNOOZE_MAX_EXTENSION_MINUTES);
+                        if now < limit {
+                            // Ran out while they're away: wait for them instead.
+                            notify_user = false;
+                            dropped = Some(SuppressedBy::Idle);
+                            extended = true;
+                            let recheck = now + chrono::Duration::seconds(IDLE_SNOOZE_RECHECK_SECS as i64);
+                            suppression = Suppression::SnoozedUntil(recheck.min(limit));
+                        } else {
+                            suppression = Suppression::Active;
+                        }
+                    } else {
+                        suppression = Suppression::Active;
+                    }
+                }
+                if !extended {
+                    snooze_extended_from = None;
+                }
+
+                if notify_user && !prefs.strict_mode && tracks_idle(&prefs, remote_session) {
+                    if let Ok(secs) = idle_detector.get_idle_time() {
+                        last_idle_secs = Some(secs);
+                        if is_idle(&prefs, secs) {
+                            notify_user = false;
+                            dropped = Some(SuppressedBy::Idle);
+                            if !was_idle {
+                                answered_reminder = reminder_sent_at;
+                                on_break = record_response(&stats, &mut reminder_sent_at, secs);
+                                idle_since = Instant::now().checked_sub(Duration::from_secs(secs));
+                            }
+                            was_idle = true;
+                            if prefs.count_idle_skips {
+                                stats.record(StatsEvent::Skipped);
+                            }
+                        } else if was_idle {
+                            // The idle poll confirms returns; until then the
+                            // user is still away.
+                            notify_user = false;
+                            dropped = Some(SuppressedBy::Idle);
+                        }
+                    }
+                } else if !tracks_idle(&prefs, remote_session) {
+                    last_idle_secs = None;
+                }
+
+                if notify_user && silent_mode {
+                    notify_user = false;
+                    dropped = Some(SuppressedBy::SilentMode);
+                }
+
+                if notify_user && skip_once.remove(&ReminderKind::Posture) {
+                    notify_user = false;
+                    dropped = Some(SuppressedBy::SkippedNext);
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.skip_next = pending_skips(&skip_once);
+                    });
+                }
+
+                if let Some(reason) = dropped {
+                    let idle = reason == SuppressedBy::Idle;
+                    last_suppression = Some(SuppressionRecord {
+                        at: now,
+                        reason,
+                        idle_secs: last_idle_secs.filter(|_| idle),
+                        away_threshold_secs: idle.then(|| prefs.away_threshold_secs()),
+                        blocking_app: blocking_app
+                            .clone()
+                            .filter(|_| reason == SuppressedBy::AppRunning),
+                    });
+                }
+
+                let suggest_long_break = notify_user
+                    && prefs
+                        .long_break_after_snoozes
+                        .is_some_and(|after| consecutive_snoozes >= after);
+                if notify_user {
+                    long_break_offered = suggest_long_break;
+                    if suggest_long_break {
+                        send_long_break_reminder(&app, &prefs, consecutive_snoozes).await;
+                    } else {
+                        // Over the hourly overlay budget, or no overlay at
+                        // all, it's a notification.
+                        let use_overlay = overlay_budget(&prefs, &mut overlays_shown)
+                            .is_some_and(|left| left > 0);
+                        let (message, posture) = if prefs.desk_alternation {
+                            (desk_message(desk_posture), Some(desk_posture))
+                        } else {
+                            (picker.pick(&prefs, ReminderKind::Posture), None)
+                        };
+                        let used_overlay = send_reminder_with_posture(
+                            &app,
+                            &prefs,
+                            ReminderKind::Posture,
+                            message,
+                            posture,
+                            false,
+                            use_overlay,
+                        )
+                        .await;
+                        if used_overlay {
+                            overlays_shown.push_back(Instant::now());
+                        }
+                    }
+                    if prefs.desk_alternation && !suggest_long_break {
+                        desk_posture = desk_posture.flipped();
+                        stats.set_desk_posture(desk_posture);
+                    }
+                    stats.record(StatsEvent::ReminderShown);
+                    note_reminder_sent(&stats, &mut reminder_sent_at);
+                    let overlay_left = overlay_budget(&prefs, &mut overlays_shown);
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.last_notification_at = Some(now);
+                        snapshot.idle_seconds = last_idle_secs;
+                        snapshot.long_break_offered = long_break_offered;
+                        snapshot.overlay_budget = overlay_left;
+                        snapshot.desk_posture = prefs.desk_alternation.then_some(desk_posture);
+                    });
+                } else {
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.idle_seconds = last_idle_secs;
+                    });
+                }
+
+                let interval = effective_interval(&prefs, sprint, adapt);
+                next_instant = safe_next(match suppression.snoozed_until().filter(|_| extended) {
+                    Some(until) => Instant::now() + (until - now).to_std().unwrap_or_default(),
+                    // Also puts a snoozed reminder's successor back on the beat.
+                    None if prefs.preserve_rhythm => snap_to_clock(
+                        &prefs,
+                        next_on_rhythm(rhythm_anchor, interval, Instant::now()),
+                    ),
+                    None => next_interval_instant(&prefs, sprint, adapt),
+                });
+                next_instant = skip_days_off(&prefs, next_instant, interval);
+                sleep.as_mut().reset(next_instant);
+                update_status(&app, &status, |snapshot| {
+                    suppression.publish(snapshot);
+                    snapshot.next_trigger_at = if suppression.is_paused() {
+                        None
+                    } else {
+                        Some(timestamp_from_instant(next_instant))
+                    };
+                    snapshot.idle_seconds = last_idle_secs;
+                });
+            }
+            _ = &mut oneoff_sleep, if oneoff_armed => {
+                let now = Utc::now();
+                let due: Vec<OneOffReminder> = {
+                    let mut pending = oneoffs.lock().unwrap();
+                    let (due, remaining) = pending.drain(..).partition(|oneoff| oneoff.at <= now);
+                    *pending = remaining;
+                    due
+                };
+
+                // One-offs were explicitly requested, so they fire regardless of
+                // pause, snooze, or idle and leave the regular interval alone.
+                for oneoff in due {
+                    let message = oneoff
+                        .message
+                        .unwrap_or_else(|| picker.pick(&prefs, ReminderKind::Posture));
+                    send_reminder_message(&app, &prefs, ReminderKind::Posture, message).await;
+                }
+                update_status(&app, &status, |snapshot| {
+                    snapshot.last_notification_at = Some(now);
+                });
+
+                oneoff_armed = rearm_oneoff(&oneoffs, oneoff_sleep.as_mut());
+            }
+            _ = &mut summary_sleep, if summary_armed => {
+                if stats.mark_summarized(stats::today()) {
+                    send_evening_summary(&app, &stats.today());
+                }
+                summary_armed = rearm_summary(&prefs, summary_sleep.as_mut());
+            }
+            _ = &mut sprint_sleep, if sprint.is_some() => {
+                sprint = None;
+                let reschedule = !suppression.holds(Utc::now());
+                if reschedule {
+                    next_instant = next_interval_instant(&prefs, sprint, adapt);
+                    sleep.as_mut().reset(next_instant);
+                }
+                update_status(&app, &status, |snapshot| {
+                    snapshot.sprint_until = None;
+                    if reschedule {
+                        snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                    }
+                });
+            }
+            _ = &mut micro_sleep, if micro_armed => {
+                // Same suppression as regular reminders, but they leave the main
+                // interval and the daily stats alone.
+                let near_main = next_instant.saturating_duration_since(Instant::now())
+                    < Duration::from_secs(MICRO_BREAK_MAIN_GAP_SECS);
+                let now = Utc::now();
+                let held = hold_reason(
+                    &prefs,
+                    Holds {
+                        paused: suppression.is_paused(),
+                        awaiting_return: awaiting_return.is_some(),
+                        screen_locked,
+                        display_off,
+                        lid_closed,
+                        app_running: blocking_app.is_some(),
+                        on_call,
+                        busy_flag,
+                        day_off,
+                    },
+                    |kind| is_overridden(&overrides, kind, now),
+                );
+                let suppressed = suppression.holds(now)
+                    || silent_mode
+                    || held.is_some()
+                    || (!prefs.strict_mode && was_idle);
+                if !suppressed && !near_main {
+                    if skip_once.remove(&ReminderKind::MicroBreak) {
+                        update_status(&app, &status, |snapshot| {
+                            snapshot.skip_next = pending_skips(&skip_once);
+                        });
+                    } else {
+                        send_reminder(&app, &prefs, &mut picker, ReminderKind::MicroBreak).await;
+                    }
+                }
+                micro_armed = rearm_micro_break(&prefs, micro_sleep.as_mut());
+                micro_due_at = micro_armed.then(|| timestamp_from_instant(micro_sleep.deadline()));
+            }
+            _ = idle_poll.tick() => {
+                let mode_now = if prefs.eco_on_battery
+                    && power_source::is_on_battery().unwrap_or(false)
+                {
+                    PowerMode::Eco
+                } else {
+                    PowerMode::Normal
+                };
+                if mode_now != power_mode {
+                    power_mode = mode_now;
+                    let period = power_mode.idle_poll_interval();
+                    idle_poll = tokio::time::interval_at(Instant::now() + period, period);
+                    idle_poll.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.power_mode = power_mode;
+                    });
+                }
+
+                // logind is asked over D-Bus, which blocks.
+                let locked_now = async_runtime::spawn_blocking(screen_lock::is_screen_locked)
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or(false);
+                if locked_now != screen_locked {
+                    screen_locked = locked_now;
+                    // Unlocking means someone is back at the desk; start fresh.
+                    let reset = !screen_locked && !suppression.holds(Utc::now());
+                    let reset = reset && awaiting_return.is_none();
+                    if reset {
+                        suppression.clear_snooze();
+                        consecutive_snoozes = 0;
+                        next_instant = next_interval_instant(&prefs, sprint, adapt);
+                        sleep.as_mut().reset(next_instant);
+                    }
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.screen_locked = screen_locked;
+                        snapshot.pause_reason =
+                            pause_reason(
+                                screen_locked,
+                                display_off,
+                                lid_closed,
+                                blocking_app.is_some(),
+                                on_call,
+                                day_off,
+                                busy_flag,
+                            );
+                        if reset {
+                            suppression.publish(snapshot);
+                            snapshot.snooze_escalation = 0;
+                            snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                        }
+                    });
+                }
+
+                // Scanned once per tick, on a blocking thread.
+                let blocking_now = if prefs.pause_during_processes.is_empty() {
+                    None
+                } else {
+                    let watcher = process_watcher.clone();
+                    let names = prefs.pause_during_processes.clone();
+                    async_runtime::spawn_blocking(move || watcher.find_running(&names))
+                        .await
+                        .ok()
+                        .flatten()
+                };
+                if blocking_now != blocking_app {
+                    blocking_app = blocking_now;
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.blocking_app = blocking_app.clone();
+                        snapshot.pause_reason =
+                            pause_reason(
+                                screen_locked,
+                                display_off,
+                                lid_closed,
+                                blocking_app.is_some(),
+                                on_call,
+                                day_off,
+                                busy_flag,
+                            );
+                    });
+                }
+
+                // X11 and Mutter are both asked synchronously.
+                let display_off_now = prefs.suppress_when_display_off
+                    && async_runtime::spawn_blocking(display_power::is_display_off)
+                        .await
+                        .ok()
+                        .flatten()
+                        .unwrap_or(false);
+                if display_off_now != display_off {
+                    display_off = display_off_now;
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.pause_reason =
+                            pause_reason(
+                                screen_locked,
+                                display_off,
+                                lid_closed,
+                                blocking_app.is_some(),
+                                on_call,
+                                day_off,
+                                busy_flag,
+                            );
+                    });
+                }
 
-                if notify_user {
-                    if let Some(until) = snoozed_until {
-                        if now < until {
-                            notify_user = false;
-                        } else {
-                            snoozed_until = None;
-                        }
-                    }
+                let lid_closed_now = prefs.suppress_when_lid_closed
+                    && async_runtime::spawn_blocking(lid_state::is_lid_closed)
+                        .await
+                        .ok()
+                        .flatten()
+                        .unwrap_or(false);
+                if lid_closed_now != lid_closed {
+                    lid_closed = lid_closed_now;
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.lid_closed = lid_closed;
+                        snapshot.pause_reason = pause_reason(
+                            screen_locked,
+                            display_off,
+                            lid_closed,
+                            blocking_app.is_some(),
+                            on_call,
+                            day_off,
+                            busy_flag,
+                        );
+                    });
                 }
 
-                if notify_user && prefs.activity_detection {
-                    if let Ok(secs) = idle_detector.get_idle_time() {
-                        last_idle_secs = Some(secs);
-                        if secs >= idle_threshold_secs {
-                            notify_user = false;
-                            was_idle = true;
-                        } else {
-                            was_idle = false;
-                        }
+                // Weekend mode switches over at midnight; the next reminder
+                // already picks the weekend messages, this keeps the shown
+                // interval in step and moves a pending reminder onto the
+                // weekend interval (or back off it).
+                let weekend_now = prefs.is_weekend(stats::today());
+                if weekend_now != weekend {
+                    weekend = weekend_now;
+                    let interval_secs = effective_interval(&prefs, sprint, adapt).as_secs();
+                    let reschedule = prefs.weekend_interval_minutes.is_some()
+                        && !suppression.holds(Utc::now());
+                    if reschedule {
+                        next_instant = next_interval_instant(&prefs, sprint, adapt);
+                        sleep.as_mut().reset(next_instant);
                     }
-                } else if !prefs.activity_detection {
-                    last_idle_secs = None;
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.effective_interval_secs = interval_secs;
+                        if reschedule {
+                            snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                        }
+                    });
                 }
 
-                if notify_user {
-                    send_reminder(&app, &prefs).await;
+                let day_off_now = !prefs.is_reminder_day(stats::today());
+                if day_off_now != day_off {
+                    day_off = day_off_now;
                     update_status(&app, &status, |snapshot| {
-                        snapshot.last_notification_at = Some(now);
-                        snapshot.idle_seconds = last_idle_secs;
+                        snapshot.pause_reason = pause_reason(
+                            screen_locked,
+                            display_off,
+                            lid_closed,
+                            blocking_app.is_some(),
+                            on_call,
+                            day_off,
+                            busy_flag,
+                        );
                     });
-                } else {
+                }
+
+                let busy_now = prefs
+                    .busy_flag_path
+                    .as_deref()
+                    .is_some_and(|path| Path::new(path).exists());
+                if busy_now != busy_flag {
+                    busy_flag = busy_now;
                     update_status(&app, &status, |snapshot| {
-                        snapshot.idle_seconds = last_idle_secs;
+                        snapshot.busy_flag = busy_flag;
+                        snapshot.pause_reason = pause_reason(
+                            screen_locked,
+                            display_off,
+                            lid_closed,
+                            blocking_app.is_some(),
+                            on_call,
+                            day_off,
+                            busy_flag,
+                        );
                     });
                 }
 
-                next_instant = Instant::now() + prefs.interval_duration();
-                sleep.as_mut().reset(next_instant);
-                update_status(&app, &status, |snapshot| {
-                    snapshot.paused = paused;
-                    snapshot.snoozed_until = snoozed_until;
-                    snapshot.next_trigger_at = if paused {
-                        None
-                    } else {
-                        Some(timestamp_from_instant(next_instant))
-                    };
-                    snapshot.idle_seconds = last_idle_secs;
-                });
-            }
-            _ = idle_poll.tick() => {
-                if prefs.activity_detection {
+                let on_call_now = prefs.suppress_on_bluetooth_call
+                    && async_runtime::spawn_blocking(bluetooth_call::is_on_call)
+                        .await
+                        .ok()
+                        .flatten()
+                        .unwrap_or(false);
+                if on_call_now != on_call {
+                    on_call = on_call_now;
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.pause_reason =
+                            pause_reason(
+                                screen_locked,
+                                display_off,
+                                lid_closed,
+                                blocking_app.is_some(),
+                                on_call,
+                                day_off,
+                                busy_flag,
+                            );
+                    });
+                }
+
+                let remote_now = remote_session::is_remote().unwrap_or(false);
+                if remote_now != remote_session {
+                    remote_session = remote_now;
+                    update_status(&app, &status, |snapshot| {
+                        snapshot.remote_session = remote_session;
+                    });
+                }
+
+                if tracks_idle(&prefs, remote_session) {
                     if let Ok(secs) = idle_detector.get_idle_time() {
                         last_idle_secs = Some(secs);
-                        let idle_now = secs >= prefs.idle_threshold_secs();
+                        let mut idle_now = is_idle(&prefs, secs);
+                        if idle_now {
+                            active_polls = 0;
+                        } else if was_idle {
+                            active_polls = active_polls.saturating_add(1);
+                            idle_now = active_polls < prefs.active_debounce_polls;
+                        }
                         let mut updated_next = false;
+                        let mut end_break = false;
+
+                        // Active-time mode: count the active part of each poll
+                        // against the interval, and fire once it's all counted.
+                        let polled_at = Instant::now();
+                        let since_poll = last_poll_at.replace(polled_at).map(|at| polled_at - at);
+                        if let Some(since_poll) = since_poll.filter(|_| {
+                            prefs.count_only_active_time
+                                && !suppression.holds(Utc::now())
+                                && awaiting_return.is_none()
+                        }) {
+                            let active = active_part(since_poll, secs, idle_now);
+                            let remaining = remaining_active_time(
+                                active_budget,
+                                next_instant,
+                                polled_at,
+                                active,
+                            );
+                            let deadline = polled_at + remaining;
+                            active_budget = Some((remaining, deadline));
+                            if deadline != next_instant {
+                                next_instant = deadline;
+                                sleep.as_mut().reset(next_instant);
+                                updated_next = true;
+                            }
+                        } else {
+                            active_budget = None;
+                        }
+
                         if idle_now {
+                            if !was_idle {
+                                answered_reminder = reminder_sent_at;
+                                on_break = record_response(&stats, &mut reminder_sent_at, secs)
+                                    || awaiting_return.is_some();
+                                idle_since = Instant::now().checked_sub(Duration::from_secs(secs));
+                            }
                             was_idle = true;
                         } else if was_idle {
                             was_idle = false;
-                            if !paused {
-                                let now = Utc::now();
-                                if let Some(until) = snoozed_until {
-                                    if until <= now {
-                                        snoozed_until = None;
-                                    }
-                                }
-                                let snooze_active = snoozed_until.map(|until| until > Utc::now()).unwrap_or(false);
-                                if !snooze_active {
-                                    next_instant = Instant::now() + prefs.interval_duration();
-                                    sleep.as_mut().reset(next_instant);
-                                    updated_next = true;
-                                }
+                            if let Some(since) = idle_since.take() {
+                                // Leave out the polls spent confirming the return.
+                                let confirming = u64::from(active_polls.saturating_sub(1))
+                                    * power_mode.idle_poll_interval().as_secs();
+                                let away = since.elapsed().as_secs().saturating_sub(confirming);
+                                stats.record(StatsEvent::IdleEpisode(away));
+                                recent_idle.push_back((Instant::now(), away));
+                            }
+                            let answered = answered_reminder.take().map(local_hour_of);
+                            if awaiting_return.is_none() {
+                                stats.record_answer(StatsEvent::BreakTaken, answered);
+                                snoozed_minutes = 0;
+                                consecutive_snoozes = 0;
+                            }
+                            // Stepping away already was the break.
+                            long_break_offered = false;
+                            if on_break && prefs.require_return_confirmation {
+                                awaiting_return = Some(Instant::now());
+                            } else {
+                                end_break = true;
+                            }
+                        } else if awaiting_return.is_some_and(|since| {
+                            since.elapsed() >= Duration::from_secs(RETURN_CONFIRMATION_WINDOW_SECS)
+                        }) {
+                            end_break = true;
+                        }
+
+                        if end_break {
+                            let completed = on_break;
+                            on_break = false;
+                            awaiting_return = None;
+                            if !suppression.is_paused() && snooze_extended_from.is_some() {
+                                // The stretched snooze was waiting for this.
+                                suppression.clear_snooze();
+                                next_instant = after_return_instant();
+                                sleep.as_mut().reset(next_instant);
+                                updated_next = true;
+                            } else if !suppression.holds(Utc::now()) {
+                                suppression.clear_snooze();
+                                next_instant = if completed {
+                                    after_break_instant(&prefs, sprint, adapt)
+                                } else {
+                                    next_interval_instant(&prefs, sprint, adapt)
+                                };
+                                sleep.as_mut().reset(next_instant);
+                                updated_next = true;
                             }
                         }
+                        adapt = adaptive_scale(&prefs, &mut recent_idle, engine_started, Instant::now());
+                        let interval_secs = effective_interval(&prefs, sprint, adapt).as_secs();
+                        let active_secs = prefs.count_only_active_time.then(|| {
+                            effective_interval(&prefs, sprint, adapt)
+                                .saturating_sub(next_instant.saturating_duration_since(Instant::now()))
+                                .as_secs()
+                        });
 
                         update_status(&app, &status, |snapshot| {
                             snapshot.idle_seconds = last_idle_secs;
-                            snapshot.paused = paused;
-                            snapshot.snoozed_until = snoozed_until;
-                            if paused {
+                            snapshot.active_secs = active_secs;
+                            snapshot.effective_interval_secs = interval_secs;
+                            snapshot.campaign = prefs.campaign_progress(stats::today());
+                            suppression.publish(snapshot);
+                            snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                            snapshot.snooze_escalation = consecutive_snoozes;
+                            snapshot.long_break_offered = long_break_offered;
+                            if suppression.is_paused() || awaiting_return.is_some() {
                                 snapshot.next_trigger_at = None;
                             } else if updated_next {
                                 snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
@@ -458,8 +5533,12 @@ async fn run_engine(
                         });
                     }
                 } else if last_idle_secs.is_some() || was_idle {
+                    last_poll_at = None;
                     last_idle_secs = None;
                     was_idle = false;
+                    on_break = false;
+                    awaiting_return = None;
+                    idle_since = None;
                     update_status(&app, &status, |snapshot| {
                         snapshot.idle_seconds = last_idle_secs;
                     });
@@ -468,26 +5547,51 @@ async fn run_engine(
             Some(msg) = control_rx.recv() => {
                 match msg {
                     ControlMessage::PreferencesUpdated(new_prefs) => {
-                        prefs = new_prefs;
-                        let now = Utc::now();
-                        let mut recalculated_next = Instant::now() + prefs.interval_duration();
-                        if let Some(until) = snoozed_until {
-                            if until > now {
-                                if let Ok(wait) = (until - now).to_std() {
-                                    recalculated_next = Instant::now() + wait;
-                                } else {
-                                    recalculated_next = Instant::now();
-                                }
-                            } else {
-                                snoozed_until = None;
-                            }
+                        if new_prefs.starting_posture != prefs.starting_posture
+                            || (new_prefs.desk_alternation && !prefs.desk_alternation)
+                        {
+                            desk_posture = new_prefs.starting_posture;
+                            stats.set_desk_posture(desk_posture);
+                        }
+                        prefs = *new_prefs;
+                        adapt = adaptive_scale(&prefs, &mut recent_idle, engine_started, Instant::now());
+                        if !prefs.require_return_confirmation && awaiting_return.take().is_some() {
+                            on_break = false;
+                        }
+                        summary_armed = rearm_summary(&prefs, summary_sleep.as_mut());
+                        micro_armed = rearm_micro_break(&prefs, micro_sleep.as_mut());
+                        micro_due_at =
+                            micro_armed.then(|| timestamp_from_instant(micro_sleep.deadline()));
+                        let (wait, still_snoozed) = wait_after_preferences_change(
+                            suppression.snoozed_until(),
+                            Utc::now(),
+                            effective_interval(&prefs, sprint, adapt),
+                        );
+                        next_instant = Instant::now() + wait;
+                        if still_snoozed.is_none() {
+                            next_instant = snap_to_clock(&prefs, next_instant);
+                        }
+                        next_instant = skip_days_off(
+                            &prefs,
+                            safe_next(next_instant),
+                            effective_interval(&prefs, sprint, adapt),
+                        );
+                        if still_snoozed.is_none() {
+                            suppression.clear_snooze();
                         }
-                        next_instant = recalculated_next;
                         sleep.as_mut().reset(next_instant);
+                        let overlay_left = overlay_budget(&prefs, &mut overlays_shown);
+                        let interval_secs = effective_interval(&prefs, sprint, adapt).as_secs();
                         update_status(&app, &status, |snapshot| {
-                            snapshot.paused = paused;
-                            snapshot.snoozed_until = snoozed_until;
-                            snapshot.next_trigger_at = if paused {
+                            snapshot.overlay_budget = overlay_left;
+                            snapshot.effective_interval_secs = interval_secs;
+                            snapshot.campaign = prefs.campaign_progress(stats::today());
+                            snapshot.desk_posture = prefs.desk_alternation.then_some(desk_posture);
+                            snapshot.strict_mode = prefs.strict_mode;
+                            snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                            snapshot.snooze_escalation = consecutive_snoozes;
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = if suppression.is_paused() {
                                 None
                             } else {
                                 Some(timestamp_from_instant(next_instant))
@@ -496,14 +5600,15 @@ async fn run_engine(
                         });
                     }
                     ControlMessage::Pause(flag) => {
-                        paused = flag;
-                        if !paused {
-                            next_instant = Instant::now() + prefs.interval_duration();
+                        // Resuming drops any snooze from before the pause too.
+                        suppression = Suppression::from_pause(flag);
+                        if !flag {
+                            next_instant = next_interval_instant(&prefs, sprint, adapt);
                             sleep.as_mut().reset(next_instant);
                         }
                         update_status(&app, &status, |snapshot| {
-                            snapshot.paused = paused;
-                            snapshot.next_trigger_at = if paused {
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = if flag {
                                 None
                             } else {
                                 Some(timestamp_from_instant(next_instant))
@@ -511,26 +5616,98 @@ async fn run_engine(
                             snapshot.idle_seconds = last_idle_secs;
                         });
                     }
-                    ControlMessage::Snooze(duration) => {
-                        let until = Utc::now() + chrono::Duration::from_std(duration).unwrap();
-                        snoozed_until = Some(until);
-                        next_instant = Instant::now() + duration;
+                    ControlMessage::PauseUntil(until) => {
+                        suppression = Suppression::PausedUntil(until);
+                        next_instant = safe_next(instant_from_timestamp(until));
                         sleep.as_mut().reset(next_instant);
                         update_status(&app, &status, |snapshot| {
-                            snapshot.snoozed_until = snoozed_until;
-                            snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = None;
+                            snapshot.idle_seconds = last_idle_secs;
+                        });
+                    }
+                    ControlMessage::SilentMode(enabled) => {
+                        silent_mode = enabled;
+                        update_status(&app, &status, |snapshot| {
+                            snapshot.silent_mode = silent_mode;
+                        });
+                    }
+                    ControlMessage::Snooze(duration) => {
+                        // While paused there's nothing to snooze and no
+                        // reminder to force; `suppression.snooze` refuses it
+                        // below and nothing is counted.
+                        if snooze_budget(&prefs, snoozed_minutes) == Some(0)
+                            && !suppression.is_paused()
+                        {
+                            // Out of snooze budget: the break happens now instead.
+                            send_reminder(&app, &prefs, &mut picker, ReminderKind::Posture).await;
+                            stats.record(StatsEvent::ReminderShown);
+                            note_reminder_sent(&stats, &mut reminder_sent_at);
+                            let now = Utc::now();
+                            suppression.clear_snooze();
+                            if !suppression.is_paused() {
+                                next_instant = next_interval_instant(&prefs, sprint, adapt);
+                                sleep.as_mut().reset(next_instant);
+                            }
+                            update_status(&app, &status, |snapshot| {
+                                snapshot.last_notification_at = Some(now);
+                                suppression.publish(snapshot);
+                                snapshot.next_trigger_at = if suppression.is_paused() {
+                                    None
+                                } else {
+                                    Some(timestamp_from_instant(next_instant))
+                                };
+                                snapshot.idle_seconds = last_idle_secs;
+                            });
+                        } else {
+                            let until = Utc::now() + chrono::Duration::from_std(duration).unwrap();
+                            let snoozed = suppression.snooze(until);
+                            if snoozed {
+                                stats.record(StatsEvent::Snoozed);
+                                record_response(&stats, &mut reminder_sent_at, 0);
+                                snoozed_minutes += duration.as_secs().div_ceil(60);
+                                consecutive_snoozes = consecutive_snoozes.saturating_add(1);
+                                snooze_extended_from = None;
+                                next_instant = safe_next(Instant::now() + duration);
+                                sleep.as_mut().reset(next_instant);
+                            }
+                            update_status(&app, &status, |snapshot| {
+                                suppression.publish(snapshot);
+                                if snoozed {
+                                    snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                                }
+                                snapshot.idle_seconds = last_idle_secs;
+                                snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                                snapshot.snooze_escalation = consecutive_snoozes;
+                            });
+                        }
+                    }
+                    ControlMessage::SnoozeUntil(until) => {
+                        let snoozed = suppression.snooze(until);
+                        if snoozed {
+                            stats.record(StatsEvent::Snoozed);
+                            record_response(&stats, &mut reminder_sent_at, 0);
+                            snooze_extended_from = None;
+                            next_instant = safe_next(instant_from_timestamp(until));
+                            sleep.as_mut().reset(next_instant);
+                        }
+                        update_status(&app, &status, |snapshot| {
+                            suppression.publish(snapshot);
+                            if snoozed {
+                                snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                            }
                             snapshot.idle_seconds = last_idle_secs;
                         });
                     }
                     ControlMessage::ClearSnooze => {
-                        snoozed_until = None;
-                        if !paused {
-                            next_instant = Instant::now() + prefs.interval_duration();
+                        suppression.clear_snooze();
+                        if !suppression.is_paused() {
+                            next_instant = next_interval_instant(&prefs, sprint, adapt);
                             sleep.as_mut().reset(next_instant);
                         }
                         update_status(&app, &status, |snapshot| {
-                            snapshot.snoozed_until = None;
-                            snapshot.next_trigger_at = if paused {
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = if suppression.is_paused() {
                                 None
                             } else {
                                 Some(timestamp_from_instant(next_instant))
@@ -539,14 +5716,24 @@ async fn run_engine(
                         });
                     }
                     ControlMessage::SkipCurrent => {
-                        snoozed_until = None;
-                        if !paused {
-                            next_instant = Instant::now() + prefs.interval_duration();
+                        stats.record(StatsEvent::Skipped);
+                        record_response(&stats, &mut reminder_sent_at, 0);
+                        suppression.clear_snooze();
+                        if !suppression.is_paused() {
+                            let interval = effective_interval(&prefs, sprint, adapt);
+                            next_instant = safe_next(snap_to_clock(
+                                &prefs,
+                                if prefs.preserve_rhythm {
+                                    next_on_rhythm(rhythm_anchor, interval, Instant::now())
+                                } else {
+                                    Instant::now() + interval
+                                },
+                            ));
                             sleep.as_mut().reset(next_instant);
                         }
                         update_status(&app, &status, |snapshot| {
-                            snapshot.snoozed_until = None;
-                            snapshot.next_trigger_at = if paused {
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = if suppression.is_paused() {
                                 None
                             } else {
                                 Some(timestamp_from_instant(next_instant))
@@ -554,14 +5741,238 @@ async fn run_engine(
                             snapshot.idle_seconds = last_idle_secs;
                         });
                     }
-                    ControlMessage::TriggerNow => {
-                        send_reminder(&app, &prefs).await;
+                    ControlMessage::MiniBreak => {
+                        stats.record_answer(
+                            StatsEvent::MiniBreak,
+                            reminder_sent_at.map(local_hour_of),
+                        );
+                        record_response(&stats, &mut reminder_sent_at, 0);
+                        snoozed_minutes = 0;
+                        consecutive_snoozes = 0;
+                        suppression.clear_snooze();
+                        if !suppression.is_paused() {
+                            next_instant = next_interval_instant(&prefs, sprint, adapt);
+                            sleep.as_mut().reset(next_instant);
+                        }
+                        update_status(&app, &status, |snapshot| {
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = if suppression.is_paused() {
+                                None
+                            } else {
+                                Some(timestamp_from_instant(next_instant))
+                            };
+                            snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                            snapshot.snooze_escalation = consecutive_snoozes;
+                        });
+                    }
+                    ControlMessage::ManualBreak(minutes, note) => {
+                        stats.record_manual_break(minutes, note);
+                        snoozed_minutes = 0;
+                        consecutive_snoozes = 0;
+                        suppression.clear_snooze();
+                        if !suppression.is_paused() {
+                            next_instant = next_interval_instant(&prefs, sprint, adapt);
+                            sleep.as_mut().reset(next_instant);
+                        }
+                        update_status(&app, &status, |snapshot| {
+                            suppression.publish(snapshot);
+                            snapshot.next_trigger_at = if suppression.is_paused() {
+                                None
+                            } else {
+                                Some(timestamp_from_instant(next_instant))
+                            };
+                            snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                            snapshot.snooze_escalation = consecutive_snoozes;
+                        });
+                    }
+                    ControlMessage::CompleteBreak => {
+                        if long_break_offered {
+                            // Yes to the long break: it counts now, and the
+                            // next reminder waits for it to be over.
+                            long_break_offered = false;
+                            awaiting_return = None;
+                            on_break = false;
+                            stats.record_answer(
+                                StatsEvent::LongBreak,
+                                reminder_sent_at.map(local_hour_of),
+                            );
+                            record_response(&stats, &mut reminder_sent_at, 0);
+                            snoozed_minutes = 0;
+                            consecutive_snoozes = 0;
+                            suppression.clear_snooze();
+                            if !suppression.is_paused() {
+                                next_instant = after_break_instant(&prefs, sprint, adapt)
+                                    + Duration::from_secs(LONG_BREAK_MINUTES * 60);
+                                sleep.as_mut().reset(next_instant);
+                            }
+                            update_status(&app, &status, |snapshot| {
+                                suppression.publish(snapshot);
+                                snapshot.long_break_offered = false;
+                                snapshot.next_trigger_at = if suppression.is_paused() {
+                                    None
+                                } else {
+                                    Some(timestamp_from_instant(next_instant))
+                                };
+                                snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                                snapshot.snooze_escalation = consecutive_snoozes;
+                            });
+                        } else if awaiting_return.take().is_some() {
+                            on_break = false;
+                            if !suppression.holds(Utc::now()) {
+                                suppression.clear_snooze();
+                                next_instant = after_break_instant(&prefs, sprint, adapt);
+                                sleep.as_mut().reset(next_instant);
+                            }
+                            update_status(&app, &status, |snapshot| {
+                                suppression.publish(snapshot);
+                                snapshot.next_trigger_at = if suppression.is_paused() {
+                                    None
+                                } else {
+                                    Some(timestamp_from_instant(next_instant))
+                                };
+                            });
+                        }
+                    }
+                    ControlMessage::SkipNext(kind) => {
+                        skip_once.insert(kind);
+                        update_status(&app, &status, |snapshot| {
+                            snapshot.skip_next = pending_skips(&skip_once);
+                        });
+                    }
+                    ControlMessage::ClearStats => {
+                        stats.clear();
+                        reminder_sent_at = None;
+                        snoozed_minutes = 0;
+                        consecutive_snoozes = 0;
+                        update_status(&app, &status, |snapshot| {
+                            snapshot.snooze_budget_minutes = snooze_budget(&prefs, snoozed_minutes);
+                            snapshot.snooze_escalation = consecutive_snoozes;
+                        });
+                        let _ = app.emit(
+                            events::LOG_EVENT,
+                            events::LogPayload {
+                                level: "info".into(),
+                                message: "stats and history cleared".into(),
+                            },
+                        );
+                    }
+                    ControlMessage::Sprint(next) => {
+                        sprint = next;
+                        if let Some(active) = sprint {
+                            sprint_sleep.as_mut().reset(instant_from_timestamp(active.until));
+                        }
+                        let reschedule = !suppression.holds(Utc::now());
+                        if reschedule {
+                            next_instant = next_interval_instant(&prefs, sprint, adapt);
+                            sleep.as_mut().reset(next_instant);
+                        }
+                        update_status(&app, &status, |snapshot| {
+                            snapshot.sprint_until = sprint.map(|active| active.until);
+                            if reschedule {
+                                snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
+                            }
+                        });
+                    }
+                    ControlMessage::OneOffsChanged => {
+                        oneoff_armed = rearm_oneoff(&oneoffs, oneoff_sleep.as_mut());
+                    }
+                    ControlMessage::CatchUp(mode, overdue, reply) => {
+                        let now = Utc::now();
+                        let held = hold_reason(
+                            &prefs,
+                            Holds {
+                                paused: suppression.is_paused(),
+                                awaiting_return: awaiting_return.is_some(),
+                                screen_locked,
+                                display_off,
+                                lid_closed,
+                                app_running: blocking_app.is_some(),
+                                on_call,
+                                busy_flag,
+                                day_off: !prefs.is_reminder_day(stats::today()),
+                            },
+                            |kind| is_overridden(&overrides, kind, now),
+                        )
+                        .is_some();
+                        let fire = mode == CatchUpMode::FireOnce && !silent_mode && !held;
+                        // Overdue one-offs are folded into the single
+                        // reminder when there is one. Otherwise they stay
+                        // queued and go out on their own like always, since
+                        // the user asked for each of them.
+                        let mut messages = Vec::new();
+                        if fire && overdue.contains(&CatchUpChannel::OneOff) {
+                            let due: Vec<OneOffReminder> = {
+                                let mut pending = oneoffs.lock().unwrap();
+                                let (due, remaining) =
+                                    pending.drain(..).partition(|oneoff| oneoff.at <= now);
+                                *pending = remaining;
+                                due
+                            };
+                            messages = due
+                                .into_iter()
+                                .filter_map(|oneoff| oneoff.message)
+                                .collect();
+                            oneoff_armed = rearm_oneoff(&oneoffs, oneoff_sleep.as_mut());
+                        }
+                        if overdue.contains(&CatchUpChannel::MicroBreak) {
+                            micro_armed = rearm_micro_break(&prefs, micro_sleep.as_mut());
+                            micro_due_at =
+                                micro_armed.then(|| timestamp_from_instant(micro_sleep.deadline()));
+                        }
+                        let reschedule = overdue.contains(&CatchUpChannel::Reminder)
+                            && !suppression.is_paused();
+                        if reschedule {
+                            // A snooze that ran out during sleep is over.
+                            suppression.clear_snooze();
+                            next_instant = next_interval_instant(&prefs, sprint, adapt);
+                            sleep.as_mut().reset(next_instant);
+                        }
+
+                        if fire {
+                            // Only a micro-break overdue gets a micro-break.
+                            let kind = if overdue == [CatchUpChannel::MicroBreak] {
+                                ReminderKind::MicroBreak
+                            } else {
+                                ReminderKind::Posture
+                            };
+                            let message = if messages.is_empty() {
+                                picker.pick(&prefs, kind)
+                            } else {
+                                messages.join("\n")
+                            };
+                            send_reminder_message(&app, &prefs, kind, message).await;
+                            if reschedule {
+                                stats.record(StatsEvent::ReminderShown);
+                                note_reminder_sent(&stats, &mut reminder_sent_at);
+                            }
+                        }
+                        let _ = reply.send(fire);
+                        update_status(&app, &status, |snapshot| {
+                            if fire {
+                                snapshot.last_notification_at = Some(now);
+                            }
+                            if reschedule {
+                                suppression.publish(snapshot);
+                                snapshot.next_trigger_at =
+                                    Some(timestamp_from_instant(next_instant));
+                            }
+                        });
+                    }
+                    ControlMessage::TriggerNow(message) => {
+                        match message {
+                            Some(message) => {
+                                send_reminder_message(&app, &prefs, ReminderKind::Posture, message)
+                                    .await
+                            }
+                            None => send_reminder(&app, &prefs, &mut picker, ReminderKind::Posture).await,
+                        }
+                        note_reminder_sent(&stats, &mut reminder_sent_at);
                         let now = Utc::now();
                         update_status(&app, &status, |snapshot| {
                             snapshot.last_notification_at = Some(now);
                             snapshot.idle_seconds = last_idle_secs;
                         });
-                        next_instant = Instant::now() + prefs.interval_duration();
+                        next_instant = next_interval_instant(&prefs, sprint, adapt);
                         sleep.as_mut().reset(next_instant);
                         update_status(&app, &status, |snapshot| {
                             snapshot.next_trigger_at = Some(timestamp_from_instant(next_instant));
@@ -571,9 +5982,160 @@ async fn run_engine(
                 }
             }
         }
+
+        // Other controls may have rescheduled; a timed pause still wakes
+        // the engine when it ends, and a paused engine or held break still
+        // shows no next reminder.
+        if let Some(until) = suppression.paused_until() {
+            let wake = safe_next(instant_from_timestamp(until));
+            if wake.max(next_instant) - wake.min(next_instant) > Duration::from_secs(1) {
+                next_instant = wake;
+                sleep.as_mut().reset(next_instant);
+            }
+        }
+        if (suppression.is_paused() || awaiting_return.is_some())
+            && status.lock().unwrap().next_trigger_at.is_some()
+        {
+            update_status(&app, &status, |snapshot| {
+                snapshot.next_trigger_at = None;
+            });
+        }
+
+        let current = EnginePhase::from_state(
+            suppression.is_paused(),
+            suppression.is_snoozed(Utc::now()),
+            was_idle || screen_locked || awaiting_return.is_some(),
+            on_break,
+        );
+        if current != phase {
+            phase = current;
+            update_status(&app, &status, |snapshot| {
+                snapshot.phase = phase;
+            });
+        }
+
+        *internals.lock().unwrap() = EngineInternals {
+            suppression,
+            snoozed_minutes,
+            next_instant,
+            was_idle,
+            last_idle_secs,
+            on_break,
+            awaiting_return: awaiting_return.is_some(),
+            screen_locked,
+            display_off,
+            lid_closed,
+            on_call,
+            busy_flag,
+            silent_mode,
+            last_suppression: last_suppression.clone(),
+            micro_due_at,
+            phase,
+            interval: effective_interval(&prefs, sprint, adapt),
+            sprint,
+            adaptive_scale: adapt,
+            oneoff_at: oneoff_armed.then(|| oneoff_sleep.deadline()),
+            summary_at: summary_armed.then(|| summary_sleep.deadline()),
+            micro_break_at: micro_armed.then(|| micro_sleep.deadline()),
+        };
+    }
+}
+
+fn ensure_sound_exists(path: &str) -> Result<(), AppStateError> {
+    if Path::new(path).is_file() {
+        Ok(())
+    } else {
+        Err(AppStateError::SoundNotFound(path.to_string()))
+    }
+}
+
+/// Starts the response clock for a reminder. One still waiting for a reaction
+/// was superseded and counts as ignored.
+fn note_reminder_sent(stats: &Stats, sent_at: &mut Option<Instant>) {
+    if sent_at.replace(Instant::now()).is_some() {
+        stats.record(StatsEvent::Ignored);
+    }
+}
+
+/// Local hour of the day `at` fell in.
+fn local_hour_of(at: Instant) -> u8 {
+    let ago = chrono::Duration::from_std(at.elapsed()).unwrap_or_default();
+    (Local::now() - ago).hour() as u8
+}
+
+/// Records how long the user took to react to the pending reminder, if any.
+/// `reacted_secs_ago` backdates reactions noticed late, like going idle.
+/// Returns whether there was a pending reminder.
+fn record_response(stats: &Stats, sent_at: &mut Option<Instant>, reacted_secs_ago: u64) -> bool {
+    match sent_at.take() {
+        Some(at) => {
+            let latency = at.elapsed().as_secs().saturating_sub(reacted_secs_ago);
+            stats.record(StatsEvent::Responded(latency));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Points the one-off timer at the earliest pending one-off, returning whether
+/// there is anything left to wait for.
+fn rearm_oneoff(
+    oneoffs: &Mutex<Vec<OneOffReminder>>,
+    sleep: std::pin::Pin<&mut tokio::time::Sleep>,
+) -> bool {
+    let earliest = oneoffs.lock().unwrap().iter().map(|oneoff| oneoff.at).min();
+    match earliest {
+        Some(at) => {
+            sleep.reset(instant_from_timestamp(at));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Points the summary timer at the next local `work_end`, returning whether the
+/// evening summary is enabled at all.
+fn rearm_summary(prefs: &Preferences, sleep: std::pin::Pin<&mut tokio::time::Sleep>) -> bool {
+    let Some(work_end) = prefs.work_end.filter(|_| prefs.evening_summary) else {
+        return false;
+    };
+    sleep.reset(instant_from_timestamp(next_local_occurrence(
+        work_end,
+        Local::now(),
+    )));
+    true
+}
+
+/// Schedules the next micro-break a random 50-150% of the average spacing
+/// away, so they average out to `micro_breaks_per_hour` without a pattern.
+fn rearm_micro_break(prefs: &Preferences, sleep: std::pin::Pin<&mut tokio::time::Sleep>) -> bool {
+    if !prefs.micro_breaks_enabled {
+        return false;
+    }
+    let spacing_secs = 3600 / u64::from(prefs.micro_breaks_per_hour.max(1));
+    let wait = rng().random_range(spacing_secs / 2..=spacing_secs * 3 / 2);
+    sleep.reset(Instant::now() + Duration::from_secs(wait));
+    true
+}
+
+/// The next time the local clock reads `time`, strictly after `now`.
+fn next_local_occurrence(time: NaiveTime, now: DateTime<Local>) -> DateTime<Utc> {
+    let mut date = now.date_naive();
+    loop {
+        // `earliest` skips times that don't exist on DST-change days.
+        if let Some(candidate) = date.and_time(time).and_local_timezone(Local).earliest() {
+            if candidate > now {
+                return candidate.with_timezone(&Utc);
+            }
+        }
+        date = date.succ_opt().unwrap_or(date);
     }
 }
 
+fn instant_from_timestamp(at: DateTime<Utc>) -> Instant {
+    Instant::now() + (at - Utc::now()).to_std().unwrap_or_default()
+}
+
 fn timestamp_from_instant(instant: Instant) -> DateTime<Utc> {
     let now = Instant::now();
     let offset = if instant >= now {
@@ -598,17 +6160,29 @@ where
         tray_state.sync(&snapshot);
     }
 
+    if let Some(status_feed) = app.try_state::<StatusFeed>() {
+        status_feed.publish(&snapshot);
+    }
+
     let _ = app.emit(
         events::STATUS_EVENT,
         events::StatusPayload { status: snapshot },
     );
 }
 
-async fn send_reminder(app: &AppHandle<Wry>, prefs: &Preferences) {
-    let message = choose_reminder_message();
+async fn send_reminder(
+    app: &AppHandle<Wry>,
+    prefs: &Preferences,
+    picker: &mut MessagePicker,
+    kind: ReminderKind,
+) {
+    send_reminder_message(app, prefs, kind, picker.pick(prefs, kind)).await;
+}
 
+/// First notification icon that exists on disk, as a canonical path.
+fn find_notification_icon(app: &AppHandle<Wry>) -> Option<String> {
     // Try multiple icon paths
-    let icon_path = [
+    [
         // Try from Cargo manifest directory (dev mode - this is src-tauri/)
         std::env::var("CARGO_MANIFEST_DIR")
             .ok()
@@ -633,28 +6207,170 @@ async fn send_reminder(app: &AppHandle<Wry>, prefs: &Preferences) {
         if exists {
             eprintln!("TouchGrass: Found icon at: {}", p.display());
         }
-        exists
-    })
-    .and_then(|p| p.canonicalize().ok())
-    .map(|p| p.to_string_lossy().to_string())
-    .unwrap_or_else(|| {
+        exists
+    })
+    .and_then(|p| p.canonicalize().ok())
+    .map(|p| p.to_string_lossy().to_string())
+}
+
+/// Keeps the tray in an attention state while reminders can't be shown and
+/// warns once it's clear notifications don't work here at all. The UI still
+/// gets `REMINDER_EVENT` either way, so an open window can show a banner.
+fn record_notification_delivery(app: &AppHandle<Wry>, delivered: bool, message: &str) {
+    let failures = app.try_state::<Arc<AppState>>().map(|state| {
+        if delivered {
+            state.notification_failures.store(0, Ordering::Relaxed);
+            0
+        } else {
+            state.notification_failures.fetch_add(1, Ordering::Relaxed) + 1
+        }
+    });
+
+    if delivered {
+        tray::clear_attention(app);
+        return;
+    }
+
+    tray::show_attention(app, message);
+    if failures == Some(NOTIFICATION_FAILURE_WARN_AFTER) {
+        let _ = app.emit(
+            events::LOG_EVENT,
+            events::LogPayload {
+                level: "warning".into(),
+                message: format!(
+                    "the last {NOTIFICATION_FAILURE_WARN_AFTER} reminders could not be shown as notifications; \
+                     your desktop may not support them. Reminders will keep appearing in the tray and in this window."
+                ),
+            },
+        );
+    }
+}
+
+async fn send_reminder_message(
+    app: &AppHandle<Wry>,
+    prefs: &Preferences,
+    kind: ReminderKind,
+    message: String,
+) {
+    send_reminder_with_posture(app, prefs, kind, message, None, false, false).await;
+}
+
+/// The reminder after `long_break_after_snoozes` snoozes, offering a proper
+/// break once there's room for one.
+async fn send_long_break_reminder(app: &AppHandle<Wry>, prefs: &Preferences, snoozes: u8) {
+    let message = format!(
+        "That's {snoozes} snoozes in a row. Once you can, take a proper {LONG_BREAK_MINUTES}-minute break."
+    );
+    send_reminder_with_posture(
+        app,
+        prefs,
+        ReminderKind::Posture,
+        message,
+        None,
+        true,
+        false,
+    )
+    .await;
+}
+
+/// A `desk_alternation` message asking for `posture`.
+fn desk_message(posture: DeskPosture) -> String {
+    posture
+        .messages()
+        .choose(&mut rng())
+        .copied()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Shows a reminder and does the bookkeeping every reminder gets: the
+/// notification history, `REMINDER_EVENT` (which plays the sound), and the
+/// tray. With `overlay` the break overlay covers the screen in place of the
+/// notification, falling back to one if it can't open. Returns whether the
+/// overlay was shown.
+async fn send_reminder_with_posture(
+    app: &AppHandle<Wry>,
+    prefs: &Preferences,
+    kind: ReminderKind,
+    message: String,
+    posture: Option<DeskPosture>,
+    suggest_long_break: bool,
+    overlay: bool,
+) -> bool {
+    let message = match app.try_state::<Arc<AppState>>() {
+        Some(state) => render_message(&message, &state.message_context()),
+        None => message,
+    };
+    let used_overlay = overlay
+        && match overlay::show_break(app, prefs, &message) {
+            Ok(()) => true,
+            Err(err) => {
+                let _ = app.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "warning".into(),
+                        message: format!(
+                            "break overlay failed, sending a notification instead: {err}"
+                        ),
+                    },
+                );
+                false
+            }
+        };
+    let attention = prefs.attention_mode;
+    if prefs.haptic_enabled || (attention && haptics::supported()) {
+        haptics::pulse(app);
+    }
+    if attention && !used_overlay {
+        if let Err(err) = overlay::flash(app, prefs) {
+            let _ = app.emit(
+                events::LOG_EVENT,
+                events::LogPayload {
+                    level: "warning".into(),
+                    message: format!("attention overlay failed: {err}"),
+                },
+            );
+        }
+    }
+
+    let found_icon = find_notification_icon(app);
+    let icon_path = found_icon.clone().unwrap_or_else(|| {
         eprintln!("TouchGrass: No icon found, using fallback 'touchgrass'");
         "touchgrass".to_string()
     });
-
-    eprintln!("TouchGrass: Using notification icon path: {}", icon_path);
-
-    #[cfg(target_os = "linux")]
     let app_state = app
         .try_state::<Arc<AppState>>()
         .map(|state| state.inner().clone());
+    let mut attempt = NotificationAttempt {
+        id: app_state
+            .as_ref()
+            .map_or(0, |state| state.next_attempt_id()),
+        at: Utc::now(),
+        kind,
+        message: message.clone(),
+        icon_path: found_icon,
+        handled_by: None,
+        delivery: DeliveryStatus::Failed,
+        errors: Vec::new(),
+    };
+
+    eprintln!("TouchGrass: Using notification icon path: {}", icon_path);
 
     #[cfg(target_os = "linux")]
-    let handled_by_native_actions =
-        match show_linux_notification_with_actions(app, &message, &icon_path, app_state.clone()) {
-            Ok(()) => true,
+    let handled_by_native_actions = !used_overlay
+        && match show_linux_notification_with_actions(
+            app,
+            &message,
+            &icon_path,
+            prefs,
+            kind,
+            app_state.clone(),
+            attempt.id,
+        ) {
+            Ok(_) => true,
             Err(err) => {
                 eprintln!("TouchGrass: linux notification with actions failed: {err}");
+                attempt.errors.push(format!("linux actions: {err}"));
                 let _ = app.emit(
                     events::LOG_EVENT,
                     events::LogPayload {
@@ -669,8 +6385,22 @@ async fn send_reminder(app: &AppHandle<Wry>, prefs: &Preferences) {
     #[cfg(not(target_os = "linux"))]
     let handled_by_native_actions = false;
 
-    if !handled_by_native_actions {
+    let mut delivered = handled_by_native_actions || used_overlay;
+    let mut confirmed = used_overlay;
+    if used_overlay {
+        attempt.handled_by = Some(NotificationPath::Overlay);
+    } else if handled_by_native_actions {
+        attempt.handled_by = Some(NotificationPath::LinuxActions);
+    } else {
         // Build notification with app icon (fallback without action buttons)
+        #[cfg(target_os = "macos")]
+        let notification_result = show_macos_notification(
+            app,
+            message.clone(),
+            prefs.group_notifications.then(|| kind.thread_id()),
+        )
+        .await;
+        #[cfg(not(target_os = "macos"))]
         let notification_result = app
             .notification()
             .builder()
@@ -679,24 +6409,196 @@ async fn send_reminder(app: &AppHandle<Wry>, prefs: &Preferences) {
             .icon(icon_path.clone())
             .show();
 
-        if let Err(err) = notification_result {
-            let _ = app.emit(
-                events::LOG_EVENT,
-                events::LogPayload {
-                    level: "error".into(),
-                    message: format!("notification error: {err}"),
-                },
-            );
+        match notification_result {
+            Ok(()) => {
+                delivered = true;
+                // macOS only returns once its delivery callback has fired.
+                confirmed = cfg!(target_os = "macos");
+                attempt.handled_by = Some(NotificationPath::OsToast);
+            }
+            Err(err) => {
+                attempt.errors.push(format!("os toast: {err}"));
+                let _ = app.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "error".into(),
+                        message: format!("notification error: {err}"),
+                    },
+                );
+            }
         }
     }
 
+    if confirmed {
+        // The overlay sits on top of everything, Do Not Disturb included.
+        attempt.delivery = DeliveryStatus::Presented;
+    } else if delivered {
+        // Showing it can succeed and still land nowhere on screen. Until the
+        // desktop reports back, Do Not Disturb is the best hint there is.
+        let muted = async_runtime::spawn_blocking(do_not_disturb::is_active)
+            .await
+            .ok()
+            .flatten();
+        attempt.delivery = match muted {
+            Some(true) => DeliveryStatus::Suppressed,
+            _ => DeliveryStatus::Unknown,
+        };
+    }
+
+    record_notification_delivery(app, delivered, &message);
+    if attention && delivered {
+        tray::flash(app, &message);
+    }
+    if let Some(state) = app.try_state::<Arc<AppState>>() {
+        state.push_notification_attempt(attempt);
+    }
+
     let _ = app.emit(
         events::REMINDER_EVENT,
         ReminderPayload {
             message,
-            sound_enabled: prefs.sound_enabled,
+            kind,
+            sound_enabled: prefs.sound_enabled || attention,
+            sound_path: prefs.sound_for(kind).map(str::to_string),
+            posture,
+            suggest_long_break,
         },
     );
+    used_overlay
+}
+
+fn send_evening_summary(app: &AppHandle<Wry>, today: &DayStats) {
+    let body = format!(
+        "Today's wrap: {} breaks taken, {} skipped, longest streak {}. Go touch grass.",
+        today.breaks_taken, today.skipped, today.longest_streak
+    );
+
+    if let Err(err) = app
+        .notification()
+        .builder()
+        .title("TouchGrass")
+        .body(body)
+        .show()
+    {
+        let _ = app.emit(
+            events::LOG_EVENT,
+            events::LogPayload {
+                level: "error".into(),
+                message: format!("evening summary notification error: {err}"),
+            },
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn close_linux_notification(id: u32) -> zbus::Result<()> {
+    let connection = zbus::blocking::Connection::session()?;
+    connection.call_method(
+        Some("org.freedesktop.Notifications"),
+        "/org/freedesktop/Notifications",
+        Some("org.freedesktop.Notifications"),
+        "CloseNotification",
+        &(id,),
+    )?;
+    Ok(())
+}
+
+/// Sends a plain toast through the notification center and waits for its
+/// delivery callback, which the plugin's fire-and-forget `show` never
+/// surfaces. With a `thread` it goes through the newer notification center,
+/// the only one that groups; an unbundled dev build can't use that one,
+/// and anything it refuses is sent ungrouped instead.
+#[cfg(target_os = "macos")]
+async fn show_macos_notification(
+    app: &AppHandle<Wry>,
+    message: String,
+    thread: Option<&'static str>,
+) -> Result<(), String> {
+    if let Some(thread) = thread.filter(|_| !tauri::is_dev()) {
+        let threaded = message.clone();
+        let posted =
+            async_runtime::spawn_blocking(move || post_threaded_notification(&threaded, thread))
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|result| result);
+        match posted {
+            Ok(()) => return Ok(()),
+            Err(err) => eprintln!("TouchGrass: grouped notification failed ({err})."),
+        }
+    }
+
+    // Same bundle the plugin would use; it's set once per process, so
+    // "already set" is fine.
+    let bundle = if tauri::is_dev() {
+        "com.apple.Terminal".to_string()
+    } else {
+        app.config().identifier.clone()
+    };
+    let _ = mac_notification_sys::set_application(&bundle);
+    async_runtime::spawn_blocking(move || {
+        mac_notification_sys::Notification::new()
+            .title("TouchGrass")
+            .message(&message)
+            .send()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Posts `message` on `thread` through `UNUserNotificationCenter`, asking
+/// for permission the first time, and waits for it to be accepted.
+#[cfg(target_os = "macos")]
+fn post_threaded_notification(message: &str, thread: &str) -> Result<(), String> {
+    use std::sync::{mpsc, Once};
+
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_user_notifications::{
+        UNAuthorizationOptions, UNMutableNotificationContent, UNNotificationRequest,
+        UNUserNotificationCenter,
+    };
+
+    const CALLBACK_TIMEOUT_SECS: u64 = 5;
+    static AUTHORIZATION: Once = Once::new();
+
+    let center = UNUserNotificationCenter::currentNotificationCenter();
+    AUTHORIZATION.call_once(|| {
+        let (tx, rx) = mpsc::channel();
+        let handler = RcBlock::new(move |_granted: Bool, _error: *mut NSError| {
+            let _ = tx.send(());
+        });
+        center.requestAuthorizationWithOptions_completionHandler(
+            UNAuthorizationOptions::Alert | UNAuthorizationOptions::Sound,
+            &handler,
+        );
+        let _ = rx.recv_timeout(Duration::from_secs(CALLBACK_TIMEOUT_SECS));
+    });
+
+    let content = UNMutableNotificationContent::new();
+    content.setTitle(&NSString::from_str("TouchGrass"));
+    content.setBody(&NSString::from_str(message));
+    content.setThreadIdentifier(&NSString::from_str(thread));
+    // Unique, so a new reminder joins the thread instead of replacing the last.
+    let identifier = format!("{thread}.{}", Utc::now().timestamp_millis());
+    let request = UNNotificationRequest::requestWithIdentifier_content_trigger(
+        &NSString::from_str(&identifier),
+        &content,
+        None,
+    );
+
+    let (tx, rx) = mpsc::channel();
+    let handler = RcBlock::new(move |error: *mut NSError| {
+        let _ = tx.send(error.is_null());
+    });
+    center.addNotificationRequest_withCompletionHandler(&request, Some(&handler));
+    match rx.recv_timeout(Duration::from_secs(CALLBACK_TIMEOUT_SECS)) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("the notification center refused it".to_string()),
+        Err(_) => Err("the notification center didn't answer".to_string()),
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -704,10 +6606,23 @@ fn show_linux_notification_with_actions(
     app: &AppHandle<Wry>,
     message: &str,
     icon_path: &str,
+    prefs: &Preferences,
+    kind: ReminderKind,
     state: Option<Arc<AppState>>,
-) -> Result<(), notify_rust::error::Error> {
+    attempt_id: u64,
+) -> Result<Vec<String>, notify_rust::error::Error> {
     const ACTION_REMIND_IN_FIVE: &str = "touchgrass.remind_in_5";
     const ACTION_SKIP_BREAK: &str = "touchgrass.skip_break";
+    const ACTION_OPEN: &str = "touchgrass.open";
+
+    const PLAIN_REMIND: (&str, &str) = (
+        "Snooze 5 min",
+        "Notification action: Snooze 5 min - next reminder in five minutes.",
+    );
+    const PLAIN_SKIP: (&str, &str) = (
+        "Skip",
+        "Notification action: Skip - timer reset to a full interval.",
+    );
 
     const REMIND_VARIANTS: &[(&str, &str)] = &[
         (
@@ -903,23 +6818,54 @@ fn show_linux_notification_with_actions(
         ),
     ];
 
-    let mut rng = rng();
-    let (remind_label, remind_log) = REMIND_VARIANTS.choose(&mut rng).copied().unwrap_or((
-        "Give me five",
-        "Notification action: Give me five - stretch IOU noted.",
-    ));
-    let (skip_label, skip_log) = SKIP_VARIANTS.choose(&mut rng).copied().unwrap_or((
-        "Skip this lap",
-        "Notification action: Skip this lap. Hustle responsibly.",
-    ));
-
-    let handle = LinuxNotification::new()
+    let ((remind_label, remind_log), (skip_label, skip_log)) = if prefs.playful_labels {
+        let mut rng = rng();
+        (
+            REMIND_VARIANTS
+                .choose(&mut rng)
+                .copied()
+                .unwrap_or(PLAIN_REMIND),
+            SKIP_VARIANTS
+                .choose(&mut rng)
+                .copied()
+                .unwrap_or(PLAIN_SKIP),
+        )
+    } else {
+        (PLAIN_REMIND, PLAIN_SKIP)
+    };
+
+    let mut notification = LinuxNotification::new();
+    notification
         .summary("TouchGrass")
         .body(message)
         .icon(icon_path)
         .action(ACTION_REMIND_IN_FIVE, remind_label)
-        .action(ACTION_SKIP_BREAK, skip_label)
-        .show()?;
+        .action(ACTION_SKIP_BREAK, skip_label);
+    // Some compositors only show the first two actions.
+    if prefs.extra_notification_actions {
+        notification.action(ACTION_OPEN, "Open TouchGrass");
+    }
+
+    if prefs.replace_notifications {
+        if let Some(id) = state
+            .as_ref()
+            .and_then(|state| state.last_notification_id(kind))
+        {
+            notification.id(id);
+        }
+    }
+
+    let handle = notification.show()?;
+    let notification_id = handle.id();
+
+    if let Some(state) = state.as_ref() {
+        state.set_last_notification_id(kind, notification_id);
+    }
+
+    let mut labels = vec![remind_label.to_string(), skip_label.to_string()];
+    if prefs.extra_notification_actions {
+        labels.push("Open TouchGrass".to_string());
+    }
 
     let app_for_actions = app.clone();
     let state_for_actions = state.clone();
@@ -927,9 +6873,38 @@ fn show_linux_notification_with_actions(
     let skip_log = skip_log;
 
     async_runtime::spawn_blocking(move || {
-        handle.wait_for_action(move |identifier| {
+        // `handle_action` rather than `wait_for_action`: the latter throws
+        // the close reason away, and that's how delivery gets confirmed.
+        // Keeps the bus connection open; some desktops drop the actions
+        // otherwise.
+        let _handle = handle;
+        notify_rust::handle_action(notification_id, move |response| {
+            let identifier = match response {
+                ActionResponse::Custom(identifier) => *identifier,
+                // Closed by us, to replace it; the next attempt takes over.
+                ActionResponse::Closed(CloseReason::CloseAction) => return,
+                ActionResponse::Closed(_) => "",
+            };
+            if let Some(state) = state_for_actions.as_ref() {
+                state.set_delivery(attempt_id, DeliveryStatus::Presented);
+            }
             let app_handle = app_for_actions.clone();
             let state_arc = state_for_actions.clone();
+            // Without a state handle this is a preview or a test: say which
+            // button was pressed and leave the timer alone.
+            let log = |label: &str, text: &str| {
+                let message = match state_arc {
+                    Some(_) => text.to_string(),
+                    None => format!("Preview: \"{label}\" pressed; nothing was changed."),
+                };
+                let _ = app_handle.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "info".into(),
+                        message,
+                    },
+                );
+            };
 
             match identifier {
                 ACTION_REMIND_IN_FIVE => {
@@ -938,61 +6913,634 @@ fn show_linux_notification_with_actions(
                             state.snooze(5).await;
                         });
                     }
-                    let _ = app_handle.emit(
-                        events::LOG_EVENT,
-                        events::LogPayload {
-                            level: "info".into(),
-                            message: remind_log.into(),
-                        },
-                    );
+                    log(remind_label, remind_log);
                 }
                 ACTION_SKIP_BREAK => {
-                    if let Some(state) = state_arc {
+                    if let Some(state) = state_arc.clone() {
                         async_runtime::spawn(async move {
-                            state.skip_current_break().await;
+                            state.skip_break().await;
                         });
                     }
-                    let _ = app_handle.emit(
-                        events::LOG_EVENT,
-                        events::LogPayload {
-                            level: "info".into(),
-                            message: skip_log.into(),
-                        },
-                    );
+                    log(skip_label, skip_log);
+                }
+                ACTION_OPEN => {
+                    if state_arc.is_none() {
+                        log("Open TouchGrass", "");
+                    }
+                    // Usually opened to change how often reminders come.
+                    let _ = tray::open_settings(&app_handle, Some("cadence"));
                 }
                 _ => {}
             }
         });
     });
 
-    Ok(())
+    Ok(labels)
 }
 
-fn choose_reminder_message() -> String {
-    const MESSAGES: &[&str] = &[
-        "Stand up before you photosynthesize.",
-        "Touch grass (nearby plant also counts).",
-        "Keyboard's hot, legs are not.",
-        "Blink like you mean it: 10x.",
-        "Break speedrun in 30s. Go.",
-        "Free DLC: posture.",
-        "Up. Now. Your chair has attachment issues.",
-        "Stand before you grow roots.",
-        "Blink or become a raisin.",
-        "Walk away like the main character.",
-        "Your spine filed a ticket.",
-        "Walk. The chair will cope.",
-        "Your posture called HR.",
-        "Side quest: 30s breathing.",
-        "Keyboard is not a life partner.",
-        "AFK or AF-ache.",
-        "Stare at something >20ft, not your soul.",
-        "Load-bearing human requires maintenance.",
-    ];
+/// What `render_message` fills placeholders from.
+pub struct MessageContext {
+    pub now: DateTime<Local>,
+    pub breaks_today: u32,
+    pub streak: u32,
+    pub next: Option<DateTime<Local>>,
+}
 
-    let mut rng = rng();
-    MESSAGES
-        .choose(&mut rng)
-        .unwrap_or(&"Time for a quick reset.")
-        .to_string()
+/// Replaces `{time}`, `{breaks}`, `{streak}`, and `{next}` in `template`.
+/// Anything else in braces is left as written.
+pub fn render_message(template: &str, ctx: &MessageContext) -> String {
+    if !template.contains('{') {
+        return template.to_string();
+    }
+    let clock = |at: DateTime<Local>| at.format("%-I:%M %p").to_string();
+    let next = ctx.next.map(clock).unwrap_or_else(|| "later".to_string());
+    template
+        .replace("{time}", &clock(ctx.now))
+        .replace("{breaks}", &ctx.breaks_today.to_string())
+        .replace("{streak}", &ctx.streak.to_string())
+        .replace("{next}", &next)
+}
+
+/// Picks reminder messages according to `message_rotation`, remembering
+/// the last pick per kind.
+#[derive(Debug, Default)]
+struct MessagePicker {
+    last: HashMap<ReminderKind, usize>,
+}
+
+impl MessagePicker {
+    fn pick(&mut self, prefs: &Preferences, kind: ReminderKind) -> String {
+        let weekend = prefs.is_weekend(stats::today());
+        let kinds = kind.message_kinds();
+        let builtin = kinds.iter().flat_map(|source| {
+            if weekend {
+                source.weekend_messages()
+            } else {
+                source.builtin_messages(prefs.mood)
+            }
+        });
+        let custom = kinds
+            .iter()
+            .filter_map(|source| prefs.custom_messages.get(source))
+            .flatten();
+        let candidates: Vec<&str> = builtin
+            .copied()
+            .chain(custom.map(String::as_str))
+            .filter(|message| !message.trim().is_empty())
+            .collect();
+        self.pick_from(kind, prefs.message_rotation, &candidates)
+    }
+
+    fn pick_from(
+        &mut self,
+        kind: ReminderKind,
+        rotation: RotationMode,
+        candidates: &[&str],
+    ) -> String {
+        // Never pick from an empty pool; whatever filtering removed everything,
+        // there's still a reminder to show.
+        if candidates.is_empty() {
+            self.last.remove(&kind);
+            return FALLBACK_MESSAGE.to_string();
+        }
+
+        let len = candidates.len();
+        let last = self.last.get(&kind).copied();
+        let mut rng = rng();
+        let index = match rotation {
+            RotationMode::Random => rng.random_range(0..len),
+            RotationMode::NoRepeat => {
+                let index = rng.random_range(0..len);
+                if len > 1 && last == Some(index) {
+                    // Any other message, still uniformly.
+                    (index + rng.random_range(1..len)) % len
+                } else {
+                    index
+                }
+            }
+            RotationMode::Sequential => last.map_or(0, |last| (last + 1) % len),
+        };
+        self.last.insert(kind, index);
+        candidates[index].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn snooze_holds_until_it_ends() {
+        let mut suppression = Suppression::Active;
+        assert!(suppression.snooze(at(600)));
+        assert!(suppression.holds(at(0)));
+        assert!(!suppression.holds(at(600)));
+        assert_eq!(suppression.snoozed_until(), Some(at(600)));
+    }
+
+    #[test]
+    fn snooze_cant_end_a_pause() {
+        let mut suppression = Suppression::PausedIndefinite;
+        assert!(!suppression.snooze(at(600)));
+        assert_eq!(suppression, Suppression::PausedIndefinite);
+    }
+
+    #[test]
+    fn pause_holds_and_resuming_drops_it() {
+        let paused = Suppression::from_pause(true);
+        assert!(paused.is_paused());
+        assert!(paused.holds(at(0)));
+        assert_eq!(paused.snoozed_until(), None);
+
+        let resumed = Suppression::from_pause(false);
+        assert_eq!(resumed, Suppression::Active);
+        assert!(!resumed.holds(at(0)));
+    }
+
+    #[test]
+    fn timed_pause_expires_only_once_over() {
+        let mut suppression = Suppression::PausedUntil(at(600));
+        assert!(!suppression.expire_pause(at(599)));
+        assert!(suppression.holds(at(599)));
+        assert!(suppression.expire_pause(at(600)));
+        assert_eq!(suppression, Suppression::Active);
+        assert!(!suppression.expire_pause(at(700)));
+    }
+
+    #[test]
+    fn clear_snooze_leaves_a_pause() {
+        let mut snoozed = Suppression::SnoozedUntil(at(600));
+        snoozed.clear_snooze();
+        assert_eq!(snoozed, Suppression::Active);
+
+        let mut paused = Suppression::PausedUntil(at(600));
+        paused.clear_snooze();
+        assert_eq!(paused, Suppression::PausedUntil(at(600)));
+    }
+
+    fn local_noon(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn evaluate_suppression_checks_the_day_at_the_given_time() {
+        let prefs = Preferences {
+            reminder_days: vec![Weekday::Mon],
+            ..Preferences::default()
+        };
+        let status = StatusSnapshot::default();
+        let overrides = SuppressionOverrides::new();
+
+        // 2026-10-18 is a Sunday, the 19th a Monday.
+        let sunday = evaluate_suppression(&prefs, &status, &overrides, local_noon(2026, 10, 18));
+        assert!(sunday.day_off && sunday.suppressed);
+        let monday = evaluate_suppression(&prefs, &status, &overrides, local_noon(2026, 10, 19));
+        assert!(!monday.day_off && !monday.suppressed);
+    }
+
+    #[test]
+    fn evaluate_suppression_lets_snoozes_pauses_and_overrides_run_out() {
+        let prefs = Preferences::default();
+        let status = StatusSnapshot {
+            paused: true,
+            paused_until: Some(at(600)),
+            screen_locked: true,
+            ..StatusSnapshot::default()
+        };
+        let overrides = SuppressionOverrides::from([(SuppressionKind::ScreenLocked, at(300))]);
+
+        let early = evaluate_suppression(&prefs, &status, &overrides, at(0));
+        assert!(early.paused && !early.screen_locked);
+        let later = evaluate_suppression(&prefs, &status, &overrides, at(900));
+        assert!(!later.paused && later.screen_locked);
+
+        let snoozed = StatusSnapshot {
+            snoozed_until: Some(at(600)),
+            ..StatusSnapshot::default()
+        };
+        assert!(evaluate_suppression(&prefs, &snoozed, &overrides, at(0)).snoozed);
+        assert!(!evaluate_suppression(&prefs, &snoozed, &overrides, at(600)).snoozed);
+    }
+
+    fn local_time(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+    }
+
+    fn modifiers(resolution: &IntervalResolution) -> Vec<IntervalModifier> {
+        resolution.steps.iter().map(|step| step.modifier).collect()
+    }
+
+    #[test]
+    fn resolve_interval_leaves_a_plain_interval_alone() {
+        let prefs = Preferences::default();
+        let resolution =
+            resolve_interval(&prefs, None, 1.0, local_time(2026, 10, 19, 9, 10), false);
+        assert_eq!(resolution.interval, prefs.interval_duration());
+        assert_eq!(resolution.wait, resolution.interval);
+        assert!(resolution.steps.is_empty());
+    }
+
+    #[test]
+    fn resolve_interval_applies_weekend_then_adaptive_then_sprint() {
+        let prefs = Preferences {
+            weekend_mode: true,
+            weekend_interval_minutes: Some(60),
+            ..Preferences::default()
+        };
+        let sprint = Sprint {
+            interval: Duration::from_secs(10 * 60),
+            until: at(3600),
+        };
+        let saturday = local_time(2026, 10, 17, 9, 10);
+
+        let relaxed = resolve_interval(&prefs, None, 1.5, saturday, false);
+        assert_eq!(
+            modifiers(&relaxed),
+            [IntervalModifier::Weekend, IntervalModifier::Adaptive]
+        );
+        assert_eq!(relaxed.interval, Duration::from_secs(90 * 60));
+
+        let sprinting = resolve_interval(&prefs, Some(sprint), 1.5, saturday, false);
+        assert_eq!(
+            sprinting.steps.last().unwrap().modifier,
+            IntervalModifier::Sprint
+        );
+        assert_eq!(sprinting.wait, sprint.interval);
+    }
+
+    #[test]
+    fn resolve_interval_snaps_the_wait_but_not_the_interval() {
+        let prefs = Preferences {
+            interval_minutes: 20,
+            snap_to_clock: Some(15),
+            ..Preferences::default()
+        };
+        let resolution =
+            resolve_interval(&prefs, None, 1.0, local_time(2026, 10, 19, 9, 10), false);
+        assert_eq!(modifiers(&resolution), [IntervalModifier::SnapToClock]);
+        assert_eq!(resolution.interval, Duration::from_secs(20 * 60));
+        // 9:30 rounds up to 9:45.
+        assert_eq!(resolution.wait, Duration::from_secs(35 * 60));
+    }
+
+    #[test]
+    fn resolve_interval_adds_the_cooldown_before_snapping() {
+        let prefs = Preferences {
+            interval_minutes: 20,
+            snap_to_clock: Some(15),
+            post_break_cooldown_minutes: Some(5),
+            ..Preferences::default()
+        };
+        let now = local_time(2026, 10, 19, 9, 10);
+        let resolution = resolve_interval(&prefs, None, 1.0, now, true);
+        assert_eq!(
+            modifiers(&resolution),
+            [
+                IntervalModifier::PostBreakCooldown,
+                IntervalModifier::SnapToClock
+            ]
+        );
+        // 9:35 rounds up to 9:45.
+        assert_eq!(resolution.wait, Duration::from_secs(35 * 60));
+
+        let resolution = resolve_interval(&prefs, None, 1.0, now, false);
+        assert_eq!(modifiers(&resolution), [IntervalModifier::SnapToClock]);
+    }
+
+    #[test]
+    fn resolve_interval_moves_past_days_off() {
+        let prefs = Preferences {
+            interval_minutes: 20,
+            reminder_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            ..Preferences::default()
+        };
+        let saturday = local_time(2026, 10, 17, 10, 0);
+        let resolution = resolve_interval(&prefs, None, 1.0, saturday, false);
+        assert_eq!(modifiers(&resolution), [IntervalModifier::DaysOff]);
+        let due = saturday + chrono::Duration::from_std(resolution.wait).unwrap();
+        assert_eq!(due, local_time(2026, 10, 19, 0, 20));
+    }
+
+    #[test]
+    fn message_picker_falls_back_on_an_empty_pool() {
+        let mut picker = MessagePicker::default();
+        let pool = ["one", "two"];
+        assert_eq!(
+            picker.pick_from(ReminderKind::Water, RotationMode::Sequential, &pool),
+            "one"
+        );
+        for rotation in [
+            RotationMode::Random,
+            RotationMode::NoRepeat,
+            RotationMode::Sequential,
+        ] {
+            assert_eq!(
+                picker.pick_from(ReminderKind::Water, rotation, &[]),
+                FALLBACK_MESSAGE
+            );
+        }
+        // The empty pick forgot where the rotation was.
+        assert_eq!(
+            picker.pick_from(ReminderKind::Water, RotationMode::Sequential, &pool),
+            "one"
+        );
+    }
+
+    #[test]
+    fn message_picker_skips_blank_custom_messages() {
+        let prefs = Preferences {
+            custom_messages: HashMap::from([(ReminderKind::Posture, vec!["   ".to_string()])]),
+            ..Preferences::default()
+        };
+        let message = MessagePicker::default().pick(&prefs, ReminderKind::Posture);
+        assert!(!message.trim().is_empty());
+    }
+
+    #[test]
+    fn preferences_change_keeps_a_running_snooze() {
+        let interval = Duration::from_secs(20 * 60);
+        // Longer than the new interval and shorter both still win.
+        for until in [at(60 * 60), at(5 * 60)] {
+            assert_eq!(
+                wait_after_preferences_change(Some(until), at(0), interval),
+                ((until - at(0)).to_std().unwrap(), Some(until))
+            );
+        }
+    }
+
+    #[test]
+    fn preferences_change_restarts_the_interval_once_the_snooze_is_over() {
+        let interval = Duration::from_secs(20 * 60);
+        assert_eq!(
+            wait_after_preferences_change(Some(at(0)), at(0), interval),
+            (interval, None)
+        );
+        assert_eq!(
+            wait_after_preferences_change(Some(at(0)), at(30), interval),
+            (interval, None)
+        );
+        assert_eq!(
+            wait_after_preferences_change(None, at(0), interval),
+            (interval, None)
+        );
+    }
+
+    #[test]
+    fn stretched_snooze_does_not_fire_the_moment_the_user_is_back() {
+        let back = Instant::now();
+        let due = after_return_instant();
+        assert!(due >= back + Duration::from_secs(SNOOZE_RETURN_GRACE_SECS));
+    }
+
+    #[test]
+    fn active_part_leaves_out_idle_time() {
+        let poll = Duration::from_secs(20);
+        assert_eq!(active_part(poll, 0, false), poll);
+        assert_eq!(active_part(poll, 5, false), Duration::from_secs(15));
+        assert_eq!(active_part(poll, 60, false), Duration::ZERO);
+        assert_eq!(active_part(poll, 0, true), Duration::ZERO);
+    }
+
+    #[test]
+    fn remaining_active_time_counts_down_only_while_active() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(60);
+        let poll = Duration::from_secs(20);
+
+        // A fresh schedule owes the whole wait.
+        let remaining = remaining_active_time(None, start + interval, start, Duration::ZERO);
+        assert_eq!(remaining, interval);
+
+        // Fully active: the deadline stays where it was.
+        let now = start + poll;
+        let remaining = remaining_active_time(
+            Some((interval, start + interval)),
+            start + interval,
+            now,
+            poll,
+        );
+        assert_eq!(now + remaining, start + interval);
+
+        // Idle for a poll: nothing counted, so the deadline moves out.
+        let later = now + poll;
+        let deadline = now + remaining;
+        let idle =
+            remaining_active_time(Some((remaining, deadline)), deadline, later, Duration::ZERO);
+        assert_eq!(idle, remaining);
+        assert!(later + idle > deadline);
+
+        // Enough activity fires it.
+        assert_eq!(
+            remaining_active_time(Some((poll, deadline)), deadline, later, poll * 2),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn remaining_active_time_restarts_after_a_reschedule() {
+        let now = Instant::now();
+        let old = now + Duration::from_secs(30);
+        let snoozed = now + Duration::from_secs(300);
+        assert_eq!(
+            remaining_active_time(
+                Some((Duration::from_secs(30), old)),
+                snoozed,
+                now,
+                Duration::from_secs(20)
+            ),
+            Duration::from_secs(300)
+        );
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("touchgrass-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn config_round_trip_passes_and_cleans_up() {
+        let dir = scratch_dir("round-trip");
+        let prefs = Preferences {
+            interval_minutes: 35,
+            ..Preferences::default()
+        };
+        let check = check_config_round_trip(Some(&dir.join(PREFERENCES_FILE)), &prefs);
+        assert!(
+            matches!(check.outcome, CheckOutcome::Ok),
+            "{}",
+            check.detail
+        );
+        assert!(!dir.join(SELF_TEST_FILE).exists());
+        assert!(!dir.join(PREFERENCES_FILE).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_round_trip_skips_without_a_config_dir() {
+        let check = check_config_round_trip(None, &Preferences::default());
+        assert!(matches!(check.outcome, CheckOutcome::Skipped));
+    }
+
+    #[test]
+    fn config_round_trip_fails_on_a_missing_dir() {
+        let dir = scratch_dir("missing");
+        let path = dir.join("gone").join(PREFERENCES_FILE);
+        let check = check_config_round_trip(Some(&path), &Preferences::default());
+        assert!(matches!(check.outcome, CheckOutcome::Failed));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unscheduled_kinds_ride_along_with_posture() {
+        let kinds = ReminderKind::Posture.message_kinds();
+        assert!(kinds.contains(&ReminderKind::EyeStrain));
+        assert!(kinds.contains(&ReminderKind::Water));
+        assert_eq!(
+            ReminderKind::MicroBreak.message_kinds(),
+            [ReminderKind::MicroBreak]
+        );
+    }
+
+    fn lead() -> Duration {
+        Duration::from_secs(MIN_RESCHEDULE_LEAD_SECS)
+    }
+
+    #[test]
+    fn safe_next_never_fires_at_once() {
+        let now = Instant::now();
+        let past = now.checked_sub(Duration::from_secs(60)).unwrap_or(now);
+        for next in [past, now, now + Duration::from_secs(1)] {
+            assert!(safe_next(next) >= now + lead());
+        }
+        let later = now + Duration::from_secs(600);
+        assert_eq!(safe_next(later), later);
+    }
+
+    #[test]
+    fn a_zero_wait_reschedules_with_a_lead() {
+        // An adaptive scale of zero leaves nothing to wait.
+        let prefs = Preferences::default();
+        let now = Instant::now();
+        assert!(next_interval_instant(&prefs, None, 0.0) >= now + lead());
+        assert!(after_break_instant(&prefs, None, 0.0) >= now + lead());
+    }
+
+    #[test]
+    fn a_pause_until_in_the_past_resumes_without_firing() {
+        let until = Utc::now() - chrono::Duration::seconds(30);
+        let now = Instant::now();
+        assert!(safe_next(instant_from_timestamp(until)) >= now + lead());
+        // Its wakeup ends the pause, and that wakeup shows nothing.
+        let mut suppression = Suppression::PausedUntil(until);
+        assert!(suppression.expire_pause(Utc::now()));
+        assert!(!suppression.is_paused());
+    }
+
+    #[test]
+    fn smart_snooze_grows_through_the_day() {
+        assert_eq!(smart_snooze_minutes(0, &[]), 5);
+        assert_eq!(smart_snooze_minutes(11, &[]), 5);
+        assert_eq!(smart_snooze_minutes(12, &[]), 10);
+        assert_eq!(smart_snooze_minutes(16, &[]), 10);
+        assert_eq!(smart_snooze_minutes(17, &[]), 15);
+        assert_eq!(smart_snooze_minutes(23, &[]), 15);
+    }
+
+    #[test]
+    fn smart_snooze_leans_toward_recent_snoozes() {
+        // Halfway between the band and the average, to the nearest five.
+        assert_eq!(smart_snooze_minutes(9, &[20, 30]), 15);
+        assert_eq!(smart_snooze_minutes(12, &[14]), 10);
+        assert_eq!(smart_snooze_minutes(12, &[18]), 15);
+    }
+
+    #[test]
+    fn smart_snooze_stays_in_range() {
+        assert_eq!(smart_snooze_minutes(9, &[1, 1]), MIN_SMART_SNOOZE_MINUTES);
+        assert_eq!(
+            smart_snooze_minutes(20, &[120, 120]),
+            MAX_SMART_SNOOZE_MINUTES
+        );
+    }
+
+    fn local_at(year: i32, month: u32, day: u32, time: NaiveTime) -> DateTime<Utc> {
+        local_time(year, month, day, time.hour(), time.minute()).with_timezone(&Utc)
+    }
+
+    #[test]
+    fn snooze_boundaries_take_the_next_anchor() {
+        let prefs = Preferences::default();
+        let morning = local_time(2026, 10, 19, 10, 0);
+        let night = local_time(2026, 10, 19, 22, 0);
+        for (boundary, anchor) in [
+            (SnoozeBoundary::Afternoon, prefs.snooze_afternoon_at),
+            (SnoozeBoundary::Evening, prefs.snooze_evening_at),
+        ] {
+            assert_eq!(
+                boundary.resolve(&prefs, morning),
+                local_at(2026, 10, 19, anchor)
+            );
+            assert_eq!(
+                boundary.resolve(&prefs, night),
+                local_at(2026, 10, 20, anchor)
+            );
+        }
+    }
+
+    #[test]
+    fn tomorrow_morning_always_means_tomorrow() {
+        let prefs = Preferences::default();
+        let tomorrow = local_at(2026, 10, 20, prefs.snooze_morning_at);
+        for (hour, minute) in [(0, 30), (8, 59), (10, 0), (23, 0)] {
+            let now = local_time(2026, 10, 19, hour, minute);
+            assert_eq!(
+                SnoozeBoundary::TomorrowMorning.resolve(&prefs, now),
+                tomorrow,
+                "from {hour}:{minute:02}"
+            );
+        }
+    }
+
+    #[test]
+    fn snooze_boundary_names_parse() {
+        assert_eq!(
+            "afternoon".parse::<SnoozeBoundary>().ok(),
+            Some(SnoozeBoundary::Afternoon)
+        );
+        assert_eq!(
+            " tomorrow_morning ".parse::<SnoozeBoundary>().ok(),
+            Some(SnoozeBoundary::TomorrowMorning)
+        );
+        for name in ["noon", "Evening", ""] {
+            assert!(matches!(
+                name.parse::<SnoozeBoundary>(),
+                Err(AppStateError::UnknownSnoozeBoundary(_))
+            ));
+        }
+    }
 }
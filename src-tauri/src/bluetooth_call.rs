@@ -0,0 +1,99 @@
+/// Whether a Bluetooth headset is in its call (hands-free) mode, or `None`
+/// when it can't be told here. Tells calls apart from music by the headset
+/// profile, so listening to something doesn't hold reminders back. Blocks
+/// briefly, so call it off the async runtime.
+pub fn is_on_call() -> Option<bool> {
+    platform::is_on_call()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::process::Command;
+
+    /// Looks for a `bluez` card on a head-unit profile in `pactl list cards`,
+    /// which PulseAudio and PipeWire (through pipewire-pulse) both answer.
+    /// PulseAudio calls it `headset_head_unit` or `handsfree_head_unit`,
+    /// PipeWire `headset-head-unit` with an optional codec suffix.
+    pub fn is_on_call() -> Option<bool> {
+        let output = Command::new("pactl")
+            .args(["list", "cards"])
+            .env("LC_ALL", "C")
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let mut bluetooth_card = false;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let line = line.trim();
+            if line.starts_with("Card #") {
+                bluetooth_card = false;
+            } else if let Some(name) = line.strip_prefix("Name:") {
+                bluetooth_card = name.trim().starts_with("bluez_card.");
+            } else if let Some(profile) = line.strip_prefix("Active Profile:") {
+                if bluetooth_card && profile.replace('_', "-").contains("head-unit") {
+                    return Some(true);
+                }
+            }
+        }
+        Some(false)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::{
+        Devices::FunctionDiscovery::PKEY_Device_EnumeratorName,
+        Media::Audio::{
+            eCommunications, eRender, AudioSessionStateActive, IAudioSessionManager2,
+            IMMDeviceEnumerator, MMDeviceEnumerator,
+        },
+        System::Com::{
+            CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ,
+        },
+    };
+
+    /// Windows exposes a headset's call mode as its own "Hands-Free" endpoint,
+    /// enumerated by `BTHHFENUM`. It's on a call when that endpoint is the
+    /// default for communications and has an active session.
+    pub fn is_on_call() -> Option<bool> {
+        // SAFETY: COM is initialized for this thread before any call, and
+        // every interface is released when its wrapper drops.
+        unsafe {
+            // Already initialized (possibly another way) is fine too.
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
+            let Ok(device) = enumerator.GetDefaultAudioEndpoint(eRender, eCommunications) else {
+                // No communications device at all.
+                return Some(false);
+            };
+            let enumerator_name = device
+                .OpenPropertyStore(STGM_READ)
+                .and_then(|store| store.GetValue(&PKEY_Device_EnumeratorName))
+                .ok()?
+                .to_string();
+            if !enumerator_name.eq_ignore_ascii_case("BTHHFENUM") {
+                return Some(false);
+            }
+
+            let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None).ok()?;
+            let sessions = manager.GetSessionEnumerator().ok()?;
+            let count = sessions.GetCount().ok()?;
+            Some((0..count).any(|index| {
+                sessions
+                    .GetSession(index)
+                    .and_then(|session| session.GetState())
+                    .is_ok_and(|state| state == AudioSessionStateActive)
+            }))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    pub fn is_on_call() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,621 @@
+use std::{fs, io, path::Path};
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// How far past `now` recurrences are expanded. A busy block longer than
+/// this is cut short, which the snooze cap would do anyway.
+const LOOKAHEAD_HOURS: i64 = 24;
+/// Ceiling on the occurrences walked per rule, so a daily event from years
+/// back or a malformed rule can't stall the read.
+const MAX_OCCURRENCES: u32 = 20_000;
+
+/// A timed, opaque event from the calendar file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Busy {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// A `DATE-TIME` as written, so recurrences keep their wall-clock time
+/// across daylight-saving changes.
+#[derive(Debug, Clone, Copy)]
+struct Stamp {
+    naive: NaiveDateTime,
+    zone: Zone,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    /// No `Z` and no usable `TZID`: floating, read as local time.
+    Local,
+}
+
+impl Stamp {
+    fn to_utc(self) -> Option<DateTime<Utc>> {
+        match self.zone {
+            Zone::Utc => Some(Utc.from_utc_datetime(&self.naive)),
+            Zone::Named(zone) => zone
+                .from_local_datetime(&self.naive)
+                .earliest()
+                .map(|at| at.with_timezone(&Utc)),
+            Zone::Local => Local
+                .from_local_datetime(&self.naive)
+                .earliest()
+                .map(|at| at.with_timezone(&Utc)),
+        }
+    }
+
+    fn plus_days(self, days: i64) -> Self {
+        Self {
+            naive: self.naive + Duration::days(days),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// The part of an `RRULE` that's understood: `FREQ=DAILY` or `WEEKLY`, with
+/// `INTERVAL`, `UNTIL`, `COUNT`, and plain `BYDAY` days for weekly rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    until: Option<DateTime<Utc>>,
+    count: Option<u32>,
+    by_day: Vec<Weekday>,
+}
+
+/// A `VEVENT` while it's being read.
+#[derive(Default)]
+struct Draft {
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<Duration>,
+    /// `None` also for an `RRULE` that isn't understood, which leaves only
+    /// the first occurrence.
+    rule: Option<Rule>,
+    excluded: Vec<DateTime<Utc>>,
+    /// All-day, transparent, or with a start that couldn't be read.
+    ignored: bool,
+}
+
+/// When the run of back-to-back events under way at `now` ends, or `None`
+/// if `now` is free. Reads the `.ics` file at `path`.
+pub fn busy_until(path: &Path, now: DateTime<Utc>) -> io::Result<Option<DateTime<Utc>>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(end_of_busy_block(parse_events(&contents, now), now))
+}
+
+/// Merges events that overlap or touch the one running at `now`.
+fn end_of_busy_block(mut events: Vec<Busy>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    events.sort_by_key(|event| event.start);
+    let mut end: Option<DateTime<Utc>> = None;
+    for event in events {
+        match end {
+            None if event.start <= now && now < event.end => end = Some(event.end),
+            Some(current) if event.start <= current => end = Some(current.max(event.end)),
+            Some(_) => break,
+            None => {}
+        }
+    }
+    end
+}
+
+/// Timed `VEVENT`s in `contents` that are still running at `now` or start
+/// within `LOOKAHEAD_HOURS`, with daily and weekly recurrences expanded.
+/// All-day and transparent ("free") events don't block anything and are
+/// left out. The end comes from `DTEND`, or `DURATION` when there's none.
+/// Moved single occurrences (`RECURRENCE-ID`) aren't matched up with their
+/// series.
+fn parse_events(contents: &str, now: DateTime<Utc>) -> Vec<Busy> {
+    let horizon = now + Duration::hours(LOOKAHEAD_HOURS);
+    let mut events = Vec::new();
+    let mut current: Option<Draft> = None;
+    for line in unfold(contents) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(Draft::default()),
+            ("END", "VEVENT") => {
+                if let Some(draft) = current.take() {
+                    events.extend(
+                        occurrences(draft)
+                            .into_iter()
+                            .filter(|event| event.end > now && event.start < horizon),
+                    );
+                }
+            }
+            ("DTSTART", value) => {
+                if let Some(event) = current.as_mut() {
+                    event.start = parse_stamp(params, value);
+                    event.ignored |= event.start.is_none();
+                }
+            }
+            ("DTEND", value) => {
+                if let Some(event) = current.as_mut() {
+                    event.end = parse_stamp(params, value);
+                }
+            }
+            ("DURATION", value) => {
+                if let Some(event) = current.as_mut() {
+                    event.duration = parse_duration(value);
+                }
+            }
+            ("RRULE", value) => {
+                if let Some(event) = current.as_mut() {
+                    event.rule = parse_rule(value);
+                }
+            }
+            ("EXDATE", value) => {
+                if let Some(event) = current.as_mut() {
+                    event.excluded.extend(
+                        value
+                            .split(',')
+                            .filter_map(|value| parse_stamp(params, value.trim()))
+                            .filter_map(Stamp::to_utc),
+                    );
+                }
+            }
+            ("TRANSP", "TRANSPARENT") => {
+                if let Some(event) = current.as_mut() {
+                    event.ignored = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Every occurrence of `draft`, in order; one for an event that doesn't
+/// repeat, none for one that's ignored or has no usable end.
+fn occurrences(draft: Draft) -> Vec<Busy> {
+    let (Some(start), false) = (draft.start, draft.ignored) else {
+        return Vec::new();
+    };
+    let length = match (draft.end, draft.duration) {
+        (Some(end), _) => end
+            .to_utc()
+            .zip(start.to_utc())
+            .map(|(end, start)| end - start),
+        (None, duration) => duration,
+    };
+    let Some(length) = length.filter(|length| *length > Duration::zero()) else {
+        return Vec::new();
+    };
+
+    let starts = match draft.rule {
+        Some(rule) => recurrence_starts(start, &rule),
+        None => vec![start],
+    };
+    starts
+        .into_iter()
+        .filter_map(Stamp::to_utc)
+        .filter(|start| !draft.excluded.contains(start))
+        .map(|start| Busy {
+            start,
+            end: start + length,
+        })
+        .collect()
+}
+
+/// Start of each occurrence of `rule`, beginning with `start` itself.
+/// Stops at `UNTIL`, after `COUNT`, or after `MAX_OCCURRENCES`.
+fn recurrence_starts(start: Stamp, rule: &Rule) -> Vec<Stamp> {
+    let limit = rule.count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+    let interval = i64::from(rule.interval.max(1));
+    let mut days: Vec<i64> = match rule.frequency {
+        Frequency::Daily => vec![0],
+        Frequency::Weekly if rule.by_day.is_empty() => vec![0],
+        // Weeks start on Monday; days before DTSTART's weekday in its first
+        // week are skipped below.
+        Frequency::Weekly => {
+            let offset = i64::from(start.naive.weekday().num_days_from_monday());
+            rule.by_day
+                .iter()
+                .map(|day| i64::from(day.num_days_from_monday()) - offset)
+                .collect()
+        }
+    };
+    days.sort_unstable();
+    days.dedup();
+    let period = match rule.frequency {
+        Frequency::Daily => interval,
+        Frequency::Weekly => interval * 7,
+    };
+
+    let mut starts = Vec::new();
+    let mut step = 0;
+    while (starts.len() as u32) < limit && step < i64::from(MAX_OCCURRENCES) {
+        for day in &days {
+            let days_in = step * period + day;
+            if days_in < 0 {
+                continue;
+            }
+            let occurrence = start.plus_days(days_in);
+            let over = rule
+                .until
+                .zip(occurrence.to_utc())
+                .is_some_and(|(until, at)| at > until);
+            if over {
+                return starts;
+            }
+            starts.push(occurrence);
+            if starts.len() as u32 >= limit {
+                return starts;
+            }
+        }
+        step += 1;
+    }
+    starts
+}
+
+/// `FREQ=...;INTERVAL=...;...` when the frequency is daily or weekly and
+/// nothing in it would change which days match; `None` otherwise.
+fn parse_rule(value: &str) -> Option<Rule> {
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        until: None,
+        count: None,
+        by_day: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    _ => return None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|n| *n > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => rule.until = Some(parse_until(value)?),
+            "BYDAY" => {
+                rule.by_day = value
+                    .split(',')
+                    .map(parse_weekday)
+                    .collect::<Option<Vec<_>>>()?;
+            }
+            "WKST" => {}
+            _ => return None,
+        }
+    }
+    rule.frequency = frequency?;
+    if rule.frequency == Frequency::Daily && !rule.by_day.is_empty() {
+        return None;
+    }
+    Some(rule)
+}
+
+/// `UNTIL` as a `DATE-TIME`, or as a `DATE` covering that whole day.
+fn parse_until(value: &str) -> Option<DateTime<Utc>> {
+    match parse_stamp("", value) {
+        Some(stamp) => stamp.to_utc(),
+        None => NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()?
+            .and_hms_opt(23, 59, 59)
+            .map(|end| Utc.from_utc_datetime(&end)),
+    }
+}
+
+/// `MO` through `SU`; ordinals like `2TU` only make sense monthly and
+/// aren't accepted.
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    Some(match value.trim().to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// An RFC 5545 `DURATION` such as `PT1H30M` or `P1D`. Negative durations
+/// don't describe an event's length and come back as `None`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let mut rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    let mut seen = false;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = after;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        total += match (unit, in_time) {
+            ('W', false) => Duration::weeks(amount),
+            ('D', false) => Duration::days(amount),
+            ('H', true) => Duration::hours(amount),
+            ('M', true) => Duration::minutes(amount),
+            ('S', true) => Duration::seconds(amount),
+            _ => return None,
+        };
+        seen = true;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    seen.then_some(total)
+}
+
+/// Joins folded lines (a continuation starts with a space or tab).
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// `NAME;PARAMS:VALUE` as the upper-cased name, the raw parameters, and
+/// the value.
+fn split_property(line: &str) -> Option<(String, &str, &str)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.to_ascii_uppercase(), params, value.trim()))
+}
+
+/// A `DATE-TIME` value with its zone. `None` for all-day `DATE` values and
+/// anything unreadable. Times without `Z` use their `TZID` (IANA, or the
+/// Windows names Outlook writes), or local time.
+fn parse_stamp(params: &str, value: &str) -> Option<Stamp> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Stamp {
+            naive,
+            zone: Zone::Utc,
+        });
+    }
+    if NaiveDate::parse_from_str(value, "%Y%m%d").is_ok() {
+        return None;
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .and_then(|tzid| zone_for(tzid.trim_matches('"')))
+        .map_or(Zone::Local, Zone::Named);
+    Some(Stamp { naive, zone })
+}
+
+/// An IANA zone for `tzid`, which may also be a Windows zone name or carry
+/// the leading `/` some exporters add.
+fn zone_for(tzid: &str) -> Option<Tz> {
+    let tzid = tzid.trim_start_matches('/');
+    tzid.parse::<Tz>()
+        .ok()
+        .or_else(|| windows_zone(tzid)?.parse().ok())
+}
+
+/// The IANA zone for a Windows time zone name, per CLDR's `windowsZones`
+/// (the "001" territory). Covers the zones calendars commonly carry.
+fn windows_zone(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "Dateline Standard Time" => "Etc/GMT+12",
+        "Hawaiian Standard Time" => "Pacific/Honolulu",
+        "Alaskan Standard Time" => "America/Anchorage",
+        "Pacific Standard Time" => "America/Los_Angeles",
+        "US Mountain Standard Time" => "America/Phoenix",
+        "Mountain Standard Time" => "America/Denver",
+        "Central America Standard Time" => "America/Guatemala",
+        "Central Standard Time" => "America/Chicago",
+        "Central Standard Time (Mexico)" => "America/Mexico_City",
+        "Canada Central Standard Time" => "America/Regina",
+        "SA Pacific Standard Time" => "America/Bogota",
+        "Eastern Standard Time" => "America/New_York",
+        "US Eastern Standard Time" => "America/Indianapolis",
+        "Atlantic Standard Time" => "America/Halifax",
+        "Newfoundland Standard Time" => "America/St_Johns",
+        "E. South America Standard Time" => "America/Sao_Paulo",
+        "Argentina Standard Time" => "America/Buenos_Aires",
+        "SA Western Standard Time" => "America/La_Paz",
+        "Pacific SA Standard Time" => "America/Santiago",
+        "UTC" => "Etc/UTC",
+        "GMT Standard Time" => "Europe/London",
+        "Greenwich Standard Time" => "Atlantic/Reykjavik",
+        "W. Europe Standard Time" => "Europe/Berlin",
+        "Central Europe Standard Time" => "Europe/Budapest",
+        "Romance Standard Time" => "Europe/Paris",
+        "Central European Standard Time" => "Europe/Warsaw",
+        "W. Central Africa Standard Time" => "Africa/Lagos",
+        "GTB Standard Time" => "Europe/Bucharest",
+        "E. Europe Standard Time" => "Europe/Chisinau",
+        "FLE Standard Time" => "Europe/Kiev",
+        "Israel Standard Time" => "Asia/Jerusalem",
+        "Egypt Standard Time" => "Africa/Cairo",
+        "South Africa Standard Time" => "Africa/Johannesburg",
+        "Turkey Standard Time" => "Europe/Istanbul",
+        "Arab Standard Time" => "Asia/Riyadh",
+        "Russian Standard Time" => "Europe/Moscow",
+        "E. Africa Standard Time" => "Africa/Nairobi",
+        "Iran Standard Time" => "Asia/Tehran",
+        "Arabian Standard Time" => "Asia/Dubai",
+        "Pakistan Standard Time" => "Asia/Karachi",
+        "India Standard Time" => "Asia/Calcutta",
+        "Nepal Standard Time" => "Asia/Katmandu",
+        "Bangladesh Standard Time" => "Asia/Dhaka",
+        "SE Asia Standard Time" => "Asia/Bangkok",
+        "China Standard Time" => "Asia/Shanghai",
+        "Singapore Standard Time" => "Asia/Singapore",
+        "Taipei Standard Time" => "Asia/Taipei",
+        "W. Australia Standard Time" => "Australia/Perth",
+        "Korea Standard Time" => "Asia/Seoul",
+        "Tokyo Standard Time" => "Asia/Tokyo",
+        "Cen. Australia Standard Time" => "Australia/Adelaide",
+        "AUS Central Standard Time" => "Australia/Darwin",
+        "E. Australia Standard Time" => "Australia/Brisbane",
+        "AUS Eastern Standard Time" => "Australia/Sydney",
+        "Tasmania Standard Time" => "Australia/Hobart",
+        "New Zealand Standard Time" => "Pacific/Auckland",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        parse_stamp("", value).and_then(Stamp::to_utc).unwrap()
+    }
+
+    fn calendar(events: &[&str]) -> String {
+        let mut contents = String::from("BEGIN:VCALENDAR\r\n");
+        for event in events {
+            contents.push_str("BEGIN:VEVENT\r\n");
+            contents.push_str(event);
+            contents.push_str("END:VEVENT\r\n");
+        }
+        contents.push_str("END:VCALENDAR\r\n");
+        contents
+    }
+
+    #[test]
+    fn durations_parse() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1D"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("PT45S"), Some(Duration::seconds(45)));
+        assert_eq!(parse_duration("-PT15M"), None);
+        assert_eq!(parse_duration("P"), None);
+        assert_eq!(parse_duration("PT5X"), None);
+    }
+
+    #[test]
+    fn windows_zone_names_map_to_iana() {
+        assert_eq!(
+            zone_for("Pacific Standard Time"),
+            Some(chrono_tz::America::Los_Angeles)
+        );
+        assert_eq!(
+            zone_for("W. Europe Standard Time"),
+            Some(chrono_tz::Europe::Berlin)
+        );
+        assert_eq!(
+            zone_for("/Europe/Istanbul"),
+            Some(chrono_tz::Europe::Istanbul)
+        );
+        assert_eq!(zone_for("Nowhere Standard Time"), None);
+
+        let stamp = parse_stamp("TZID=\"Pacific Standard Time\"", "20261019T090000").unwrap();
+        assert_eq!(stamp.to_utc(), Some(utc("20261019T160000Z")));
+    }
+
+    #[test]
+    fn duration_stands_in_for_a_missing_end() {
+        let contents = calendar(&["DTSTART:20261019T090000Z\r\nDURATION:PT1H\r\n"]);
+        let events = parse_events(&contents, utc("20261019T093000Z"));
+        assert_eq!(
+            events,
+            [Busy {
+                start: utc("20261019T090000Z"),
+                end: utc("20261019T100000Z"),
+            }]
+        );
+    }
+
+    #[test]
+    fn all_day_and_free_events_dont_count() {
+        let contents = calendar(&[
+            "DTSTART;VALUE=DATE:20261019\r\nDTEND;VALUE=DATE:20261020\r\n",
+            "DTSTART:20261019T090000Z\r\nDTEND:20261019T100000Z\r\nTRANSP:TRANSPARENT\r\n",
+        ]);
+        assert!(parse_events(&contents, utc("20261019T093000Z")).is_empty());
+    }
+
+    #[test]
+    fn daily_rule_stops_after_count() {
+        let rule = parse_rule("FREQ=DAILY;COUNT=3").unwrap();
+        let contents = calendar(&[
+            "DTSTART:20261012T090000Z\r\nDTEND:20261012T093000Z\r\nRRULE:FREQ=DAILY;COUNT=3\r\n",
+        ]);
+        assert_eq!(rule.count, Some(3));
+        // The third occurrence, two days on, is still there; the fourth isn't.
+        assert_eq!(parse_events(&contents, utc("20261014T091500Z")).len(), 1);
+        assert!(parse_events(&contents, utc("20261015T091500Z")).is_empty());
+    }
+
+    #[test]
+    fn weekly_rule_follows_byday_until_until() {
+        // Monday and Wednesday standups, through Wednesday 21 October.
+        let contents = calendar(&["DTSTART:20261005T090000Z\r\nDTEND:20261005T091500Z\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20261021T235959Z\r\n"]);
+        let busy = |at: &str| end_of_busy_block(parse_events(&contents, utc(at)), utc(at));
+        assert_eq!(busy("20261019T091000Z"), Some(utc("20261019T091500Z")));
+        assert_eq!(busy("20261021T091000Z"), Some(utc("20261021T091500Z")));
+        // Tuesday is free, and so is the Monday after UNTIL.
+        assert_eq!(busy("20261020T091000Z"), None);
+        assert_eq!(busy("20261026T091000Z"), None);
+    }
+
+    #[test]
+    fn weekly_rule_keeps_wall_clock_time_across_dst() {
+        // 09:00 in Berlin is 07:00 UTC in summer and 08:00 after 25 October.
+        let contents = calendar(&[
+            "DTSTART;TZID=Europe/Berlin:20261019T090000\r\nDURATION:PT30M\r\n\
+             RRULE:FREQ=WEEKLY;INTERVAL=1\r\n",
+        ]);
+        let events = parse_events(&contents, utc("20261026T080000Z"));
+        assert!(events.contains(&Busy {
+            start: utc("20261026T080000Z"),
+            end: utc("20261026T083000Z"),
+        }));
+    }
+
+    #[test]
+    fn excluded_dates_and_unsupported_rules() {
+        let contents = calendar(&[
+            "DTSTART:20261019T090000Z\r\nDTEND:20261019T100000Z\r\n\
+             RRULE:FREQ=DAILY\r\nEXDATE:20261020T090000Z\r\n",
+            "DTSTART:20260919T120000Z\r\nDTEND:20260919T130000Z\r\n\
+             RRULE:FREQ=MONTHLY;BYMONTHDAY=19\r\n",
+        ]);
+        let busy = |at: &str| end_of_busy_block(parse_events(&contents, utc(at)), utc(at));
+        assert_eq!(busy("20261020T093000Z"), None);
+        assert_eq!(busy("20261021T093000Z"), Some(utc("20261021T100000Z")));
+        // Monthly isn't expanded; only the first occurrence would count.
+        assert_eq!(busy("20261019T123000Z"), None);
+    }
+
+    #[test]
+    fn back_to_back_events_merge() {
+        let contents = calendar(&[
+            "DTSTART:20261019T090000Z\r\nDTEND:20261019T100000Z\r\n",
+            "DTSTART:20261019T100000Z\r\nDURATION:PT30M\r\n",
+            "DTSTART:20261019T110000Z\r\nDURATION:PT30M\r\n",
+        ]);
+        let now = utc("20261019T093000Z");
+        assert_eq!(
+            end_of_busy_block(parse_events(&contents, now), now),
+            Some(utc("20261019T103000Z"))
+        );
+    }
+}
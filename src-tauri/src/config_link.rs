@@ -0,0 +1,140 @@
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Url, Wry};
+
+use crate::{
+    app_state::{Preferences, PreferencesUpdate},
+    events, tray,
+};
+
+const SCHEME: &str = "touchgrass";
+const HOST: &str = "config";
+/// `touchgrass://settings/<section>` opens the window on that section.
+const SETTINGS_HOST: &str = "settings";
+/// Links longer than this are rejected before decoding.
+const MAX_LINK_LEN: usize = 4096;
+
+/// The part of `Preferences` worth sharing as a preset. Machine-specific
+/// settings (autostart, hotkeys, ports, messages) stay out of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedConfig {
+    pub interval_minutes: u64,
+    pub activity_detection: bool,
+    pub idle_threshold_minutes: u64,
+    pub idle_grace_secs: u64,
+    pub sound_enabled: bool,
+    pub playful_labels: bool,
+    #[serde(default)]
+    pub first_reminder_delay_minutes: Option<u64>,
+    #[serde(default)]
+    pub max_total_snooze_minutes: Option<u64>,
+    pub mini_break_weight: f64,
+}
+
+impl From<&Preferences> for SharedConfig {
+    fn from(prefs: &Preferences) -> Self {
+        Self {
+            interval_minutes: prefs.interval_minutes,
+            activity_detection: prefs.activity_detection,
+            idle_threshold_minutes: prefs.idle_threshold_minutes,
+            idle_grace_secs: prefs.idle_grace_secs,
+            sound_enabled: prefs.sound_enabled,
+            playful_labels: prefs.playful_labels,
+            first_reminder_delay_minutes: prefs.first_reminder_delay_minutes,
+            max_total_snooze_minutes: prefs.max_total_snooze_minutes,
+            mini_break_weight: prefs.mini_break_weight,
+        }
+    }
+}
+
+impl SharedConfig {
+    /// As an update, so applying a link goes through the same validation and
+    /// clamping as the settings screen.
+    pub fn into_update(self) -> PreferencesUpdate {
+        PreferencesUpdate {
+            interval_minutes: Some(self.interval_minutes),
+            interval_seconds: Some(None),
+            activity_detection: Some(self.activity_detection),
+            idle_threshold_minutes: Some(self.idle_threshold_minutes),
+            idle_grace_secs: Some(self.idle_grace_secs),
+            sound_enabled: Some(self.sound_enabled),
+            playful_labels: Some(self.playful_labels),
+            first_reminder_delay_minutes: Some(self.first_reminder_delay_minutes),
+            max_total_snooze_minutes: Some(self.max_total_snooze_minutes),
+            mini_break_weight: Some(self.mini_break_weight),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn export_link(prefs: &Preferences) -> String {
+    let json = serde_json::to_vec(&SharedConfig::from(prefs)).unwrap_or_default();
+    format!("{SCHEME}://{HOST}?d={}", URL_SAFE_NO_PAD.encode(json))
+}
+
+pub fn parse_link(link: &str) -> Option<SharedConfig> {
+    if link.len() > MAX_LINK_LEN {
+        return None;
+    }
+    let url = Url::parse(link).ok()?;
+    if url.scheme() != SCHEME || url.host_str() != Some(HOST) {
+        return None;
+    }
+    let (_, data) = url.query_pairs().find(|(key, _)| key == "d")?;
+    let json = URL_SAFE_NO_PAD.decode(data.as_bytes()).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// The most recent config link opened, kept until the UI asks for it so a
+/// link that launched the app isn't lost before the window is listening.
+#[derive(Default)]
+pub struct PendingConfigLink(Mutex<Option<events::ConfigLinkPayload>>);
+
+impl PendingConfigLink {
+    pub fn take(&self) -> Option<events::ConfigLinkPayload> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Handles URLs opened through the deep-link plugin. Config links are not
+/// applied here; the UI gets them on `CONFIG_LINK_EVENT` and asks first.
+pub fn handle_urls(app: &AppHandle<Wry>, urls: Vec<Url>) {
+    for url in urls {
+        if url.scheme() == SCHEME && url.host_str() == Some(SETTINGS_HOST) {
+            let section = url.path().trim_matches('/');
+            let section = (!section.is_empty()).then_some(section);
+            if let Err(message) = tray::open_settings(app, section) {
+                let _ = app.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "warning".into(),
+                        message,
+                    },
+                );
+            }
+            continue;
+        }
+
+        let link = url.to_string();
+        let Some(config) = parse_link(&link) else {
+            let _ = app.emit(
+                events::LOG_EVENT,
+                events::LogPayload {
+                    level: "warning".into(),
+                    message: format!("ignored an unrecognized TouchGrass link: {link}"),
+                },
+            );
+            continue;
+        };
+
+        let payload = events::ConfigLinkPayload { link, config };
+        if let Some(pending) = app.try_state::<PendingConfigLink>() {
+            *pending.0.lock().unwrap() = Some(payload.clone());
+        }
+        let _ = app.emit(events::CONFIG_LINK_EVENT, payload);
+        tray::focus_main_window(app);
+    }
+}
@@ -0,0 +1,210 @@
+/// Whether the display is powered down while the system stays awake, or
+/// `None` when it can't be told on this platform or session. Wayland is
+/// only covered under GNOME; other compositors don't expose output power
+/// to clients, so there it stays `None`.
+pub fn is_display_off() -> Option<bool> {
+    platform::is_display_off()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::sync::OnceLock;
+
+    use x11::{dpms, xlib};
+    use zbus::blocking::{Connection, Proxy};
+
+    /// X11's DPMS level, or failing that (no X server, or XWayland, which
+    /// has no DPMS) what Mutter says.
+    pub fn is_display_off() -> Option<bool> {
+        dpms_off().or_else(mutter_off)
+    }
+
+    /// Reads `PowerSaveMode` from GNOME's display config: 0 is on, 1 to 3
+    /// are standby, suspend and off, and -1 means Mutter doesn't know.
+    fn mutter_off() -> Option<bool> {
+        static SESSION_BUS: OnceLock<Option<Connection>> = OnceLock::new();
+        let connection = SESSION_BUS
+            .get_or_init(|| Connection::session().ok())
+            .as_ref()?;
+
+        let display_config = Proxy::new(
+            connection,
+            "org.gnome.Mutter.DisplayConfig",
+            "/org/gnome/Mutter/DisplayConfig",
+            "org.gnome.Mutter.DisplayConfig",
+        )
+        .ok()?;
+        match display_config.get_property::<i32>("PowerSaveMode").ok()? {
+            mode if mode < 0 => None,
+            mode => Some(mode != 0),
+        }
+    }
+
+    /// Asks the X server for the DPMS power level.
+    fn dpms_off() -> Option<bool> {
+        // SAFETY: the display is opened and closed here and never shared; the
+        // DPMS calls only write into the locals passed to them.
+        unsafe {
+            let display = xlib::XOpenDisplay(std::ptr::null());
+            if display.is_null() {
+                return None;
+            }
+
+            let result = if dpms::DPMSCapable(display) != 0 {
+                let mut power_level = 0;
+                let mut enabled = 0;
+                if dpms::DPMSInfo(display, &mut power_level, &mut enabled) != 0 {
+                    Some(enabled != 0 && power_level != dpms::DPMSModeOn)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            xlib::XCloseDisplay(display);
+            result
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGMainDisplayID() -> u32;
+        fn CGDisplayIsAsleep(display: u32) -> u32;
+    }
+
+    pub fn is_display_off() -> Option<bool> {
+        // SAFETY: both calls take and return plain values.
+        Some(unsafe { CGDisplayIsAsleep(CGMainDisplayID()) } != 0)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{
+        sync::{
+            atomic::{AtomicU8, Ordering},
+            OnceLock,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use windows_sys::Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        System::{
+            LibraryLoader::GetModuleHandleW,
+            Power::{RegisterPowerSettingNotification, POWERBROADCAST_SETTING},
+            SystemServices::GUID_CONSOLE_DISPLAY_STATE,
+        },
+        UI::WindowsAndMessaging::{
+            CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+            DEVICE_NOTIFY_WINDOW_HANDLE, HWND_MESSAGE, MSG, PBT_POWERSETTINGCHANGE,
+            WM_POWERBROADCAST, WNDCLASSW,
+        },
+    };
+
+    /// Last `GUID_CONSOLE_DISPLAY_STATE` value: 0 off, 1 on, 2 dimmed.
+    static DISPLAY_STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+    const UNKNOWN: u8 = u8::MAX;
+
+    /// Windows has nothing to poll (`SC_MONITORPOWER` only sets the power
+    /// state), so a message-only window subscribes to display state
+    /// changes on first use. Windows sends the current state right away.
+    pub fn is_display_off() -> Option<bool> {
+        static LISTENER: OnceLock<bool> = OnceLock::new();
+        if !*LISTENER.get_or_init(start_listener) {
+            return None;
+        }
+        match DISPLAY_STATE.load(Ordering::Relaxed) {
+            UNKNOWN => None,
+            state => Some(state == 0),
+        }
+    }
+
+    fn start_listener() -> bool {
+        let started = thread::Builder::new()
+            .name("display-power".into())
+            .spawn(listen)
+            .is_ok();
+        // Give the initial broadcast a moment so the first answer is real.
+        for _ in 0..10 {
+            if DISPLAY_STATE.load(Ordering::Relaxed) != UNKNOWN {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        started
+    }
+
+    fn listen() {
+        let class: Vec<u16> = "TouchGrassDisplayPower\0".encode_utf16().collect();
+        // SAFETY: the class name outlives the window, which lives as long
+        // as this thread; the message loop only hands messages back to it.
+        unsafe {
+            let instance = GetModuleHandleW(std::ptr::null());
+            let window_class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance,
+                lpszClassName: class.as_ptr(),
+                ..Default::default()
+            };
+            RegisterClassW(&window_class);
+            let window = CreateWindowExW(
+                0,
+                class.as_ptr(),
+                std::ptr::null(),
+                0,
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null(),
+            );
+            if window.is_null()
+                || RegisterPowerSettingNotification(
+                    window,
+                    &GUID_CONSOLE_DISPLAY_STATE,
+                    DEVICE_NOTIFY_WINDOW_HANDLE,
+                ) == 0
+            {
+                return;
+            }
+            let mut message = MSG::default();
+            while GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {
+                DispatchMessageW(&message);
+            }
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        window: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if message == WM_POWERBROADCAST && wparam == PBT_POWERSETTINGCHANGE as WPARAM {
+            // SAFETY: for PBT_POWERSETTINGCHANGE, lparam points at a
+            // POWERBROADCAST_SETTING whose data is the new state.
+            let setting = &*(lparam as *const POWERBROADCAST_SETTING);
+            if setting.DataLength >= 1 {
+                DISPLAY_STATE.store(setting.Data[0], Ordering::Relaxed);
+            }
+            return 1;
+        }
+        DefWindowProcW(window, message, wparam, lparam)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub fn is_display_off() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,67 @@
+/// Whether the desktop is holding notification banners back right now (Do
+/// Not Disturb, Focus Assist, a presentation), or `None` when it can't be
+/// told here. Blocks briefly, so call it off the async runtime.
+pub fn is_active() -> Option<bool> {
+    platform::is_active()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::process::Command;
+
+    use zbus::blocking::{Connection, Proxy};
+
+    /// Asks the notification server for its `Inhibited` property (KDE and a
+    /// few others have it), then GNOME's `show-banners` setting.
+    pub fn is_active() -> Option<bool> {
+        inhibited().or_else(gnome_banners_hidden)
+    }
+
+    fn inhibited() -> Option<bool> {
+        let connection = Connection::session().ok()?;
+        let server = Proxy::new(
+            &connection,
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications",
+        )
+        .ok()?;
+        server.get_property::<bool>("Inhibited").ok()
+    }
+
+    fn gnome_banners_hidden() -> Option<bool> {
+        let output = Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        match String::from_utf8_lossy(&output.stdout).trim() {
+            "true" => Some(false),
+            "false" => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS};
+
+    /// Anything but "accepts notifications" (quiet hours, a full-screen app,
+    /// presentation mode, a locked screen) keeps toasts from popping up.
+    pub fn is_active() -> Option<bool> {
+        let mut state = 0;
+        // SAFETY: the call only writes the state into `state`.
+        let result = unsafe { SHQueryUserNotificationState(&mut state) };
+        (result >= 0).then_some(state != QUNS_ACCEPTS_NOTIFICATIONS)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    pub fn is_active() -> Option<bool> {
+        None
+    }
+}
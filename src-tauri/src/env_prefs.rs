@@ -0,0 +1,122 @@
+use serde_json::Value;
+
+use crate::app_state::Preferences;
+
+/// Preferences that can be set from the environment, as
+/// `(variable, preferences.json key)`. Values are read as JSON when they
+/// parse (`30`, `true`, `null`) and as plain strings otherwise.
+const VARS: &[(&str, &str)] = &[
+    ("TOUCHGRASS_INTERVAL_MINUTES", "intervalMinutes"),
+    (
+        "TOUCHGRASS_FIRST_REMINDER_DELAY_MINUTES",
+        "firstReminderDelayMinutes",
+    ),
+    ("TOUCHGRASS_ACTIVITY_DETECTION", "activityDetection"),
+    ("TOUCHGRASS_IDLE_THRESHOLD_MINUTES", "idleThresholdMinutes"),
+    ("TOUCHGRASS_SOUND_ENABLED", "soundEnabled"),
+    ("TOUCHGRASS_AUTOSTART_ENABLED", "autostartEnabled"),
+    ("TOUCHGRASS_THEME", "theme"),
+    ("TOUCHGRASS_MOOD", "mood"),
+    ("TOUCHGRASS_PRESERVE_RHYTHM", "preserveRhythm"),
+    ("TOUCHGRASS_SNAP_TO_CLOCK", "snapToClock"),
+    ("TOUCHGRASS_ATTENTION_MODE", "attentionMode"),
+];
+
+/// `prefs` with any `TOUCHGRASS_*` variables from `VARS` laid over it. A
+/// value that doesn't fit its preference is reported and skipped.
+///
+/// The result is only ever held in memory; `preferences.json` keeps what
+/// the user saved, so unsetting a variable brings that back.
+pub fn apply(prefs: Preferences) -> Preferences {
+    overlay(prefs, true).0
+}
+
+/// `(variable, preferences.json key)` for each variable that currently
+/// overrides a preference.
+pub fn active() -> Vec<(&'static str, &'static str)> {
+    overlay(Preferences::default(), false).1
+}
+
+/// `updated` with any change to an overridden preference undone, as long as
+/// the change only repeats the value the variable already gives it (a form
+/// sending back what it was shown). Any other change to an overridden
+/// preference is refused with the variable's name, since it would be lost
+/// the moment it was saved.
+pub fn keep_overridden(
+    saved: &Preferences,
+    effective: &Preferences,
+    updated: Preferences,
+) -> Result<Preferences, &'static str> {
+    let overrides = active();
+    if overrides.is_empty() {
+        return Ok(updated);
+    }
+    let (Ok(saved), Ok(effective), Ok(mut values)) = (
+        serde_json::to_value(saved),
+        serde_json::to_value(effective),
+        serde_json::to_value(&updated),
+    ) else {
+        return Ok(updated);
+    };
+    for (var, key) in overrides {
+        if values[key] == saved[key] {
+            continue;
+        }
+        if values[key] != effective[key] {
+            return Err(var);
+        }
+        values[key] = saved[key].clone();
+    }
+    Ok(serde_json::from_value(values).unwrap_or(updated))
+}
+
+fn overlay(prefs: Preferences, report: bool) -> (Preferences, Vec<(&'static str, &'static str)>) {
+    let mut applied = Vec::new();
+    let Ok(mut base) = serde_json::to_value(&prefs) else {
+        return (prefs, applied);
+    };
+    for (var, key) in VARS {
+        let Ok(raw) = std::env::var(var) else {
+            continue;
+        };
+        let value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+        let mut candidate = base.clone();
+        candidate[*key] = value;
+        match serde_json::from_value::<Preferences>(candidate.clone()) {
+            Ok(_) => {
+                base = candidate;
+                applied.push((*var, *key));
+            }
+            Err(err) if report => eprintln!("TouchGrass: ignoring {var} ({err})."),
+            Err(_) => {}
+        }
+    }
+    if applied.is_empty() {
+        return (prefs, applied);
+    }
+    let overlaid = serde_json::from_value(base).unwrap_or(prefs);
+    (overlaid, applied)
+}
+
+/// The `VARS` preferences as `TOUCHGRASS_*=value` lines for a shell profile,
+/// single-quoted so any value survives `sh`. Unset optional preferences are
+/// left out.
+pub fn export(prefs: &Preferences) -> String {
+    let Ok(values) = serde_json::to_value(prefs) else {
+        return String::new();
+    };
+    VARS.iter()
+        .filter_map(|(var, key)| {
+            let value = match values.get(key)? {
+                Value::Null => return None,
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            Some(format!("{var}={}\n", shell_quote(&value)))
+        })
+        .collect()
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
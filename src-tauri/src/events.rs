@@ -1,10 +1,13 @@
 use serde::Serialize;
 
-use crate::app_state::StatusSnapshot;
+use crate::{app_state::StatusSnapshot, config_link::SharedConfig};
 
 pub const STATUS_EVENT: &str = "touchgrass://status";
 pub const REMINDER_EVENT: &str = "touchgrass://reminder";
 pub const LOG_EVENT: &str = "touchgrass://log";
+pub const UPDATE_EVENT: &str = "touchgrass://update";
+pub const CONFIG_LINK_EVENT: &str = "touchgrass://config-link";
+pub const NAVIGATE_EVENT: &str = "touchgrass://navigate";
 
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,3 +21,31 @@ pub struct LogPayload {
     pub level: String,
     pub message: String,
 }
+
+/// Progress of `install_update`, emitted on `UPDATE_EVENT`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "stage")]
+pub enum UpdatePayload {
+    #[serde(rename_all = "camelCase")]
+    Downloading {
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    Installing,
+    Installed,
+}
+
+/// A shared config link was opened; the UI confirms before applying it.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigLinkPayload {
+    pub link: String,
+    pub config: SharedConfig,
+}
+
+/// Asks the UI to show a settings section; `None` is the default view.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigatePayload {
+    pub section: Option<String>,
+}
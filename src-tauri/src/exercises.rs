@@ -0,0 +1,46 @@
+use serde::Serialize;
+
+/// A short stretch that can be fired on demand as a reminder.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Exercise {
+    pub name: &'static str,
+    pub instructions: &'static str,
+}
+
+impl Exercise {
+    pub fn reminder_message(&self) -> String {
+        format!("{}: {}", self.name, self.instructions)
+    }
+}
+
+pub const EXERCISES: &[Exercise] = &[
+    Exercise {
+        name: "Neck rolls",
+        instructions: "Slowly roll your head in a circle, 5 times each way.",
+    },
+    Exercise {
+        name: "Shoulder shrugs",
+        instructions: "Lift your shoulders to your ears, hold 3s, drop. Repeat 10x.",
+    },
+    Exercise {
+        name: "Wrist stretch",
+        instructions: "Arm out, palm up, gently pull your fingers back for 15s per hand.",
+    },
+    Exercise {
+        name: "Standing back bend",
+        instructions: "Stand, hands on lower back, lean back gently for 10s. Repeat 3x.",
+    },
+    Exercise {
+        name: "Calf raises",
+        instructions: "Rise onto your toes and lower slowly, 15 times.",
+    },
+    Exercise {
+        name: "20-20-20",
+        instructions: "Look at something 20 feet away for 20 seconds.",
+    },
+    Exercise {
+        name: "Chest opener",
+        instructions: "Clasp hands behind your back, straighten arms, lift your chest for 15s.",
+    },
+];
@@ -0,0 +1,114 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    async_runtime, AppHandle, Manager, PhysicalPosition, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, Wry,
+};
+
+use crate::app_state::AppState;
+
+pub const FOCUS_BAR_LABEL: &str = "focus-bar";
+const WIDTH: f64 = 240.0;
+const HEIGHT: f64 = 56.0;
+/// Gap between the bar and the screen corner it starts in.
+const CORNER_MARGIN: i32 = 24;
+/// How long the bar has to stay put before its position is saved, so a
+/// drag doesn't rewrite the stats file on every step.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Bumped on every move; a pending save only goes ahead if it's still the
+/// latest one.
+static MOVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Where the user last left the bar, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusBarPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Opens the bar where it was last left, or in the top-right corner of the
+/// primary monitor the first time (and when that spot is no longer on any
+/// screen). Just brings it back if it's already open.
+pub fn show(app: &AppHandle<Wry>, saved: Option<FocusBarPosition>) -> tauri::Result<()> {
+    if let Some(existing) = app.get_webview_window(FOCUS_BAR_LABEL) {
+        return existing.show();
+    }
+
+    let window =
+        WebviewWindowBuilder::new(app, FOCUS_BAR_LABEL, WebviewUrl::App("focus-bar".into()))
+            .title("TouchGrass focus bar")
+            .inner_size(WIDTH, HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .transparent(true)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .focused(false)
+            .visible(false)
+            .build()?;
+
+    let position = saved
+        .filter(|position| on_screen(&window, *position))
+        .or_else(|| top_right(&window));
+    if let Some(FocusBarPosition { x, y }) = position {
+        window.set_position(PhysicalPosition::new(x, y))?;
+    }
+    window.show()
+}
+
+pub fn hide(app: &AppHandle<Wry>) -> tauri::Result<()> {
+    match app.get_webview_window(FOCUS_BAR_LABEL) {
+        Some(window) => window.close(),
+        None => Ok(()),
+    }
+}
+
+/// Saves the bar's position once it stops moving.
+pub fn moved(app: &AppHandle<Wry>, position: PhysicalPosition<i32>) {
+    let generation = MOVE_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    let app = app.clone();
+    async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        if MOVE_GENERATION.load(Ordering::Relaxed) != generation {
+            return;
+        }
+        if let Some(state) = app.try_state::<Arc<AppState>>() {
+            state.set_focus_bar_position(FocusBarPosition {
+                x: position.x,
+                y: position.y,
+            });
+        }
+    });
+}
+
+fn on_screen(window: &WebviewWindow<Wry>, position: FocusBarPosition) -> bool {
+    window.available_monitors().is_ok_and(|monitors| {
+        monitors.iter().any(|monitor| {
+            let origin = monitor.position();
+            let size = monitor.size();
+            (origin.x..origin.x + size.width as i32).contains(&position.x)
+                && (origin.y..origin.y + size.height as i32).contains(&position.y)
+        })
+    })
+}
+
+fn top_right(window: &WebviewWindow<Wry>) -> Option<FocusBarPosition> {
+    let monitor = window.primary_monitor().ok().flatten()?;
+    let width = window.outer_size().ok()?.width as i32;
+    let origin = monitor.position();
+    let scale = monitor.scale_factor();
+    let margin = (CORNER_MARGIN as f64 * scale).round() as i32;
+    Some(FocusBarPosition {
+        x: origin.x + monitor.size().width as i32 - width - margin,
+        y: origin.y + margin,
+    })
+}
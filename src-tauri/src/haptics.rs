@@ -0,0 +1,87 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, Wry};
+
+use crate::events;
+
+/// Set once the "not available" notice has been logged, so it isn't repeated
+/// for every reminder.
+static UNAVAILABLE_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Gives a short haptic tap for a reminder where the hardware supports it.
+/// Returns right away; the feedback itself runs on the main thread.
+pub fn pulse(app: &AppHandle<Wry>) {
+    if !platform::SUPPORTED {
+        log_unavailable(app, "haptic feedback isn't supported on this platform");
+        return;
+    }
+
+    let handle = app.clone();
+    if let Err(err) = app.run_on_main_thread(move || {
+        if !platform::perform() {
+            log_unavailable(&handle, "no haptic feedback device was found");
+        }
+    }) {
+        log_unavailable(app, &format!("haptic feedback failed: {err}"));
+    }
+}
+
+/// Whether this platform has a haptics API at all; the hardware may still
+/// lack a Force Touch trackpad.
+pub fn supported() -> bool {
+    platform::SUPPORTED
+}
+
+fn log_unavailable(app: &AppHandle<Wry>, message: &str) {
+    if UNAVAILABLE_LOGGED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    let _ = app.emit(
+        events::LOG_EVENT,
+        events::LogPayload {
+            level: "info".into(),
+            message: message.to_string(),
+        },
+    );
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::{class, msg_send, runtime::AnyObject};
+
+    pub const SUPPORTED: bool = true;
+
+    /// `NSHapticFeedbackPatternGeneric`.
+    const PATTERN_GENERIC: isize = 0;
+    /// `NSHapticFeedbackPerformanceTimeNow`.
+    const PERFORMANCE_TIME_NOW: usize = 1;
+
+    /// Asks AppKit's default performer for a generic tap. Macs without a
+    /// Force Touch trackpad accept the call and do nothing.
+    pub fn perform() -> bool {
+        // SAFETY: both selectors exist on macOS 10.11+, and this runs on the
+        // main thread as AppKit expects.
+        unsafe {
+            let performer: *mut AnyObject =
+                msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+            if performer.is_null() {
+                return false;
+            }
+            let _: () = msg_send![
+                performer,
+                performFeedbackPattern: PATTERN_GENERIC,
+                performanceTime: PERFORMANCE_TIME_NOW
+            ];
+        }
+        true
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub const SUPPORTED: bool = false;
+
+    pub fn perform() -> bool {
+        false
+    }
+}
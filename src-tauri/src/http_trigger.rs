@@ -0,0 +1,241 @@
+use std::{net::Ipv4Addr, sync::Arc, sync::Mutex, time::Duration};
+
+use serde::Deserialize;
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Emitter, Manager, Wry,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{app_state::AppState, events};
+
+/// Header carrying the shared secret from `http_trigger_token`.
+const TOKEN_HEADER: &str = "x-touchgrass-token";
+/// Requests bigger than this (headers and body together) are refused.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+const READ_TIMEOUT_SECS: u64 = 5;
+/// Wait after a failed `accept`, doubling while failures keep coming (out of
+/// file descriptors, say) so the loop doesn't spin.
+const ACCEPT_BACKOFF_MIN_MS: u64 = 100;
+const ACCEPT_BACKOFF_MAX_MS: u64 = 5_000;
+
+#[derive(Debug, Default, Deserialize)]
+struct TriggerBody {
+    message: Option<String>,
+}
+
+/// Optional HTTP endpoint on `127.0.0.1` so scripts and editors can ask for a
+/// reminder: `POST /trigger` with an optional `{ "message": "..." }` body.
+pub struct HttpTrigger {
+    server: Arc<ServerSlot>,
+}
+
+/// The running server's port and token, and its task.
+type ServerSlot = Mutex<Option<(u16, String, JoinHandle<()>)>>;
+
+impl HttpTrigger {
+    pub fn new() -> Self {
+        Self {
+            server: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Starts, restarts, or stops the server so it matches `port` and `token`.
+    pub fn configure(&self, app: &AppHandle<Wry>, port: Option<u16>, token: Option<&str>) {
+        let mut server = self.server.lock().unwrap();
+        let wanted = port.zip(token);
+        let current = server
+            .as_ref()
+            .map(|(port, token, _)| (*port, token.as_str()));
+        if current == wanted {
+            return;
+        }
+
+        if let Some((_, _, handle)) = server.take() {
+            handle.abort();
+        }
+
+        let Some((port, token)) = wanted else {
+            return;
+        };
+
+        let app = app.clone();
+        let slot = self.server.clone();
+        let secret = token.to_string();
+        let handle = async_runtime::spawn(async move {
+            serve(app, port, secret, slot).await;
+        });
+        *server = Some((port, token.to_string(), handle));
+    }
+}
+
+async fn serve(app: AppHandle<Wry>, port: u16, token: String, slot: Arc<ServerSlot>) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            // Forget this server so saving the same port again retries.
+            let mut server = slot.lock().unwrap();
+            if server
+                .as_ref()
+                .is_some_and(|(current, secret, _)| *current == port && *secret == token)
+            {
+                *server = None;
+            }
+            drop(server);
+            let _ = app.emit(
+                events::LOG_EVENT,
+                events::LogPayload {
+                    level: "error".into(),
+                    message: format!("HTTP trigger could not bind 127.0.0.1:{port}: {err}"),
+                },
+            );
+            return;
+        }
+    };
+
+    let token = Arc::new(token);
+    let mut backoff: Option<Duration> = None;
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => {
+                backoff = None;
+                stream
+            }
+            Err(err) => {
+                let wait = match backoff {
+                    Some(wait) => (wait * 2).min(Duration::from_millis(ACCEPT_BACKOFF_MAX_MS)),
+                    None => {
+                        let _ = app.emit(
+                            events::LOG_EVENT,
+                            events::LogPayload {
+                                level: "warning".into(),
+                                message: format!("HTTP trigger accept failed, backing off: {err}"),
+                            },
+                        );
+                        Duration::from_millis(ACCEPT_BACKOFF_MIN_MS)
+                    }
+                };
+                backoff = Some(wait);
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+        };
+
+        let app = app.clone();
+        let token = token.clone();
+        async_runtime::spawn(async move {
+            handle_connection(app, stream, &token).await;
+        });
+    }
+}
+
+async fn handle_connection(app: AppHandle<Wry>, mut stream: TcpStream, token: &str) {
+    let request = tokio::time::timeout(
+        Duration::from_secs(READ_TIMEOUT_SECS),
+        read_request(&mut stream, token),
+    )
+    .await;
+
+    let response = match request {
+        Ok(Ok(request)) => respond(&app, request).await,
+        Ok(Err(response)) => response,
+        Err(_) => "408 Request Timeout",
+    };
+    let reply = format!("HTTP/1.1 {response}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    let _ = stream.write_all(reply.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// An authorized `POST /trigger`; anything else is turned away by
+/// `read_request` before its body is read.
+struct Request {
+    body: Vec<u8>,
+}
+
+async fn respond(app: &AppHandle<Wry>, request: Request) -> &'static str {
+    let body: TriggerBody = if request.body.iter().all(u8::is_ascii_whitespace) {
+        TriggerBody::default()
+    } else {
+        match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(_) => return "400 Bad Request",
+        }
+    };
+
+    let Some(state) = app.try_state::<Arc<AppState>>() else {
+        return "503 Service Unavailable";
+    };
+    let message = body
+        .message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty());
+    state.trigger_with_message(message).await;
+    "204 No Content"
+}
+
+/// Reads one HTTP/1.1 request, or the status to refuse it with. The route
+/// and `token` are checked from the headers alone, so an unauthorized
+/// client never gets its body buffered.
+async fn read_request(stream: &mut TcpStream, token: &str) -> Result<Request, &'static str> {
+    const BAD_REQUEST: &str = "400 Bad Request";
+    let mut buffer = Vec::with_capacity(1024);
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() >= MAX_REQUEST_BYTES {
+            return Err(BAD_REQUEST);
+        }
+        buffer.extend_from_slice(read_chunk(stream).await.ok_or(BAD_REQUEST)?.as_slice());
+    };
+
+    let head = std::str::from_utf8(&buffer[..header_end]).map_err(|_| BAD_REQUEST)?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().ok_or(BAD_REQUEST)?.split_whitespace();
+    let method = request_line.next().ok_or(BAD_REQUEST)?;
+    let path = request_line.next().ok_or(BAD_REQUEST)?;
+
+    let mut content_length = 0usize;
+    let mut sent_token = None;
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(BAD_REQUEST)?;
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.parse().map_err(|_| BAD_REQUEST)?,
+            TOKEN_HEADER => sent_token = Some(value),
+            _ => {}
+        }
+    }
+
+    if path != "/trigger" {
+        return Err("404 Not Found");
+    }
+    if method != "POST" {
+        return Err("405 Method Not Allowed");
+    }
+    if sent_token != Some(token) {
+        return Err("401 Unauthorized");
+    }
+
+    let body_start = header_end + 4;
+    if content_length > MAX_REQUEST_BYTES.saturating_sub(body_start) {
+        return Err(BAD_REQUEST);
+    }
+    let mut body = buffer.split_off(body_start);
+    while body.len() < content_length {
+        body.extend_from_slice(read_chunk(stream).await.ok_or(BAD_REQUEST)?.as_slice());
+    }
+    body.truncate(content_length);
+
+    Ok(Request { body })
+}
+
+/// Up to 1 KiB more of the request, or `None` once the client hangs up.
+async fn read_chunk(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut chunk = [0u8; 1024];
+    let read = stream.read(&mut chunk).await.ok()?;
+    (read > 0).then(|| chunk[..read].to_vec())
+}
@@ -1,39 +1,95 @@
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+#[cfg(target_os = "linux")]
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Where idle time is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdleBackend {
+    /// Wayland's ext-idle-notify-v1; only reports whether the threshold passed.
+    #[cfg(target_os = "linux")]
+    WaylandIdleNotify,
+    #[cfg(target_os = "linux")]
+    X11,
+    /// The OS's own last-input time (Windows, macOS).
+    #[cfg(not(target_os = "linux"))]
+    Native,
+}
+
+/// How far the Wayland idle thread got, for telling a compositor without
+/// ext-idle-notify apart from a connection that dropped later. All `false`
+/// when Wayland isn't in use.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaylandHealth {
+    pub connected: bool,
+    pub seat_bound: bool,
+    pub notifier_bound: bool,
+    /// The thread is still dispatching events. Once it stops, idle time comes
+    /// from X11 instead.
+    pub alive: bool,
+}
+
+/// `WaylandHealth` as the thread updates it.
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct WaylandFlags {
+    seat_bound: AtomicBool,
+    notifier_bound: AtomicBool,
+    alive: AtomicBool,
+}
 
 /// Cross-platform idle time tracker with Wayland ext-idle-notify-v1 support
 pub struct IdleDetector {
     idle_since_timestamp: Arc<AtomicU64>, // Unix timestamp when user became idle
     is_idle: Arc<AtomicBool>,
-    threshold_secs: u64,
+    threshold_secs: Arc<AtomicU64>,
     #[cfg(target_os = "linux")]
-    wayland_handle: Option<WaylandIdleHandle>,
+    wayland_handle: Mutex<Option<WaylandIdleHandle>>,
+    /// Bumped whenever the Wayland notification is recreated; a thread
+    /// whose generation is behind stops at its next event.
+    #[cfg(target_os = "linux")]
+    wayland_generation: Arc<AtomicU64>,
 }
 
 #[cfg(target_os = "linux")]
 struct WaylandIdleHandle {
     #[allow(dead_code)] // Kept alive to prevent thread from being dropped
     thread_handle: std::thread::JoinHandle<()>,
+    flags: Arc<WaylandFlags>,
+    wake: WakeSlot,
 }
 
+/// Set by the Wayland thread once it's dispatching; makes the compositor
+/// send it an event, so a thread left behind notices straight away.
+#[cfg(target_os = "linux")]
+type WakeSlot = Arc<Mutex<Option<Box<dyn FnOnce() + Send>>>>;
+
 impl IdleDetector {
     pub fn new(idle_threshold_secs: u64) -> Self {
         let idle_since_timestamp = Arc::new(AtomicU64::new(0));
         let is_idle = Arc::new(AtomicBool::new(false));
+        let threshold_secs = Arc::new(AtomicU64::new(idle_threshold_secs));
 
         #[cfg(target_os = "linux")]
         {
+            let wayland_generation = Arc::new(AtomicU64::new(0));
             let wayland_handle = Self::setup_wayland_idle_detection(
-                idle_threshold_secs,
+                threshold_secs.clone(),
                 idle_since_timestamp.clone(),
                 is_idle.clone(),
+                wayland_generation.clone(),
             );
 
             Self {
                 idle_since_timestamp,
                 is_idle,
-                threshold_secs: idle_threshold_secs,
-                wayland_handle,
+                threshold_secs,
+                wayland_handle: Mutex::new(wayland_handle),
+                wayland_generation,
             }
         }
 
@@ -42,20 +98,59 @@ impl IdleDetector {
             Self {
                 idle_since_timestamp,
                 is_idle,
-                threshold_secs: idle_threshold_secs,
+                threshold_secs,
             }
         }
     }
 
+    /// Applies a new idle threshold right away. X11 and the native backends
+    /// read raw idle time and need nothing else; the Wayland notification has
+    /// the timeout built in, so the old thread is woken to tear its
+    /// notification down, a fresh one replaces it, and the idle state starts
+    /// over from "active".
+    pub fn set_threshold(&self, idle_threshold_secs: u64) {
+        if self
+            .threshold_secs
+            .swap(idle_threshold_secs, Ordering::Relaxed)
+            == idle_threshold_secs
+        {
+            return;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let mut wayland_handle = self.wayland_handle.lock().unwrap();
+            if wayland_handle.is_none() {
+                return;
+            }
+            self.wayland_generation.fetch_add(1, Ordering::SeqCst);
+            if let Some(wake) = wayland_handle
+                .as_ref()
+                .and_then(|handle| handle.wake.lock().unwrap().take())
+            {
+                wake();
+            }
+            self.is_idle.store(false, Ordering::Relaxed);
+            self.idle_since_timestamp.store(0, Ordering::Relaxed);
+            *wayland_handle = Self::setup_wayland_idle_detection(
+                self.threshold_secs.clone(),
+                self.idle_since_timestamp.clone(),
+                self.is_idle.clone(),
+                self.wayland_generation.clone(),
+            );
+        }
+    }
+
     #[cfg(target_os = "linux")]
     fn setup_wayland_idle_detection(
-        threshold_secs: u64,
+        threshold_secs: Arc<AtomicU64>,
         idle_since_timestamp: Arc<AtomicU64>,
         is_idle: Arc<AtomicBool>,
+        generation: Arc<AtomicU64>,
     ) -> Option<WaylandIdleHandle> {
         use wayland_client::{
             globals::{registry_queue_init, GlobalListContents},
-            protocol::{wl_registry, wl_seat},
+            protocol::{wl_callback, wl_registry, wl_seat},
             Connection, Dispatch, EventQueue, QueueHandle,
         };
         use wayland_protocols::ext::idle_notify::v1::client::{
@@ -73,7 +168,15 @@ impl IdleDetector {
             idle_notifier: Option<ExtIdleNotifierV1>,
             idle_since_timestamp: Arc<AtomicU64>,
             is_idle: Arc<AtomicBool>,
-            threshold_secs: u64,
+            threshold_secs: Arc<AtomicU64>,
+            generation: Arc<AtomicU64>,
+            own_generation: u64,
+        }
+
+        impl AppData {
+            fn is_current(&self) -> bool {
+                self.generation.load(Ordering::SeqCst) == self.own_generation
+            }
         }
 
         impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for AppData {
@@ -100,6 +203,19 @@ impl IdleDetector {
             }
         }
 
+        /// Only there to wake the thread; see `WaylandIdleHandle::wake`.
+        impl Dispatch<wl_callback::WlCallback, ()> for AppData {
+            fn event(
+                _state: &mut Self,
+                _callback: &wl_callback::WlCallback,
+                _event: wl_callback::Event,
+                _data: &(),
+                _conn: &Connection,
+                _qh: &QueueHandle<Self>,
+            ) {
+            }
+        }
+
         impl Dispatch<ExtIdleNotifierV1, ()> for AppData {
             fn event(
                 _state: &mut Self,
@@ -123,6 +239,9 @@ impl IdleDetector {
             ) {
                 use std::time::SystemTime;
 
+                if !state.is_current() {
+                    return;
+                }
                 match event {
                     IdleEvent::Idled => {
                         let now_secs = SystemTime::now()
@@ -132,7 +251,7 @@ impl IdleDetector {
 
                         state.is_idle.store(true, Ordering::Relaxed);
                         state.idle_since_timestamp.store(
-                            now_secs.saturating_sub(state.threshold_secs),
+                            now_secs.saturating_sub(state.threshold_secs.load(Ordering::Relaxed)),
                             Ordering::Relaxed,
                         );
                     }
@@ -145,7 +264,25 @@ impl IdleDetector {
             }
         }
 
+        /// Marks the thread dead however it ends.
+        struct ExitGuard(Arc<WaylandFlags>);
+
+        impl Drop for ExitGuard {
+            fn drop(&mut self) {
+                self.0.alive.store(false, Ordering::Relaxed);
+            }
+        }
+
+        let own_generation = generation.load(Ordering::SeqCst);
+        let flags = Arc::new(WaylandFlags {
+            alive: AtomicBool::new(true),
+            ..WaylandFlags::default()
+        });
+        let thread_flags = flags.clone();
+        let wake = WakeSlot::default();
+        let thread_wake = wake.clone();
         let handle = std::thread::spawn(move || {
+            let _guard = ExitGuard(thread_flags.clone());
             let (globals, mut event_queue): (_, EventQueue<AppData>) =
                 match registry_queue_init(&conn) {
                     Ok(result) => result,
@@ -160,10 +297,18 @@ impl IdleDetector {
                 idle_since_timestamp,
                 is_idle,
                 threshold_secs,
+                generation,
+                own_generation,
             };
 
             app_data.seat = globals.bind::<wl_seat::WlSeat, _, _>(&qh, 1..=1, ()).ok();
             app_data.idle_notifier = globals.bind::<ExtIdleNotifierV1, _, _>(&qh, 1..=1, ()).ok();
+            thread_flags
+                .seat_bound
+                .store(app_data.seat.is_some(), Ordering::Relaxed);
+            thread_flags
+                .notifier_bound
+                .store(app_data.idle_notifier.is_some(), Ordering::Relaxed);
 
             if app_data.seat.is_none() || app_data.idle_notifier.is_none() {
                 return;
@@ -171,27 +316,98 @@ impl IdleDetector {
 
             let seat = app_data.seat.as_ref().unwrap();
             let idle_notifier = app_data.idle_notifier.as_ref().unwrap();
-            let timeout_ms = threshold_secs * 1000;
-            let _idle_notification =
+            let timeout_ms = app_data.threshold_secs.load(Ordering::Relaxed) * 1000;
+            let idle_notification =
                 idle_notifier.get_idle_notification(timeout_ms as u32, seat, &qh, ());
 
-            loop {
+            let wake_conn = conn.clone();
+            let wake_qh = qh.clone();
+            *thread_wake.lock().unwrap() = Some(Box::new(move || {
+                wake_conn.display().sync(&wake_qh, ());
+                let _ = wake_conn.flush();
+            }));
+            // Checked after the wake is in place: a threshold change either
+            // shows up here or finds the wake and uses it.
+            while app_data.is_current() {
                 if event_queue.blocking_dispatch(&mut app_data).is_err() {
                     break;
                 }
             }
+            idle_notification.destroy();
+            let _ = conn.flush();
         });
 
         Some(WaylandIdleHandle {
             thread_handle: handle,
+            flags,
+            wake,
         })
     }
 
+    /// Whether the Wayland thread is up and still running.
+    #[cfg(target_os = "linux")]
+    fn wayland_alive(&self) -> bool {
+        self.wayland_handle
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|handle| handle.flags.alive.load(Ordering::Relaxed))
+    }
+
+    pub fn wayland_health(&self) -> WaylandHealth {
+        #[cfg(target_os = "linux")]
+        {
+            match self.wayland_handle.lock().unwrap().as_ref() {
+                // A handle only exists once the connection was made.
+                Some(handle) => WaylandHealth {
+                    connected: true,
+                    seat_bound: handle.flags.seat_bound.load(Ordering::Relaxed),
+                    notifier_bound: handle.flags.notifier_bound.load(Ordering::Relaxed),
+                    alive: handle.flags.alive.load(Ordering::Relaxed),
+                },
+                None => WaylandHealth::default(),
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            WaylandHealth::default()
+        }
+    }
+
+    pub fn backend(&self) -> IdleBackend {
+        #[cfg(target_os = "linux")]
+        {
+            if self.wayland_alive() {
+                IdleBackend::WaylandIdleNotify
+            } else {
+                IdleBackend::X11
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            IdleBackend::Native
+        }
+    }
+
+    /// The threshold in effect for every backend.
+    pub fn threshold_secs(&self) -> u64 {
+        self.threshold_secs.load(Ordering::Relaxed)
+    }
+
+    /// Whether `idle_secs` from `get_idle_time` is past the threshold as it
+    /// stands right now.
+    pub fn is_past_threshold(&self, idle_secs: u64) -> bool {
+        past_threshold(idle_secs, self.threshold_secs())
+    }
+
     /// Get idle time in seconds
     pub fn get_idle_time(&self) -> Result<u64, IdleDetectionError> {
         #[cfg(target_os = "linux")]
         {
-            if self.wayland_handle.is_some() {
+            // A thread that died left `is_idle` wherever it was.
+            if self.wayland_alive() {
                 // Wayland idle detection is active
                 if self.is_idle.load(Ordering::Relaxed) {
                     use std::time::SystemTime;
@@ -204,13 +420,13 @@ impl IdleDetector {
 
                     let idle_secs = now.saturating_sub(idle_since);
                     // Return at least the threshold, since that's the minimum idle time
-                    return Ok(idle_secs.max(self.threshold_secs));
+                    return Ok(idle_secs.max(self.threshold_secs.load(Ordering::Relaxed)));
                 } else {
                     return Ok(0);
                 }
             }
 
-            // Fall back to X11 detection
+            // Fall back to X11 detection (or D-Bus, through user-idle2)
             return self.try_x11_idle();
         }
 
@@ -231,6 +447,12 @@ impl IdleDetector {
     }
 }
 
+/// The comparison every backend's reading is judged by. Wayland only ever
+/// reports 0 or at least the threshold, so it agrees with its own flag.
+fn past_threshold(idle_secs: u64, threshold_secs: u64) -> bool {
+    idle_secs >= threshold_secs
+}
+
 #[derive(Debug)]
 pub enum IdleDetectionError {
     X11Error,
@@ -245,3 +467,27 @@ impl std::fmt::Display for IdleDetectionError {
 }
 
 impl std::error::Error for IdleDetectionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn past_threshold_includes_the_threshold_itself() {
+        assert!(!past_threshold(119, 120));
+        assert!(past_threshold(120, 120));
+    }
+
+    #[test]
+    fn set_threshold_takes_effect_at_once() {
+        // Without a Wayland display this is the X11 path, which judges each
+        // reading against the threshold as it is at that moment.
+        let detector = IdleDetector::new(120);
+        assert!(detector.is_past_threshold(200));
+        detector.set_threshold(300);
+        assert_eq!(detector.threshold_secs(), 300);
+        assert!(!detector.is_past_threshold(200));
+        detector.set_threshold(60);
+        assert!(detector.is_past_threshold(200));
+    }
+}
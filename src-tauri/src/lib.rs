@@ -1,19 +1,58 @@
 mod app_state;
+mod bluetooth_call;
+mod calendar;
+mod config_link;
+mod display_power;
+mod do_not_disturb;
+mod env_prefs;
 mod events;
+mod exercises;
+mod focus_bar;
+mod haptics;
+mod http_trigger;
 mod idle_detection;
+mod lid_state;
+mod overlay;
+mod power_source;
+mod prefs_watch;
+mod process_watch;
+mod remote_session;
+mod screen_lock;
+mod shortcuts;
+mod sound_uri;
+mod stats;
+mod status_feed;
 mod tray;
+mod updates;
 
 use std::sync::Arc;
 
-use app_state::{AppState, Preferences, PreferencesUpdate, StatusSnapshot};
+use app_state::{
+    AppState, CampaignConfig, Capabilities, CatchUpReport, ConfigReport, DeliveryStatus,
+    EffectiveInterval, EngineDump, EnginePhase, EngineUptime, IdleProbe, Mood, NotificationAttempt,
+    NotificationCheck, OneOffReminder, PendingAction, Preferences, PreferencesUpdate, ReminderKind,
+    SelfTestReport, SnoozeBoundary, StatusSnapshot, SuppressionKind, SuppressionOverride,
+    SuppressionRecord, SuppressionStatus, TimedAction, UpcomingReminder,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use config_link::PendingConfigLink;
+use events::ConfigLinkPayload;
 use events::StatusPayload;
+use exercises::{Exercise, EXERCISES};
+use idle_detection::WaylandHealth;
+use stats::{DayStats, HourEffectiveness, IntervalSuggestion, SnoozeReason, WeeklyReport};
 use tauri::{AppHandle, Emitter, Manager, State, WindowEvent, Wry};
 use tauri_plugin_autostart::MacosLauncher;
+use tauri_plugin_deep_link::DeepLinkExt;
 #[cfg(desktop)]
 use tauri_plugin_updater::Builder as UpdaterBuilder;
+use updates::UpdateInfo;
 
 type CommandResult<T> = Result<T, String>;
 
+/// Passed by the login item so a launch can tell it came from autostart.
+const AUTOSTART_ARG: &str = "--autostart";
+
 #[tauri::command]
 async fn get_preferences(state: State<'_, Arc<AppState>>) -> CommandResult<Preferences> {
     Ok(state.preferences())
@@ -31,11 +70,416 @@ async fn update_preferences(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn reload_preferences(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Preferences> {
+    state
+        .reload_preferences(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn clear_stats(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.clear_stats().await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn next_reminder_label(state: State<'_, Arc<AppState>>) -> CommandResult<String> {
+    Ok(state.next_reminder_label())
+}
+
+#[tauri::command]
+async fn capabilities(state: State<'_, Arc<AppState>>) -> CommandResult<Capabilities> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || state.capabilities())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn engine_uptime(state: State<'_, Arc<AppState>>) -> CommandResult<EngineUptime> {
+    Ok(state.engine_uptime())
+}
+
+#[tauri::command]
+async fn idle_probe(state: State<'_, Arc<AppState>>) -> CommandResult<IdleProbe> {
+    Ok(state.idle_probe())
+}
+
+#[tauri::command]
+async fn wayland_health(state: State<'_, Arc<AppState>>) -> CommandResult<WaylandHealth> {
+    Ok(state.wayland_health())
+}
+
+#[tauri::command]
+async fn show_focus_bar(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Preferences> {
+    state
+        .set_focus_bar(&app, true)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn hide_focus_bar(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Preferences> {
+    state
+        .set_focus_bar(&app, false)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_strict_mode(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    enabled: bool,
+) -> CommandResult<Preferences> {
+    state
+        .set_strict_mode(&app, enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn last_suppression(
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Option<SuppressionRecord>> {
+    Ok(state.last_suppression())
+}
+
+#[tauri::command]
+async fn catch_up(state: State<'_, Arc<AppState>>) -> CommandResult<CatchUpReport> {
+    Ok(state.catch_up().await)
+}
+
+#[tauri::command]
+async fn current_idle_seconds(state: State<'_, Arc<AppState>>) -> CommandResult<Option<u64>> {
+    Ok(state.current_idle_seconds())
+}
+
+#[tauri::command]
+async fn weekly_report(state: State<'_, Arc<AppState>>) -> CommandResult<WeeklyReport> {
+    Ok(state.weekly_report())
+}
+
+#[tauri::command]
+async fn effectiveness_by_hour(
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<HourEffectiveness>> {
+    Ok(state.effectiveness_by_hour())
+}
+
+#[tauri::command]
+async fn suggest_interval(state: State<'_, Arc<AppState>>) -> CommandResult<IntervalSuggestion> {
+    Ok(state.suggest_interval())
+}
+
+#[tauri::command]
+async fn set_mood(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    mood: Mood,
+) -> CommandResult<Preferences> {
+    state.set_mood(&app, mood).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn start_campaign(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    config: CampaignConfig,
+) -> CommandResult<Preferences> {
+    state
+        .start_campaign(&app, config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn stop_campaign(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Preferences> {
+    state.stop_campaign(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_tray_tooltip_template(
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Option<String>> {
+    Ok(state.tray_tooltip_template())
+}
+
+#[tauri::command]
+async fn set_tray_tooltip_template(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    template: Option<String>,
+) -> CommandResult<Option<String>> {
+    state
+        .set_tray_tooltip_template(&app, template)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_breaks_per_day(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    count: u32,
+) -> CommandResult<u64> {
+    state
+        .set_breaks_per_day(&app, count)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_idle_threshold(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    minutes: u64,
+) -> CommandResult<u64> {
+    state
+        .set_idle_threshold(&app, minutes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_interval_from_string(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    input: String,
+) -> CommandResult<Preferences> {
+    state
+        .set_interval_from_string(&app, &input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn export_config_link(state: State<'_, Arc<AppState>>) -> CommandResult<String> {
+    Ok(config_link::export_link(&state.preferences()))
+}
+
+#[tauri::command]
+async fn tray_available(state: State<'_, Arc<AppState>>) -> CommandResult<bool> {
+    Ok(state.tray_available())
+}
+
+#[tauri::command]
+async fn open_settings(app: AppHandle<Wry>, section: Option<String>) -> CommandResult<()> {
+    tray::open_settings(&app, section.as_deref())
+}
+
+#[tauri::command]
+async fn export_env(state: State<'_, Arc<AppState>>) -> CommandResult<String> {
+    Ok(env_prefs::export(&state.preferences()))
+}
+
+#[tauri::command]
+async fn take_pending_config_link(
+    pending: State<'_, PendingConfigLink>,
+) -> CommandResult<Option<ConfigLinkPayload>> {
+    Ok(pending.take())
+}
+
+#[tauri::command]
+async fn apply_config_link(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    link: String,
+) -> CommandResult<Preferences> {
+    let config = config_link::parse_link(&link).ok_or("not a valid TouchGrass config link")?;
+    state
+        .update_preferences(&app, config.into_update())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn import_messages_from_file(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    path: String,
+    kind: Option<ReminderKind>,
+) -> CommandResult<usize> {
+    state
+        .import_messages_from_file(
+            &app,
+            std::path::Path::new(&path),
+            kind.unwrap_or(ReminderKind::Posture),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn get_status(state: State<'_, Arc<AppState>>) -> CommandResult<StatusSnapshot> {
     Ok(state.status())
 }
 
+#[tauri::command]
+async fn engine_phase(state: State<'_, Arc<AppState>>) -> CommandResult<EnginePhase> {
+    Ok(state.engine_phase())
+}
+
+#[tauri::command]
+async fn debug_dump_state(state: State<'_, Arc<AppState>>) -> CommandResult<EngineDump> {
+    state.debug_dump_state().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_today_stats(state: State<'_, Arc<AppState>>) -> CommandResult<DayStats> {
+    Ok(state.today_stats())
+}
+
+#[tauri::command]
+async fn idle_histogram(
+    state: State<'_, Arc<AppState>>,
+    date: String,
+) -> CommandResult<Vec<(u64, u64)>> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date {date:?}; expected YYYY-MM-DD"))?;
+    Ok(state.idle_histogram(date))
+}
+
+#[tauri::command]
+async fn suppression_status(state: State<'_, Arc<AppState>>) -> CommandResult<SuppressionStatus> {
+    Ok(state.suppression_status())
+}
+
+#[tauri::command]
+async fn evaluate_at(
+    state: State<'_, Arc<AppState>>,
+    timestamp_ms: i64,
+) -> CommandResult<SuppressionStatus> {
+    let at = DateTime::<Utc>::from_timestamp_millis(timestamp_ms)
+        .ok_or_else(|| format!("invalid timestamp: {timestamp_ms}"))?;
+    state.evaluate_at(at).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn diagnose_notifications(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<NotificationCheck>> {
+    Ok(state.diagnose_notifications(&app).await)
+}
+
+#[tauri::command]
+async fn self_test(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<SelfTestReport> {
+    Ok(state.self_test(&app))
+}
+
+#[tauri::command]
+async fn verify_config(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<ConfigReport> {
+    state.verify_config(&app).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn repair_config(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<ConfigReport> {
+    state.repair_config(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn notification_history(
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<NotificationAttempt>> {
+    Ok(state.notification_history())
+}
+
+#[tauri::command]
+async fn last_delivery_status(state: State<'_, Arc<AppState>>) -> CommandResult<DeliveryStatus> {
+    Ok(state.last_delivery_status())
+}
+
+#[tauri::command]
+async fn clear_notification_history(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.clear_notification_history();
+    Ok(())
+}
+
+#[tauri::command]
+async fn preview_overlay(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    overlay::preview(&app, &state.preferences()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn preview_notification(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<String>> {
+    state.preview_notification(&app).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn panic_hide(app: AppHandle<Wry>, state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.panic_hide(&app);
+    Ok(())
+}
+
+#[tauri::command]
+async fn check_for_updates(app: AppHandle<Wry>) -> CommandResult<UpdateInfo> {
+    updates::check(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn install_update(app: AppHandle<Wry>) -> CommandResult<bool> {
+    updates::install(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_completion_rate(state: State<'_, Arc<AppState>>) -> CommandResult<Option<f64>> {
+    Ok(state.today_completion_rate())
+}
+
+#[tauri::command]
+async fn log_manual_break(
+    state: State<'_, Arc<AppState>>,
+    duration_minutes: u64,
+    note: Option<String>,
+) -> CommandResult<()> {
+    state
+        .log_manual_break(duration_minutes, note)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mini_break(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.mini_break().await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn complete_break(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.complete_break().await;
+    Ok(())
+}
+
 #[tauri::command]
 async fn set_pause_state(state: State<'_, Arc<AppState>>, paused: bool) -> CommandResult<()> {
     state.set_pause(paused).await;
@@ -43,11 +487,137 @@ async fn set_pause_state(state: State<'_, Arc<AppState>>, paused: bool) -> Comma
 }
 
 #[tauri::command]
-async fn snooze_for_minutes(state: State<'_, Arc<AppState>>, minutes: u64) -> CommandResult<()> {
-    state.snooze(minutes).await;
+async fn pause_until(state: State<'_, Arc<AppState>>, timestamp_ms: i64) -> CommandResult<()> {
+    let until = DateTime::<Utc>::from_timestamp_millis(timestamp_ms)
+        .ok_or_else(|| format!("invalid timestamp: {timestamp_ms}"))?;
+    state.pause_until(until).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_silent_mode(state: State<'_, Arc<AppState>>, enabled: bool) -> CommandResult<()> {
+    state.set_silent_mode(enabled).await;
     Ok(())
 }
 
+#[tauri::command]
+async fn start_sprint(
+    state: State<'_, Arc<AppState>>,
+    interval_minutes: u64,
+    duration_minutes: u64,
+) -> CommandResult<()> {
+    state.start_sprint(interval_minutes, duration_minutes).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn end_sprint(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.end_sprint().await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn snooze_for_minutes(state: State<'_, Arc<AppState>>, minutes: u64) -> CommandResult<bool> {
+    Ok(state.snooze(minutes).await)
+}
+
+#[tauri::command]
+async fn snooze_with_reason(
+    state: State<'_, Arc<AppState>>,
+    minutes: u64,
+    reason: String,
+) -> CommandResult<bool> {
+    Ok(state.snooze_with_reason(minutes, &reason).await)
+}
+
+#[tauri::command]
+async fn snooze_reasons(state: State<'_, Arc<AppState>>) -> CommandResult<Vec<SnoozeReason>> {
+    Ok(state.snooze_reasons())
+}
+
+/// For the in-app snooze key (`S` by default). Safe to call on key repeat.
+#[tauri::command]
+async fn snooze_default(state: State<'_, Arc<AppState>>) -> CommandResult<Option<DateTime<Utc>>> {
+    Ok(state.snooze_default().await)
+}
+
+#[tauri::command]
+async fn snooze_escalating(state: State<'_, Arc<AppState>>) -> CommandResult<Option<u64>> {
+    Ok(state.snooze_escalating().await)
+}
+
+/// For the in-app skip key (`K` by default). Safe to call on key repeat.
+#[tauri::command]
+async fn skip_break(state: State<'_, Arc<AppState>>) -> CommandResult<bool> {
+    Ok(state.skip_break().await)
+}
+
+#[tauri::command]
+async fn skip_next(state: State<'_, Arc<AppState>>, channel: ReminderKind) -> CommandResult<()> {
+    state.skip_next(channel).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn queue_actions(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    actions: Vec<TimedAction>,
+) -> CommandResult<Vec<PendingAction>> {
+    state
+        .queue_actions(&app, actions)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn clear_action_queue(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    state.clear_action_queue(&app);
+    Ok(())
+}
+
+#[tauri::command]
+async fn snooze_until_free(
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Option<DateTime<Utc>>> {
+    state.snooze_until_free().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn effective_interval(state: State<'_, Arc<AppState>>) -> CommandResult<EffectiveInterval> {
+    Ok(state.effective_interval())
+}
+
+#[tauri::command]
+async fn snooze_to_boundary(
+    state: State<'_, Arc<AppState>>,
+    boundary: String,
+) -> CommandResult<Option<DateTime<Utc>>> {
+    let boundary = boundary
+        .parse::<SnoozeBoundary>()
+        .map_err(|e| e.to_string())?;
+    Ok(state.snooze_to_boundary(boundary).await)
+}
+
+#[tauri::command]
+async fn load_sound(
+    state: State<'_, Arc<AppState>>,
+    kind: Option<ReminderKind>,
+) -> CommandResult<String> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        state.load_sound(kind.unwrap_or(ReminderKind::Posture))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn skip_next_autostart(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.skip_next_autostart().map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn clear_snooze(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
     state.clear_snooze().await;
@@ -60,12 +630,121 @@ async fn trigger_preview(state: State<'_, Arc<AppState>>) -> CommandResult<()> {
     Ok(())
 }
 
+#[tauri::command]
+async fn trigger_if_active(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<bool> {
+    Ok(state.trigger_if_active(&app).await)
+}
+
+#[tauri::command]
+fn list_exercises() -> Vec<Exercise> {
+    EXERCISES.to_vec()
+}
+
+#[tauri::command]
+async fn trigger_exercise(
+    app: AppHandle<Wry>,
+    state: State<'_, Arc<AppState>>,
+    index: usize,
+) -> CommandResult<Exercise> {
+    state
+        .trigger_exercise(&app, index)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn override_suppression(
+    state: State<'_, Arc<AppState>>,
+    kind: SuppressionKind,
+    until: i64,
+) -> CommandResult<SuppressionOverride> {
+    let until = DateTime::<Utc>::from_timestamp_millis(until)
+        .ok_or_else(|| format!("invalid timestamp: {until}"))?;
+    state
+        .override_suppression(kind, until)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_overrides(
+    state: State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<SuppressionOverride>> {
+    Ok(state.list_overrides())
+}
+
+#[tauri::command]
+async fn clear_override(
+    state: State<'_, Arc<AppState>>,
+    kind: SuppressionKind,
+) -> CommandResult<bool> {
+    Ok(state.clear_override(kind))
+}
+
+#[tauri::command]
+async fn schedule_oneoff(
+    state: State<'_, Arc<AppState>>,
+    timestamp_ms: i64,
+    message: Option<String>,
+) -> CommandResult<OneOffReminder> {
+    let at = DateTime::<Utc>::from_timestamp_millis(timestamp_ms)
+        .ok_or_else(|| format!("invalid timestamp: {timestamp_ms}"))?;
+    state
+        .schedule_oneoff(at, message)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn upcoming(
+    state: State<'_, Arc<AppState>>,
+    count: usize,
+) -> CommandResult<Vec<UpcomingReminder>> {
+    Ok(state.upcoming(count))
+}
+
+#[tauri::command]
+async fn list_oneoffs(state: State<'_, Arc<AppState>>) -> CommandResult<Vec<OneOffReminder>> {
+    Ok(state.list_oneoffs())
+}
+
+#[tauri::command]
+async fn cancel_oneoff(state: State<'_, Arc<AppState>>, id: u64) -> CommandResult<()> {
+    state.cancel_oneoff(id).await.map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let mut builder = tauri::Builder::default();
+
+    // Must come first: a second launch (e.g. from a config link) hands its
+    // arguments to the running instance and exits, so there's only ever one
+    // engine sending reminders and writing preferences.
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            if args.iter().any(|arg| arg == AUTOSTART_ARG) {
+                // Login raced a manual launch; stay in the background.
+                let _ = app.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "info".into(),
+                        message: "login launch ignored; TouchGrass is already running".into(),
+                    },
+                );
+                return;
+            }
+            tray::focus_main_window(app);
+        }));
+    }
+
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
-            Some(vec!["--autostart"]),
+            Some(vec![AUTOSTART_ARG]),
         ))
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_opener::init())
@@ -74,33 +753,155 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             get_preferences,
             update_preferences,
+            reload_preferences,
+            set_mood,
+            suggest_interval,
+            current_idle_seconds,
+            idle_probe,
+            capabilities,
+            next_reminder_label,
+            clear_stats,
+            override_suppression,
+            list_overrides,
+            clear_override,
+            snooze_with_reason,
+            snooze_reasons,
+            engine_uptime,
+            trigger_if_active,
+            export_env,
+            set_breaks_per_day,
+            tray_available,
+            evaluate_at,
+            open_settings,
+            skip_next,
+            queue_actions,
+            clear_action_queue,
+            snooze_until_free,
+            load_sound,
+            skip_next_autostart,
+            set_idle_threshold,
+            preview_notification,
+            log_manual_break,
+            weekly_report,
+            start_campaign,
+            stop_campaign,
+            get_tray_tooltip_template,
+            set_tray_tooltip_template,
+            self_test,
+            pause_until,
+            last_delivery_status,
+            verify_config,
+            repair_config,
+            wayland_health,
+            effectiveness_by_hour,
+            show_focus_bar,
+            hide_focus_bar,
+            set_strict_mode,
+            last_suppression,
+            catch_up,
+            snooze_to_boundary,
+            effective_interval,
+            set_interval_from_string,
+            import_messages_from_file,
+            export_config_link,
+            take_pending_config_link,
+            apply_config_link,
             get_status,
+            engine_phase,
+            debug_dump_state,
+            get_today_stats,
+            get_completion_rate,
+            idle_histogram,
+            suppression_status,
+            diagnose_notifications,
+            notification_history,
+            clear_notification_history,
+            preview_overlay,
+            panic_hide,
+            check_for_updates,
+            install_update,
             set_pause_state,
+            set_silent_mode,
+            start_sprint,
+            end_sprint,
             snooze_for_minutes,
+            snooze_default,
+            snooze_escalating,
+            skip_break,
             clear_snooze,
-            trigger_preview
+            mini_break,
+            complete_break,
+            trigger_preview,
+            list_exercises,
+            trigger_exercise,
+            schedule_oneoff,
+            list_oneoffs,
+            upcoming,
+            cancel_oneoff
         ])
         .setup(|app| {
+            // Check if launched with --autostart flag (from login)
+            let args: Vec<String> = std::env::args().collect();
+            let is_autostart = args.iter().any(|arg| arg == AUTOSTART_ARG);
+            if is_autostart && app_state::take_skip_autostart_marker(app.handle()) {
+                eprintln!("TouchGrass: skipping this login launch as requested.");
+                std::process::exit(0);
+            }
+
             #[cfg(desktop)]
             {
                 app.handle()
                     .plugin(UpdaterBuilder::new().build())
                     .map_err(|e| boxed(e))?;
+                app.handle()
+                    .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+                    .map_err(|e| boxed(e))?;
             }
 
             let app_handle = app.handle();
-            let state = AppState::initialize(&app_handle).map_err(|e| boxed(e))?;
+            let state = AppState::initialize(&app_handle, is_autostart).map_err(|e| boxed(e))?;
             let tray_state = state.clone();
 
             app.manage(state.clone());
 
-            tray::setup_tray(&app_handle, tray_state).map_err(|e| boxed(e))?;
+            shortcuts::apply_panic_hotkey(app_handle, state.preferences().panic_hotkey.as_deref());
 
-            // Check if launched with --autostart flag (from login)
-            let args: Vec<String> = std::env::args().collect();
-            let is_autostart = args.iter().any(|arg| arg == "--autostart");
+            if let Err(err) = tray::setup_tray(&app_handle, tray_state) {
+                // Some desktops (GNOME without an extension) have no tray.
+                // Keep going with the window as the only way in.
+                state.mark_tray_unavailable();
+                let message = format!(
+                    "system tray unavailable ({err}); the window will stay open instead of hiding to the tray"
+                );
+                eprintln!("TouchGrass: {message}.");
+                let _ = app.emit(
+                    events::LOG_EVENT,
+                    events::LogPayload {
+                        level: "warning".into(),
+                        message,
+                    },
+                );
+            }
 
-            if is_autostart {
+            if state.preferences().focus_bar_enabled {
+                state.sync_focus_bar(app_handle, true);
+            }
+
+            app.manage(PendingConfigLink::default());
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(err) = app.deep_link().register_all() {
+                eprintln!("TouchGrass: failed to register touchgrass:// links ({err}).");
+            }
+            let link_handle = app_handle.clone();
+            app.deep_link().on_open_url(move |event| {
+                config_link::handle_urls(&link_handle, event.urls());
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                config_link::handle_urls(app_handle, urls);
+            }
+
+            if is_autostart && !state.preferences().autostart_show_window && state.tray_available()
+            {
                 // Hide window on autostart - run in tray only
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.hide();
@@ -123,19 +924,34 @@ pub fn run() {
             }
         })
         .on_window_event(|window, event| {
+            if window.label() == focus_bar::FOCUS_BAR_LABEL {
+                if let WindowEvent::Moved(position) = event {
+                    focus_bar::moved(window.app_handle(), *position);
+                }
+                return;
+            }
             if window.label() != "main" {
                 return;
             }
 
             match event {
                 WindowEvent::CloseRequested { api, .. } => {
-                    // Prevent the window from closing, hide it instead
-                    api.prevent_close();
-                    let _ = window.hide();
+                    // Prevent the window from closing, hide it instead,
+                    // unless there's no tray to get it back from.
+                    let has_tray = window
+                        .try_state::<Arc<AppState>>()
+                        .is_none_or(|state| state.tray_available());
+                    if has_tray {
+                        api.prevent_close();
+                        let _ = window.hide();
+                    }
                 }
                 WindowEvent::Resized(_) => {
                     // Also handle minimize button (fallback for platforms that emit this)
-                    if let Ok(true) = window.is_minimized() {
+                    let to_tray = window
+                        .try_state::<Arc<AppState>>()
+                        .is_none_or(|state| state.minimize_to_tray());
+                    if to_tray && window.is_minimized().unwrap_or(false) {
                         let _ = window.hide();
                     }
                 }
@@ -0,0 +1,201 @@
+/// Whether the laptop lid is shut, or `None` when it can't be told (no lid,
+/// or no way to ask on this platform). Blocks briefly, so call it off the
+/// async runtime.
+pub fn is_lid_closed() -> Option<bool> {
+    platform::is_lid_closed()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+
+    use zbus::blocking::{Connection, Proxy};
+
+    /// Asks logind for `LidClosed`, then falls back to the ACPI button state
+    /// for systems without it.
+    pub fn is_lid_closed() -> Option<bool> {
+        logind_lid_closed().or_else(acpi_lid_closed)
+    }
+
+    fn logind_lid_closed() -> Option<bool> {
+        let connection = Connection::system().ok()?;
+        let manager = Proxy::new(
+            &connection,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+        )
+        .ok()?;
+        // logind reports `false` on machines without a lid switch too.
+        if !manager
+            .get_property::<bool>("LidSwitchPresent")
+            .unwrap_or(true)
+        {
+            return None;
+        }
+        manager.get_property::<bool>("LidClosed").ok()
+    }
+
+    /// `/proc/acpi/button/lid/LID0/state` reads like `state:      closed`.
+    fn acpi_lid_closed() -> Option<bool> {
+        let lid = fs::read_dir("/proc/acpi/button/lid")
+            .ok()?
+            .flatten()
+            .next()?;
+        let state = fs::read_to_string(lid.path().join("state")).ok()?;
+        match state.split_whitespace().last()? {
+            "closed" => Some(true),
+            "open" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{
+        ffi::c_void,
+        sync::{
+            atomic::{AtomicU8, Ordering},
+            Once,
+        },
+    };
+
+    use windows_sys::Win32::{
+        System::{
+            Power::{
+                PowerSettingRegisterNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS,
+                POWERBROADCAST_SETTING,
+            },
+            SystemServices::GUID_LIDSWITCH_STATE_CHANGE,
+        },
+        UI::WindowsAndMessaging::{DEVICE_NOTIFY_CALLBACK, PBT_POWERSETTINGCHANGE},
+    };
+
+    const UNKNOWN: u8 = 0;
+    const OPEN: u8 = 1;
+    const CLOSED: u8 = 2;
+
+    /// Last state Windows reported; it sends the current one right after
+    /// registering and then every change.
+    static LID: AtomicU8 = AtomicU8::new(UNKNOWN);
+    static REGISTER: Once = Once::new();
+
+    /// Windows has no call to read the lid switch, only the
+    /// `GUID_LIDSWITCH_STATE_CHANGE` notification, so the first call
+    /// subscribes to it and later ones read what it last said.
+    pub fn is_lid_closed() -> Option<bool> {
+        REGISTER.call_once(register);
+        match LID.load(Ordering::Relaxed) {
+            OPEN => Some(false),
+            CLOSED => Some(true),
+            _ => None,
+        }
+    }
+
+    fn register() {
+        // Windows keeps a pointer to the parameters for as long as the
+        // subscription lives, which is the rest of the process.
+        let parameters = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+            Callback: Some(on_power_setting),
+            Context: std::ptr::null_mut(),
+        }));
+        let mut handle = std::ptr::null_mut();
+        // SAFETY: the GUID and parameters outlive the subscription, and the
+        // handle is only written to.
+        let result = unsafe {
+            PowerSettingRegisterNotification(
+                &GUID_LIDSWITCH_STATE_CHANGE,
+                DEVICE_NOTIFY_CALLBACK,
+                parameters as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as *mut c_void,
+                &mut handle,
+            )
+        };
+        if result != 0 {
+            eprintln!("TouchGrass: couldn't watch the lid switch (error {result}).");
+        }
+    }
+
+    unsafe extern "system" fn on_power_setting(
+        _context: *const c_void,
+        kind: u32,
+        setting: *const c_void,
+    ) -> u32 {
+        if kind != PBT_POWERSETTINGCHANGE || setting.is_null() {
+            return 0;
+        }
+        // SAFETY: for PBT_POWERSETTINGCHANGE, `setting` points to a
+        // POWERBROADCAST_SETTING whose data is a DWORD for this GUID.
+        unsafe {
+            let setting = &*(setting as *const POWERBROADCAST_SETTING);
+            if setting.DataLength as usize >= std::mem::size_of::<u32>() {
+                let open = std::ptr::read_unaligned(setting.Data.as_ptr() as *const u32) != 0;
+                LID.store(if open { OPEN } else { CLOSED }, Ordering::Relaxed);
+            }
+        }
+        0
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::{c_char, c_void};
+
+    use core_foundation::{
+        base::{CFAllocatorRef, CFType, CFTypeRef, TCFType},
+        boolean::CFBoolean,
+        string::{CFString, CFStringRef},
+    };
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+        fn IORegistryEntryCreateCFProperty(
+            entry: u32,
+            key: CFStringRef,
+            allocator: CFAllocatorRef,
+            options: u32,
+        ) -> CFTypeRef;
+        fn IOObjectRelease(object: u32) -> i32;
+    }
+
+    /// Reads `AppleClamshellState` off the power management root domain.
+    /// Macs without a lid don't have the property.
+    pub fn is_lid_closed() -> Option<bool> {
+        let key = CFString::from_static_string("AppleClamshellState");
+        // SAFETY: the matching dictionary is consumed by the lookup, the
+        // service is released here, and the property follows the Create rule.
+        unsafe {
+            let matching = IOServiceMatching(c"IOPMrootDomain".as_ptr());
+            if matching.is_null() {
+                return None;
+            }
+            // 0 is the default main port.
+            let root_domain = IOServiceGetMatchingService(0, matching);
+            if root_domain == 0 {
+                return None;
+            }
+            let value = IORegistryEntryCreateCFProperty(
+                root_domain,
+                key.as_concrete_TypeRef(),
+                std::ptr::null(),
+                0,
+            );
+            IOObjectRelease(root_domain);
+            if value.is_null() {
+                return None;
+            }
+            CFType::wrap_under_create_rule(value)
+                .downcast::<CFBoolean>()
+                .map(bool::from)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub fn is_lid_closed() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Wry};
+
+use crate::app_state::Preferences;
+
+pub const OVERLAY_LABEL: &str = "overlay";
+const PREVIEW_SECS: u64 = 5;
+/// How long the overlay covers the screen for a real break.
+const BREAK_SECS: u64 = 20;
+/// The attention-mode flash that goes along with a notification.
+const FLASH_SECS: u64 = 3;
+
+/// Bumped every time the overlay opens, so a close timer left over from an
+/// earlier one doesn't cut the current one short.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// How the overlay looks, handed to the page before it loads as
+/// `window.__TOUCHGRASS_OVERLAY__`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OverlayStyle<'a> {
+    opacity: f32,
+    color: &'a str,
+    show_countdown: bool,
+    seconds: u64,
+    /// The reminder text; the page has its own line without one.
+    message: Option<&'a str>,
+}
+
+/// Covers the screen with the overlay for a few seconds so its look can be
+/// tuned without waiting for a break.
+pub fn preview(app: &AppHandle<Wry>, prefs: &Preferences) -> tauri::Result<()> {
+    show(app, prefs, PREVIEW_SECS, None)
+}
+
+/// Covers the screen for a break with `message`, in place of the
+/// notification.
+pub fn show_break(app: &AppHandle<Wry>, prefs: &Preferences, message: &str) -> tauri::Result<()> {
+    show(app, prefs, BREAK_SECS, Some(message))
+}
+
+/// A brief overlay alongside the notification, for attention mode.
+pub fn flash(app: &AppHandle<Wry>, prefs: &Preferences) -> tauri::Result<()> {
+    show(app, prefs, FLASH_SECS, None)
+}
+
+fn show(
+    app: &AppHandle<Wry>,
+    prefs: &Preferences,
+    seconds: u64,
+    message: Option<&str>,
+) -> tauri::Result<()> {
+    if let Some(existing) = app.get_webview_window(OVERLAY_LABEL) {
+        existing.destroy()?;
+    }
+
+    let style = OverlayStyle {
+        opacity: prefs.overlay_opacity,
+        color: &prefs.overlay_color,
+        show_countdown: prefs.overlay_show_countdown,
+        seconds,
+        message,
+    };
+    let script = format!(
+        "window.__TOUCHGRASS_OVERLAY__ = {};",
+        serde_json::to_string(&style).unwrap_or_else(|_| "{}".into())
+    );
+
+    WebviewWindowBuilder::new(app, OVERLAY_LABEL, WebviewUrl::App("overlay".into()))
+        .title("TouchGrass break")
+        .initialization_script(&script)
+        .fullscreen(true)
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .build()?;
+
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    let app = app.clone();
+    async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(seconds)).await;
+        if GENERATION.load(Ordering::Relaxed) != generation {
+            return;
+        }
+        if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+            let _ = window.close();
+        }
+    });
+    Ok(())
+}
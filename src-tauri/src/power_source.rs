@@ -0,0 +1,91 @@
+/// Whether the machine is running on battery, or `None` when the platform
+/// doesn't say (desktops without a battery report `Some(false)` where they can).
+pub fn is_on_battery() -> Option<bool> {
+    platform::is_on_battery()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+
+    /// Reads `/sys/class/power_supply`: on battery when there is a battery and
+    /// no mains or USB supply is online.
+    pub fn is_on_battery() -> Option<bool> {
+        let mut has_battery = false;
+        for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let path = entry.path();
+            let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+            match kind.trim() {
+                "Battery" => has_battery = true,
+                "Mains" | "USB" => {
+                    let online = fs::read_to_string(path.join("online")).unwrap_or_default();
+                    if online.trim() == "1" {
+                        return Some(false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(has_battery)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    pub fn is_on_battery() -> Option<bool> {
+        // SAFETY: the struct is plain data that the call fills in.
+        let status = unsafe {
+            let mut status: SYSTEM_POWER_STATUS = std::mem::zeroed();
+            if GetSystemPowerStatus(&mut status) == 0 {
+                return None;
+            }
+            status
+        };
+        // 0 is offline, 1 online, 255 unknown.
+        match status.ACLineStatus {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::{
+        base::{CFRelease, CFTypeRef, TCFType},
+        string::{CFString, CFStringRef},
+    };
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+        fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+    }
+
+    pub fn is_on_battery() -> Option<bool> {
+        // SAFETY: the snapshot follows the Create rule and is released here;
+        // the source type follows the Get rule and is copied before that.
+        let source = unsafe {
+            let snapshot = IOPSCopyPowerSourcesInfo();
+            if snapshot.is_null() {
+                return None;
+            }
+            let source = IOPSGetProvidingPowerSourceType(snapshot);
+            let source =
+                (!source.is_null()).then(|| CFString::wrap_under_get_rule(source).to_string());
+            CFRelease(snapshot);
+            source
+        }?;
+        Some(source == "Battery Power")
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub fn is_on_battery() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    path::Path,
+    sync::{mpsc, Arc},
+    time::Duration,
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{async_runtime, AppHandle, Emitter, Manager, Wry};
+
+use crate::{app_state::AppState, events};
+
+/// How long the file has to stay quiet before an edit is picked up, so
+/// editors that write in several steps trigger a single reload.
+const DEBOUNCE_MS: u64 = 500;
+
+/// Reloads preferences whenever `path` is edited outside the app. Watches
+/// the parent directory, since many editors save by replacing the file.
+/// Keep the returned watcher alive for as long as edits should be noticed.
+pub fn watch(app: &AppHandle<Wry>, path: &Path) -> Option<RecommendedWatcher> {
+    let dir = path.parent()?;
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            eprintln!("TouchGrass: can't watch preferences for edits ({err}).");
+            return None;
+        }
+    };
+    if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        eprintln!(
+            "TouchGrass: can't watch {} for edits ({err}).",
+            dir.display()
+        );
+        return None;
+    }
+
+    let app = app.clone();
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        while let Ok(event) = rx.recv() {
+            if !touches(&event, &path) {
+                continue;
+            }
+            // Wait out the rest of the burst.
+            while rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)).is_ok() {}
+
+            let app = app.clone();
+            async_runtime::spawn(async move {
+                let Some(state) = app.try_state::<Arc<AppState>>() else {
+                    return;
+                };
+                if let Err(err) = state.reload_preferences(&app).await {
+                    let _ = app.emit(
+                        events::LOG_EVENT,
+                        events::LogPayload {
+                            level: "warning".into(),
+                            message: format!("ignored an edit to preferences.json: {err}"),
+                        },
+                    );
+                }
+            });
+        }
+    });
+
+    Some(watcher)
+}
+
+fn touches(event: &notify::Result<notify::Event>, path: &Path) -> bool {
+    let Ok(event) = event else {
+        return false;
+    };
+    (event.kind.is_modify() || event.kind.is_create()) && event.paths.iter().any(|p| p == path)
+}
@@ -0,0 +1,53 @@
+use std::sync::Mutex;
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// Looks for running apps that should hold reminders back. Keeps one
+/// `System` around so each scan only refreshes what changed.
+pub struct ProcessWatcher {
+    system: Mutex<System>,
+}
+
+impl ProcessWatcher {
+    pub fn new() -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+        }
+    }
+
+    /// The first of `names` with a running process, matched case-insensitively
+    /// on the executable name (with or without `.exe`). Blocks while the
+    /// process list is refreshed, so call it off the async runtime.
+    pub fn find_running(&self, names: &[String]) -> Option<String> {
+        let wanted: Vec<(String, &String)> = names
+            .iter()
+            .map(|name| (normalize(name), name))
+            .filter(|(normalized, _)| !normalized.is_empty())
+            .collect();
+        if wanted.is_empty() {
+            return None;
+        }
+
+        let mut system = self.system.lock().unwrap();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        system.processes().values().find_map(|process| {
+            let name = normalize(&process.name().to_string_lossy());
+            wanted
+                .iter()
+                .find(|(normalized, _)| *normalized == name)
+                .map(|(_, original)| (*original).clone())
+        })
+    }
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => name,
+    }
+}
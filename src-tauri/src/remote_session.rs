@@ -0,0 +1,45 @@
+/// Whether TouchGrass is running inside a remote session (RDP, SSH with X
+/// forwarding, xrdp), where the local idle time says nothing about whether
+/// the user is at the keyboard. `None` when it can't be told here.
+pub fn is_remote() -> Option<bool> {
+    platform::is_remote()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::env;
+
+    /// Going by the environment the session handed us: SSH sets
+    /// `SSH_CONNECTION`, xrdp sets `XRDP_SESSION`, and a forwarded X display
+    /// names a host (`localhost:10.0`) where a local one is just `:0`.
+    pub fn is_remote() -> Option<bool> {
+        let ssh = ["SSH_CONNECTION", "SSH_CLIENT", "XRDP_SESSION"]
+            .iter()
+            .any(|var| env::var_os(var).is_some_and(|value| !value.is_empty()));
+        let forwarded_display = env::var("DISPLAY").is_ok_and(|display| {
+            display
+                .split_once(':')
+                .is_some_and(|(host, _)| !host.is_empty() && !host.starts_with('/'))
+        });
+        Some(ssh || forwarded_display)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_REMOTESESSION};
+
+    /// Can change while running: reconnecting over Remote Desktop moves the
+    /// console session into RDP and back.
+    pub fn is_remote() -> Option<bool> {
+        // SAFETY: takes no pointers.
+        Some(unsafe { GetSystemMetrics(SM_REMOTESESSION) } != 0)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    pub fn is_remote() -> Option<bool> {
+        None
+    }
+}
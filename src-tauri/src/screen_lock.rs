@@ -0,0 +1,133 @@
+/// Whether the session's screen is locked, or `None` when the platform (or
+/// this particular desktop) doesn't tell us.
+pub fn is_screen_locked() -> Option<bool> {
+    platform::is_screen_locked()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::sync::OnceLock;
+
+    use zbus::blocking::{Connection, Proxy};
+
+    /// Reads logind's `LockedHint`, which lock screens set for the session.
+    pub fn is_screen_locked() -> Option<bool> {
+        static SYSTEM_BUS: OnceLock<Option<Connection>> = OnceLock::new();
+        let connection = SYSTEM_BUS
+            .get_or_init(|| Connection::system().ok())
+            .as_ref()?;
+
+        let session = Proxy::new(
+            connection,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1/session/auto",
+            "org.freedesktop.login1.Session",
+        )
+        .ok()?;
+        session.get_property::<bool>("LockedHint").ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::sync::OnceLock;
+
+    use windows_sys::Win32::System::{
+        RemoteDesktop::{
+            WTSFreeMemory, WTSQuerySessionInformationW, WTSSessionInfoEx, WTSINFOEXW,
+            WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, WTS_SESSIONSTATE_LOCK,
+            WTS_SESSIONSTATE_UNLOCK,
+        },
+        SystemInformation::{GetVersionExW, OSVERSIONINFOW},
+    };
+
+    /// Windows 7 (6.1) reports `SessionFlags` the wrong way round. Without
+    /// a compatibility manifest later versions all say 6.2, so 6.1 really
+    /// is Windows 7.
+    fn flags_inverted() -> bool {
+        static INVERTED: OnceLock<bool> = OnceLock::new();
+        *INVERTED.get_or_init(|| {
+            let mut info = OSVERSIONINFOW {
+                dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32,
+                ..Default::default()
+            };
+            // SAFETY: `info` is a correctly sized OSVERSIONINFOW.
+            let ok = unsafe { GetVersionExW(&mut info) } != 0;
+            ok && (info.dwMajorVersion, info.dwMinorVersion) == (6, 1)
+        })
+    }
+
+    pub fn is_screen_locked() -> Option<bool> {
+        let mut buffer = std::ptr::null_mut();
+        let mut bytes = 0u32;
+        // SAFETY: on success the API hands back a WTSINFOEXW it allocated,
+        // which is read once and then released with WTSFreeMemory.
+        unsafe {
+            let ok = WTSQuerySessionInformationW(
+                WTS_CURRENT_SERVER_HANDLE,
+                WTS_CURRENT_SESSION,
+                WTSSessionInfoEx,
+                &mut buffer,
+                &mut bytes,
+            );
+            if ok == 0 || buffer.is_null() {
+                return None;
+            }
+
+            let info = &*(buffer as *const WTSINFOEXW);
+            let locked = if info.Level == 1 {
+                match info.Data.WTSInfoExLevel1.SessionFlags as u32 {
+                    WTS_SESSIONSTATE_LOCK => Some(!flags_inverted()),
+                    WTS_SESSIONSTATE_UNLOCK => Some(flags_inverted()),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            WTSFreeMemory(buffer.cast());
+            locked
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::{
+        base::{CFType, TCFType},
+        boolean::CFBoolean,
+        dictionary::{CFDictionary, CFDictionaryRef},
+        string::CFString,
+    };
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+    }
+
+    pub fn is_screen_locked() -> Option<bool> {
+        // SAFETY: the function follows the Create rule, so the dictionary is ours
+        // to release, which the wrapper does on drop.
+        let session: CFDictionary<CFString, CFType> = unsafe {
+            let dictionary = CGSessionCopyCurrentDictionary();
+            if dictionary.is_null() {
+                return None;
+            }
+            CFDictionary::wrap_under_create_rule(dictionary)
+        };
+
+        // The key is only present while locked.
+        let locked = session
+            .find(CFString::from_static_string("CGSSessionScreenIsLocked"))
+            .and_then(|value| value.downcast::<CFBoolean>())
+            .map(bool::from)
+            .unwrap_or(false);
+        Some(locked)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub fn is_screen_locked() -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::{app_state::AppState, events};
+
+/// Registers the panic-hide hotkey, replacing any previous one. `None` leaves
+/// no global shortcut registered.
+pub fn apply_panic_hotkey(app: &AppHandle<Wry>, hotkey: Option<&str>) {
+    let shortcuts = app.global_shortcut();
+    let _ = shortcuts.unregister_all();
+
+    let Some(hotkey) = hotkey else {
+        return;
+    };
+
+    let result = shortcuts.on_shortcut(hotkey, |app, _shortcut, event| {
+        if event.state() != ShortcutState::Pressed {
+            return;
+        }
+        if let Some(state) = app.try_state::<Arc<AppState>>() {
+            state.panic_hide(app);
+        }
+    });
+
+    if let Err(err) = result {
+        let _ = app.emit(
+            events::LOG_EVENT,
+            events::LogPayload {
+                level: "error".into(),
+                message: format!("panic hotkey {hotkey} could not be registered: {err}"),
+            },
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::app_state::AppStateError;
+
+/// Sound files bigger than this aren't handed to the UI.
+const MAX_SOUND_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Custom sounds as `data:` URIs for the UI's `<audio>` element, which
+/// can't read arbitrary paths itself. Keeps the last one encoded so each
+/// reminder doesn't read the file again.
+pub struct SoundCache {
+    last: Mutex<Option<(PathBuf, SystemTime, String)>>,
+}
+
+impl SoundCache {
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+        }
+    }
+
+    /// `path` as a `data:audio/...;base64,...` URI. Re-reads the file only
+    /// when it changed since the last call.
+    pub fn load(&self, path: &str) -> Result<String, AppStateError> {
+        let file = Path::new(path);
+        let mime = mime_type(file).ok_or_else(|| AppStateError::UnsupportedSound(path.into()))?;
+        let metadata = fs::metadata(file)
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .ok_or_else(|| AppStateError::SoundNotFound(path.into()))?;
+        if metadata.len() > MAX_SOUND_BYTES {
+            return Err(AppStateError::SoundTooLarge(path.into()));
+        }
+        let modified = metadata.modified()?;
+
+        let mut last = self.last.lock().unwrap();
+        if let Some((cached_path, cached_at, uri)) = last.as_ref() {
+            if cached_path == file && *cached_at == modified {
+                return Ok(uri.clone());
+            }
+        }
+        let uri = format!("data:{mime};base64,{}", STANDARD.encode(fs::read(file)?));
+        *last = Some((file.to_path_buf(), modified, uri.clone()));
+        Ok(uri)
+    }
+}
+
+fn mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "ogg" | "oga" => "audio/ogg",
+        "flac" => "audio/flac",
+        "m4a" | "aac" => "audio/mp4",
+        "webm" => "audio/webm",
+        _ => return None,
+    })
+}
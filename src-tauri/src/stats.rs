@@ -0,0 +1,762 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Days, Local, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{app_state::DeskPosture, focus_bar::FocusBarPosition};
+
+pub const STATS_FILE: &str = "stats.json";
+/// Lower edges, in seconds, of the buckets `idle_histogram` sorts idle
+/// episodes into. The last bucket is open-ended.
+const IDLE_HISTOGRAM_BUCKETS_SECS: &[u64] = &[0, 2 * 60, 5 * 60, 15 * 60];
+/// Snooze reasons kept; the oldest are dropped first.
+const MAX_SNOOZE_REASONS: usize = 200;
+/// Days of history `suggest_interval` is given, counting today.
+pub const SUGGESTION_WINDOW_DAYS: u64 = 7;
+/// Reminders needed in the window before suggesting anything.
+const MIN_SUGGESTION_REMINDERS: u32 = 10;
+/// Share of reminders skipped or ignored that calls for a longer interval.
+const HIGH_SKIP_RATE: f64 = 0.4;
+/// Share of reminders answered with a break that counts as near-perfect.
+const HIGH_COMPLETION_RATE: f64 = 0.9;
+/// Same bounds `update_preferences` applies to `interval_minutes`.
+const SUGGESTED_INTERVAL_RANGE_MINUTES: (u64, u64) = (2, 240);
+/// Days in each half of `weekly_report`.
+pub const REPORT_WEEK_DAYS: u64 = 7;
+/// Change in completion rate between weeks that counts as a trend rather
+/// than noise.
+const TREND_THRESHOLD: f64 = 0.05;
+/// Reminders an hour needs before `effectiveness_by_hour` trusts its rate.
+const MIN_HOUR_REMINDERS: u32 = 5;
+
+/// Counters for a single local calendar day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DayStats {
+    pub reminders_shown: u32,
+    pub breaks_taken: u32,
+    pub skipped: u32,
+    pub snoozed: u32,
+    /// Short acknowledgements that keep the streak going without a full break.
+    pub mini_breaks: u32,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Reminders superseded by the next one without any reaction.
+    pub ignored: u32,
+    pub responses: u32,
+    pub response_total_secs: u64,
+    /// Mean time from a reminder to the first reaction to it.
+    pub avg_response_secs: Option<u64>,
+    /// Length of each stretch the user was away, in seconds.
+    pub idle_episodes: Vec<u64>,
+    /// Breaks the user logged themselves; also counted in `breaks_taken`.
+    pub manual_breaks: Vec<ManualBreak>,
+    /// Offered long breaks the user accepted; also counted in `breaks_taken`.
+    pub long_breaks: u32,
+    /// Reminders and breaks by local hour of the day (0-23). Only hours
+    /// with something in them are stored.
+    pub hours: BTreeMap<u8, HourCounts>,
+}
+
+/// What happened during one local hour of a day.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HourCounts {
+    pub reminders_shown: u32,
+    pub breaks_taken: u32,
+    pub mini_breaks: u32,
+}
+
+/// How well reminders in one hour of the day tend to land, over every day
+/// with stats.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourEffectiveness {
+    /// Local hour, 0-23.
+    pub hour: u8,
+    pub reminders_shown: u32,
+    pub breaks_taken: u32,
+    pub mini_breaks: u32,
+    /// As in `DayStats::completion_rate`; `None` before the first reminder.
+    pub completion_rate: Option<f64>,
+    /// Whether enough reminders fell in this hour for the rate to mean much.
+    pub enough_data: bool,
+}
+
+impl DayStats {
+    /// Share of reminders answered with a break, with each mini break counting
+    /// as `mini_break_weight` of one. `None` before the first reminder.
+    pub fn completion_rate(&self, mini_break_weight: f64) -> Option<f64> {
+        if self.reminders_shown == 0 {
+            return None;
+        }
+        let completed = self.breaks_taken as f64 + self.mini_breaks as f64 * mini_break_weight;
+        Some((completed / self.reminders_shown as f64).min(1.0))
+    }
+
+    fn this_hour(&mut self) -> &mut HourCounts {
+        self.hour(Local::now().hour() as u8)
+    }
+
+    fn hour(&mut self, hour: u8) -> &mut HourCounts {
+        self.hours.entry(hour).or_default()
+    }
+}
+
+/// One day of a `WeeklyReport`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportDay {
+    pub date: NaiveDate,
+    pub reminders_shown: u32,
+    pub breaks_taken: u32,
+    pub completion_rate: Option<f64>,
+}
+
+/// How the week's completion rate compares with the week before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Trend {
+    Up,
+    Flat,
+    Down,
+}
+
+/// The last seven days at a glance. Only days with stats are listed, so a
+/// new install reports however many days it has.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyReport {
+    /// Oldest first.
+    pub days: Vec<ReportDay>,
+    pub total_breaks: u32,
+    pub completion_rate: Option<f64>,
+    pub previous_completion_rate: Option<f64>,
+    pub best_day: Option<NaiveDate>,
+    pub worst_day: Option<NaiveDate>,
+    /// `None` unless both weeks have a completion rate.
+    pub trend: Option<Trend>,
+}
+
+/// A proposed `interval_minutes`, with the reason in a sentence.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalSuggestion {
+    pub current_minutes: u64,
+    pub suggested_minutes: u64,
+    pub rationale: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StatsEvent {
+    ReminderShown,
+    /// The user stepped away long enough to count as idle and came back.
+    BreakTaken,
+    Skipped,
+    Snoozed,
+    MiniBreak,
+    /// Accepted the longer break offered after repeated snoozes.
+    LongBreak,
+    /// Seconds from a reminder to the user's first reaction.
+    Responded(u64),
+    Ignored,
+    /// The user came back after being away this many seconds.
+    IdleEpisode(u64),
+}
+
+/// A break taken without a reminder and logged afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualBreak {
+    pub at: DateTime<Utc>,
+    pub minutes: u64,
+    pub note: Option<String>,
+}
+
+/// Why the user snoozed, as they put it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnoozeReason {
+    pub at: DateTime<Utc>,
+    pub minutes: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct StatsFile {
+    days: BTreeMap<NaiveDate, DayStats>,
+    last_summary_date: Option<NaiveDate>,
+    /// Oldest first.
+    snooze_reasons: Vec<SnoozeReason>,
+    /// Next posture for `desk_alternation`, so it survives restarts.
+    desk_posture: Option<DeskPosture>,
+    /// Where the focus bar was last dragged to.
+    focus_bar_position: Option<FocusBarPosition>,
+}
+
+/// Daily break statistics persisted next to `preferences.json`.
+pub struct Stats {
+    /// `None` keeps stats in memory only.
+    path: Option<PathBuf>,
+    file: Mutex<StatsFile>,
+}
+
+impl Stats {
+    pub fn load(path: PathBuf) -> Self {
+        let file = read_stats(&path).unwrap_or_default();
+        Self {
+            path: Some(path),
+            file: Mutex::new(file),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            file: Mutex::new(StatsFile::default()),
+        }
+    }
+
+    pub fn record(&self, event: StatsEvent) {
+        self.record_answer(event, None);
+    }
+
+    /// `record`, with a break filed under `reminder_hour`, the local hour of
+    /// the reminder it answers, instead of the hour it happened in. A 9:58
+    /// reminder taken at 10:05 is a 9:00 success, not a 9:00 miss and a
+    /// 10:00 break with no reminder.
+    pub fn record_answer(&self, event: StatsEvent, reminder_hour: Option<u8>) {
+        let mut file = self.file.lock().unwrap();
+        let day = file.days.entry(today()).or_default();
+        let break_hour = reminder_hour.unwrap_or_else(|| Local::now().hour() as u8);
+
+        match event {
+            StatsEvent::ReminderShown => {
+                day.reminders_shown += 1;
+                day.this_hour().reminders_shown += 1;
+            }
+            StatsEvent::BreakTaken => {
+                day.breaks_taken += 1;
+                day.hour(break_hour).breaks_taken += 1;
+                day.current_streak += 1;
+                day.longest_streak = day.longest_streak.max(day.current_streak);
+            }
+            StatsEvent::Skipped => {
+                day.skipped += 1;
+                day.current_streak = 0;
+            }
+            StatsEvent::Snoozed => day.snoozed += 1,
+            StatsEvent::Responded(secs) => {
+                day.responses += 1;
+                day.response_total_secs += secs;
+                day.avg_response_secs = Some(day.response_total_secs / day.responses as u64);
+            }
+            StatsEvent::Ignored => day.ignored += 1,
+            StatsEvent::IdleEpisode(secs) => day.idle_episodes.push(secs),
+            StatsEvent::MiniBreak => {
+                day.mini_breaks += 1;
+                day.hour(break_hour).mini_breaks += 1;
+                day.current_streak += 1;
+                day.longest_streak = day.longest_streak.max(day.current_streak);
+            }
+            StatsEvent::LongBreak => {
+                day.long_breaks += 1;
+                day.breaks_taken += 1;
+                day.hour(break_hour).breaks_taken += 1;
+                day.current_streak += 1;
+                day.longest_streak = day.longest_streak.max(day.current_streak);
+            }
+        }
+
+        self.persist(&file);
+    }
+
+    /// Counts a break the user took on their own, streak included.
+    pub fn record_manual_break(&self, minutes: u64, note: Option<String>) {
+        let mut file = self.file.lock().unwrap();
+        let day = file.days.entry(today()).or_default();
+        day.breaks_taken += 1;
+        day.this_hour().breaks_taken += 1;
+        day.current_streak += 1;
+        day.longest_streak = day.longest_streak.max(day.current_streak);
+        day.manual_breaks.push(ManualBreak {
+            at: Utc::now(),
+            minutes,
+            note,
+        });
+        self.persist(&file);
+    }
+
+    pub fn record_snooze_reason(&self, minutes: u64, reason: String) {
+        let mut file = self.file.lock().unwrap();
+        if file.snooze_reasons.len() >= MAX_SNOOZE_REASONS {
+            let excess = file.snooze_reasons.len() + 1 - MAX_SNOOZE_REASONS;
+            file.snooze_reasons.drain(..excess);
+        }
+        file.snooze_reasons.push(SnoozeReason {
+            at: Utc::now(),
+            minutes,
+            reason,
+        });
+        self.persist(&file);
+    }
+
+    /// Recorded snooze reasons, newest first.
+    pub fn snooze_reasons(&self) -> Vec<SnoozeReason> {
+        let file = self.file.lock().unwrap();
+        file.snooze_reasons.iter().rev().cloned().collect()
+    }
+
+    pub fn desk_posture(&self) -> Option<DeskPosture> {
+        self.file.lock().unwrap().desk_posture
+    }
+
+    pub fn set_desk_posture(&self, posture: DeskPosture) {
+        let mut file = self.file.lock().unwrap();
+        if file.desk_posture != Some(posture) {
+            file.desk_posture = Some(posture);
+            self.persist(&file);
+        }
+    }
+
+    pub fn focus_bar_position(&self) -> Option<FocusBarPosition> {
+        self.file.lock().unwrap().focus_bar_position
+    }
+
+    pub fn set_focus_bar_position(&self, position: FocusBarPosition) {
+        let mut file = self.file.lock().unwrap();
+        if file.focus_bar_position != Some(position) {
+            file.focus_bar_position = Some(position);
+            self.persist(&file);
+        }
+    }
+
+    pub fn today(&self) -> DayStats {
+        self.day(today()).unwrap_or_default()
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Option<DayStats> {
+        self.file.lock().unwrap().days.get(&date).cloned()
+    }
+
+    /// The last `days` days that have stats, oldest first, today included.
+    pub fn recent_days(&self, days: u64) -> Vec<DayStats> {
+        let today = today();
+        let start = today
+            .checked_sub_days(Days::new(days.saturating_sub(1)))
+            .unwrap_or(NaiveDate::MIN);
+        self.file
+            .lock()
+            .unwrap()
+            .days
+            .range(start..=today)
+            .map(|(_, day)| day.clone())
+            .collect()
+    }
+
+    /// Days with stats from `start` through `end`, oldest first.
+    pub fn days_between(&self, start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, DayStats)> {
+        self.file
+            .lock()
+            .unwrap()
+            .days
+            .range(start..=end)
+            .map(|(date, day)| (*date, day.clone()))
+            .collect()
+    }
+
+    /// `(bucket start secs, episodes)` for each idle-length bucket on `date`;
+    /// empty when nothing was recorded that day.
+    pub fn idle_histogram(&self, date: NaiveDate) -> Vec<(u64, u64)> {
+        let Some(day) = self.day(date).filter(|day| !day.idle_episodes.is_empty()) else {
+            return Vec::new();
+        };
+
+        let mut buckets: Vec<(u64, u64)> = IDLE_HISTOGRAM_BUCKETS_SECS
+            .iter()
+            .map(|start| (*start, 0))
+            .collect();
+        for secs in day.idle_episodes {
+            if let Some(bucket) = buckets.iter_mut().rev().find(|(start, _)| secs >= *start) {
+                bucket.1 += 1;
+            }
+        }
+        buckets
+    }
+
+    /// Forgets every recorded day and snooze reason. Whether today's summary
+    /// went out is kept, so clearing doesn't send it again.
+    pub fn clear(&self) {
+        let mut file = self.file.lock().unwrap();
+        file.days.clear();
+        file.snooze_reasons.clear();
+        self.persist(&file);
+    }
+
+    /// Marks today as summarized, returning `false` if it already was.
+    pub fn mark_summarized(&self, date: NaiveDate) -> bool {
+        let mut file = self.file.lock().unwrap();
+        if file.last_summary_date == Some(date) {
+            return false;
+        }
+        file.last_summary_date = Some(date);
+        self.persist(&file);
+        true
+    }
+
+    /// Where the stats are saved; `None` when kept in memory only.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Writes the stats out now, reporting any failure rather than just
+    /// logging it.
+    pub fn save(&self) -> std::io::Result<()> {
+        match &self.path {
+            Some(path) => write_stats(path, &self.file.lock().unwrap()),
+            None => Ok(()),
+        }
+    }
+
+    fn persist(&self, file: &StatsFile) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(err) = write_stats(path, file) {
+            eprintln!("TouchGrass: failed to save stats ({err}).");
+        }
+    }
+}
+
+/// Suggests an interval from how reminders were answered over `days`: many
+/// skipped or ignored reminders call for spacing them out, while near-perfect
+/// completion with plenty of stepping away on one's own allows tightening.
+pub fn suggest_interval(days: &[DayStats], current_minutes: u64) -> IntervalSuggestion {
+    let reminders: u32 = days.iter().map(|day| day.reminders_shown).sum();
+    let keep = |rationale: &str| IntervalSuggestion {
+        current_minutes,
+        suggested_minutes: current_minutes,
+        rationale: rationale.to_string(),
+    };
+    if reminders < MIN_SUGGESTION_REMINDERS {
+        return keep("Not enough reminders yet to suggest a change.");
+    }
+
+    let shown = reminders as f64;
+    let skipped: u32 = days.iter().map(|day| day.skipped + day.ignored).sum();
+    let completed: u32 = days
+        .iter()
+        .map(|day| day.breaks_taken + day.mini_breaks)
+        .sum();
+    let idle_episodes: usize = days.iter().map(|day| day.idle_episodes.len()).sum();
+    let skip_rate = skipped as f64 / shown;
+    let completion_rate = completed as f64 / shown;
+
+    let (min, max) = SUGGESTED_INTERVAL_RANGE_MINUTES;
+    if skip_rate >= HIGH_SKIP_RATE && current_minutes < max {
+        let suggested = round_to_five(current_minutes as f64 * 1.25)
+            .max(current_minutes + 5)
+            .min(max);
+        return IntervalSuggestion {
+            current_minutes,
+            suggested_minutes: suggested,
+            rationale: format!(
+                "{:.0}% of reminders were skipped or ignored; fewer, better-timed ones may stick.",
+                skip_rate * 100.0
+            ),
+        };
+    }
+    if completion_rate >= HIGH_COMPLETION_RATE
+        && idle_episodes >= reminders as usize
+        && current_minutes > min
+    {
+        let suggested = round_to_five(current_minutes as f64 * 0.8)
+            .min(current_minutes.saturating_sub(5))
+            .max(min);
+        return IntervalSuggestion {
+            current_minutes,
+            suggested_minutes: suggested,
+            rationale: format!(
+                "{:.0}% of reminders ended in a break and you often step away on your own; you can handle more frequent ones.",
+                completion_rate * 100.0
+            ),
+        };
+    }
+    keep("Your current interval is working well.")
+}
+
+/// Sums up the `REPORT_WEEK_DAYS` ending `today` from `days`, which may
+/// reach back another week for the trend. Mini breaks count as
+/// `mini_break_weight` of a break, as in `DayStats::completion_rate`.
+pub fn weekly_report(
+    days: &[(NaiveDate, DayStats)],
+    today: NaiveDate,
+    mini_break_weight: f64,
+) -> WeeklyReport {
+    let week_start = today
+        .checked_sub_days(Days::new(REPORT_WEEK_DAYS - 1))
+        .unwrap_or(NaiveDate::MIN);
+    let (this_week, last_week): (Vec<_>, Vec<_>) =
+        days.iter().partition(|(date, _)| *date >= week_start);
+
+    let mut report_days: Vec<ReportDay> = this_week
+        .iter()
+        .map(|(date, day)| ReportDay {
+            date: *date,
+            reminders_shown: day.reminders_shown,
+            breaks_taken: day.breaks_taken,
+            completion_rate: day.completion_rate(mini_break_weight),
+        })
+        .collect();
+    report_days.sort_by_key(|day| day.date);
+
+    let rated = || {
+        report_days
+            .iter()
+            .filter_map(|day| day.completion_rate.map(|rate| (day.date, rate)))
+    };
+    // Ties go to the earlier day either way.
+    let best_day = rated()
+        .reduce(|best, day| if day.1 > best.1 { day } else { best })
+        .map(|(date, _)| date);
+    let worst_day = rated()
+        .reduce(|worst, day| if day.1 < worst.1 { day } else { worst })
+        .map(|(date, _)| date);
+
+    let completion_rate = combined_rate(this_week.iter().map(|(_, day)| day), mini_break_weight);
+    let previous_completion_rate =
+        combined_rate(last_week.iter().map(|(_, day)| day), mini_break_weight);
+    let trend = completion_rate
+        .zip(previous_completion_rate)
+        .map(|(current, previous)| match current - previous {
+            change if change >= TREND_THRESHOLD => Trend::Up,
+            change if change <= -TREND_THRESHOLD => Trend::Down,
+            _ => Trend::Flat,
+        });
+
+    WeeklyReport {
+        total_breaks: report_days.iter().map(|day| day.breaks_taken).sum(),
+        days: report_days,
+        completion_rate,
+        previous_completion_rate,
+        best_day,
+        worst_day,
+        trend,
+    }
+}
+
+/// All 24 hours of the day, midnight first, with `days` added up per hour.
+/// Mini breaks count as `mini_break_weight` of a break, as in
+/// `DayStats::completion_rate`. Stats from before hours were tracked only
+/// show up in the daily totals, not here.
+pub fn effectiveness_by_hour<'a>(
+    days: impl IntoIterator<Item = &'a DayStats>,
+    mini_break_weight: f64,
+) -> Vec<HourEffectiveness> {
+    let mut totals = [HourCounts::default(); 24];
+    for day in days {
+        for (hour, counts) in &day.hours {
+            let Some(total) = totals.get_mut(*hour as usize) else {
+                continue;
+            };
+            total.reminders_shown += counts.reminders_shown;
+            total.breaks_taken += counts.breaks_taken;
+            total.mini_breaks += counts.mini_breaks;
+        }
+    }
+
+    (0u8..)
+        .zip(totals)
+        .map(|(hour, counts)| {
+            let completion_rate = (counts.reminders_shown > 0).then(|| {
+                let completed =
+                    counts.breaks_taken as f64 + counts.mini_breaks as f64 * mini_break_weight;
+                (completed / counts.reminders_shown as f64).min(1.0)
+            });
+            HourEffectiveness {
+                hour,
+                reminders_shown: counts.reminders_shown,
+                breaks_taken: counts.breaks_taken,
+                mini_breaks: counts.mini_breaks,
+                completion_rate,
+                enough_data: counts.reminders_shown >= MIN_HOUR_REMINDERS,
+            }
+        })
+        .collect()
+}
+
+/// Completion rate across several days together, rather than the mean of
+/// each day's rate, so a day with one reminder doesn't count like a full one.
+fn combined_rate<'a>(
+    days: impl Iterator<Item = &'a DayStats>,
+    mini_break_weight: f64,
+) -> Option<f64> {
+    let (shown, completed) = days.fold((0u32, 0.0), |(shown, completed), day| {
+        (
+            shown + day.reminders_shown,
+            completed + day.breaks_taken as f64 + day.mini_breaks as f64 * mini_break_weight,
+        )
+    });
+    (shown > 0).then(|| (completed / shown as f64).min(1.0))
+}
+
+fn round_to_five(minutes: f64) -> u64 {
+    ((minutes / 5.0).round() * 5.0) as u64
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Whether `contents` reads as a stats file.
+pub fn parses(contents: &str) -> bool {
+    serde_json::from_str::<StatsFile>(contents).is_ok()
+}
+
+fn read_stats(path: &Path) -> Option<StatsFile> {
+    let contents = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!("TouchGrass: stats.json was invalid ({err}); starting fresh.");
+            None
+        }
+    }
+}
+
+fn write_stats(path: &Path, file: &StatsFile) -> std::io::Result<()> {
+    let handle = File::create(path)?;
+    serde_json::to_writer_pretty(handle, file)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(shown: u32, breaks: u32, skipped: u32, idle_episodes: usize) -> DayStats {
+        DayStats {
+            reminders_shown: shown,
+            breaks_taken: breaks,
+            skipped,
+            idle_episodes: vec![300; idle_episodes],
+            ..DayStats::default()
+        }
+    }
+
+    #[test]
+    fn suggest_interval_waits_for_enough_reminders() {
+        let suggestion = suggest_interval(&[day(5, 0, 5, 0)], 20);
+        assert_eq!(suggestion.suggested_minutes, 20);
+    }
+
+    #[test]
+    fn suggest_interval_spaces_out_skipped_reminders() {
+        let suggestion = suggest_interval(&[day(10, 2, 4, 0), day(10, 2, 4, 0)], 20);
+        assert_eq!(suggestion.current_minutes, 20);
+        assert_eq!(suggestion.suggested_minutes, 25);
+        assert!(suggestion.rationale.starts_with("40%"));
+    }
+
+    #[test]
+    fn suggest_interval_tightens_when_breaks_come_easily() {
+        let suggestion = suggest_interval(&[day(10, 10, 0, 12)], 20);
+        assert_eq!(suggestion.suggested_minutes, 15);
+    }
+
+    #[test]
+    fn suggest_interval_needs_stepping_away_to_tighten() {
+        let suggestion = suggest_interval(&[day(10, 10, 0, 2)], 20);
+        assert_eq!(suggestion.suggested_minutes, 20);
+    }
+
+    #[test]
+    fn suggest_interval_stays_in_range() {
+        let (min, max) = SUGGESTED_INTERVAL_RANGE_MINUTES;
+        assert_eq!(
+            suggest_interval(&[day(10, 0, 10, 0)], max).suggested_minutes,
+            max
+        );
+        assert_eq!(
+            suggest_interval(&[day(10, 0, 10, 0)], max - 2).suggested_minutes,
+            max
+        );
+        assert_eq!(
+            suggest_interval(&[day(10, 10, 0, 10)], 4).suggested_minutes,
+            min
+        );
+    }
+
+    fn hours(counts: &[(u8, u32, u32, u32)]) -> DayStats {
+        DayStats {
+            hours: counts
+                .iter()
+                .map(|&(hour, reminders_shown, breaks_taken, mini_breaks)| {
+                    (
+                        hour,
+                        HourCounts {
+                            reminders_shown,
+                            breaks_taken,
+                            mini_breaks,
+                        },
+                    )
+                })
+                .collect(),
+            ..DayStats::default()
+        }
+    }
+
+    #[test]
+    fn effectiveness_by_hour_lists_every_hour() {
+        let by_hour = effectiveness_by_hour(&[hours(&[(9, 4, 2, 0)])], 0.5);
+        assert_eq!(by_hour.len(), 24);
+        assert!(by_hour
+            .iter()
+            .zip(0u8..)
+            .all(|(entry, hour)| entry.hour == hour));
+        assert_eq!(by_hour[0].completion_rate, None);
+        assert_eq!(by_hour[9].completion_rate, Some(0.5));
+    }
+
+    #[test]
+    fn effectiveness_by_hour_adds_days_up_before_the_cutoff() {
+        let day = hours(&[(14, MIN_HOUR_REMINDERS - 1, 0, 0)]);
+        assert!(!effectiveness_by_hour([&day], 0.5)[14].enough_data);
+        let more = hours(&[(14, 1, 0, 0)]);
+        let by_hour = effectiveness_by_hour([&day, &more], 0.5);
+        assert_eq!(by_hour[14].reminders_shown, MIN_HOUR_REMINDERS);
+        assert!(by_hour[14].enough_data);
+    }
+
+    #[test]
+    fn effectiveness_by_hour_weighs_mini_breaks() {
+        let day = hours(&[(10, 4, 1, 2)]);
+        assert_eq!(
+            effectiveness_by_hour([&day], 0.5)[10].completion_rate,
+            Some(0.5)
+        );
+        assert_eq!(
+            effectiveness_by_hour([&day], 0.0)[10].completion_rate,
+            Some(0.25)
+        );
+    }
+
+    #[test]
+    fn a_break_counts_in_its_reminders_hour() {
+        let stats = Stats::in_memory();
+        let hour = (Local::now().hour() as u8 + 23) % 24;
+        stats.record_answer(StatsEvent::BreakTaken, Some(hour));
+        stats.record_answer(StatsEvent::MiniBreak, Some(hour));
+        let today = stats.today();
+        assert_eq!(today.breaks_taken, 1);
+        assert_eq!(today.hours[&hour].breaks_taken, 1);
+        assert_eq!(today.hours[&hour].mini_breaks, 1);
+        assert_eq!(today.hours.len(), 1);
+    }
+}
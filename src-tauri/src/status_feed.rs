@@ -0,0 +1,192 @@
+use std::{
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::{SinkExt, StreamExt};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Emitter, Wry,
+};
+use tokio::{net::TcpListener, sync::broadcast};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{app_state::StatusSnapshot, events};
+
+/// Wait after a failed `accept`, doubling while failures keep coming so the
+/// loop doesn't spin.
+const ACCEPT_BACKOFF_MIN_MS: u64 = 100;
+const ACCEPT_BACKOFF_MAX_MS: u64 = 5_000;
+
+/// Read-only WebSocket feed on `127.0.0.1` that mirrors every status update as
+/// the same JSON payload the UI receives on `STATUS_EVENT`. Incoming messages
+/// are ignored; there is no control over the socket.
+pub struct StatusFeed {
+    /// Dropped when the server stops, which closes every open connection.
+    tx: Arc<SenderSlot>,
+    latest: Arc<Mutex<Option<String>>>,
+    server: Arc<ServerSlot>,
+}
+
+type SenderSlot = Mutex<Option<broadcast::Sender<String>>>;
+/// The running server's port and its task.
+type ServerSlot = Mutex<Option<(u16, JoinHandle<()>)>>;
+
+impl StatusFeed {
+    pub fn new() -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(None)),
+            latest: Arc::new(Mutex::new(None)),
+            server: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn publish(&self, status: &StatusSnapshot) {
+        let payload = events::StatusPayload {
+            status: status.clone(),
+        };
+        let Ok(json) = serde_json::to_string(&payload) else {
+            return;
+        };
+        *self.latest.lock().unwrap() = Some(json.clone());
+        if let Some(tx) = self.tx.lock().unwrap().as_ref() {
+            // No subscribers is the common case; nothing to do then.
+            let _ = tx.send(json);
+        }
+    }
+
+    /// Starts, restarts, or stops the server so it matches `port`.
+    pub fn configure(&self, app: &AppHandle<Wry>, port: Option<u16>) {
+        let mut server = self.server.lock().unwrap();
+        if server.as_ref().map(|(current, _)| *current) == port {
+            return;
+        }
+
+        if let Some((_, handle)) = server.take() {
+            handle.abort();
+        }
+        *self.tx.lock().unwrap() = None;
+
+        let Some(port) = port else {
+            return;
+        };
+
+        let (tx, _) = broadcast::channel(16);
+        *self.tx.lock().unwrap() = Some(tx.clone());
+
+        let app = app.clone();
+        let feed = Feed {
+            tx,
+            latest: self.latest.clone(),
+            current_tx: self.tx.clone(),
+            server: self.server.clone(),
+        };
+        let handle = async_runtime::spawn(async move {
+            serve(app, port, feed).await;
+        });
+        *server = Some((port, handle));
+    }
+}
+
+/// What a running server shares with its `StatusFeed`.
+struct Feed {
+    tx: broadcast::Sender<String>,
+    latest: Arc<Mutex<Option<String>>>,
+    current_tx: Arc<SenderSlot>,
+    server: Arc<ServerSlot>,
+}
+
+async fn serve(app: AppHandle<Wry>, port: u16, feed: Feed) {
+    let Feed { tx, latest, .. } = &feed;
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            // Forget this server so saving the same port again retries.
+            let mut server = feed.server.lock().unwrap();
+            if server.as_ref().is_some_and(|(current, _)| *current == port) {
+                *server = None;
+                let mut current_tx = feed.current_tx.lock().unwrap();
+                if current_tx
+                    .as_ref()
+                    .is_some_and(|current| current.same_channel(tx))
+                {
+                    *current_tx = None;
+                }
+            }
+            drop(server);
+            let _ = app.emit(
+                events::LOG_EVENT,
+                events::LogPayload {
+                    level: "error".into(),
+                    message: format!("status feed could not bind 127.0.0.1:{port}: {err}"),
+                },
+            );
+            return;
+        }
+    };
+
+    let mut backoff: Option<Duration> = None;
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => {
+                backoff = None;
+                stream
+            }
+            Err(err) => {
+                let wait = match backoff {
+                    Some(wait) => (wait * 2).min(Duration::from_millis(ACCEPT_BACKOFF_MAX_MS)),
+                    None => {
+                        let _ = app.emit(
+                            events::LOG_EVENT,
+                            events::LogPayload {
+                                level: "warning".into(),
+                                message: format!("status feed accept failed, backing off: {err}"),
+                            },
+                        );
+                        Duration::from_millis(ACCEPT_BACKOFF_MIN_MS)
+                    }
+                };
+                backoff = Some(wait);
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+        };
+
+        let mut rx = tx.subscribe();
+        // Send the current status straight away so clients don't wait for a change.
+        let greeting = latest.lock().unwrap().clone();
+        async_runtime::spawn(async move {
+            let Ok(mut socket) = tokio_tungstenite::accept_async(stream).await else {
+                return;
+            };
+
+            if let Some(json) = greeting {
+                if socket.send(Message::Text(json.into())).await.is_err() {
+                    return;
+                }
+            }
+
+            loop {
+                tokio::select! {
+                    update = rx.recv() => {
+                        let json = match update {
+                            Ok(json) => json,
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        };
+                        if socket.send(Message::Text(json.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    incoming = socket.next() => {
+                        match incoming {
+                            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
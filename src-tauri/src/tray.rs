@@ -1,62 +1,200 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
 use tauri::{
-    menu::{Menu, MenuBuilder, MenuItemKind},
+    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem},
     tray::TrayIconBuilder,
-    AppHandle, Manager, Wry,
+    AppHandle, Emitter, Manager, Wry,
 };
 
-use crate::app_state::{AppState, StatusSnapshot};
+use crate::{
+    app_state::{self, AppState, PauseReason, SnoozeBoundary, StatusSnapshot},
+    events,
+};
 
 const TRAY_ID: &str = "touchgrass-tray";
+const TRAY_TOOLTIP: &str = "TouchGrass";
+/// How long `flash` keeps the tray marked.
+const FLASH_SECS: u64 = 10;
 const MENU_OPEN: &str = "open-settings";
 const MENU_PAUSE: &str = "toggle-pause";
+const MENU_BREAK_NOW: &str = "break-now";
 const MENU_SNOOZE_5: &str = "snooze-5";
 const MENU_SNOOZE_15: &str = "snooze-15";
+const MENU_SNOOZE_SMART: &str = "snooze-smart";
+const MENU_SNOOZE_AFTERNOON: &str = "snooze-afternoon";
+const MENU_SNOOZE_EVENING: &str = "snooze-evening";
+const MENU_SNOOZE_TOMORROW: &str = "snooze-tomorrow";
 const MENU_QUIT: &str = "quit";
+/// Sections of the settings view `open_settings` can land on.
+const SETTINGS_SECTIONS: &[&str] = &["cadence", "preferences"];
+
+/// Tray menu text. Every field falls back to the English default, so an
+/// override only needs the labels it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayLabels {
+    pub open: String,
+    pub pause: String,
+    pub resume: String,
+    pub break_now: String,
+    pub snooze_5: String,
+    pub snooze_15: String,
+    pub snooze_smart: String,
+    pub snooze_afternoon: String,
+    pub snooze_evening: String,
+    pub snooze_tomorrow: String,
+    /// Appended to the snooze items as "{label} - {snoozed_until} HH:MM".
+    pub snoozed_until: String,
+    pub quit: String,
+}
 
-#[derive(Clone)]
+impl Default for TrayLabels {
+    fn default() -> Self {
+        Self {
+            open: "Open TouchGrass".into(),
+            pause: "Pause reminders".into(),
+            resume: "Resume reminders".into(),
+            break_now: "Take a break now".into(),
+            snooze_5: "Snooze 5 minutes".into(),
+            snooze_15: "Snooze 15 minutes".into(),
+            snooze_smart: "Snooze (smart)".into(),
+            snooze_afternoon: "Snooze until after lunch".into(),
+            snooze_evening: "Snooze until evening".into(),
+            snooze_tomorrow: "Snooze until tomorrow morning".into(),
+            snoozed_until: "snoozed until".into(),
+            quit: "Quit".into(),
+        }
+    }
+}
+
+/// Handles to the tray items whose state follows the engine status. Swapped
+/// out whenever the menu is rebuilt.
 pub struct TrayState {
-    menu: Menu<Wry>,
+    items: Mutex<TrayItems>,
+    app: AppHandle<Wry>,
+    state: Arc<AppState>,
+    /// Set while `show_attention` has the tooltip.
+    attention: AtomicBool,
+}
+
+struct TrayItems {
+    labels: TrayLabels,
+    pause_item: CheckMenuItem<Wry>,
+    snooze_items: Vec<(MenuItem<Wry>, String)>,
 }
 
 impl TrayState {
-    pub fn new(menu: Menu<Wry>) -> Self {
-        Self { menu }
+    pub fn sync(&self, status: &StatusSnapshot) {
+        self.items.lock().unwrap().sync(status);
+        if self.attention.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(tray) = self.app.tray_by_id(TRAY_ID) {
+            let _ = tray.set_tooltip(Some(self.tooltip(status)));
+        }
     }
 
-    pub fn sync(&self, status: &StatusSnapshot) {
-        if let Some(MenuItemKind::Check(check_item)) = self.menu.get(MENU_PAUSE) {
-            let paused = status.paused;
-            let label = if paused {
-                "Resume reminders"
-            } else {
-                "Pause reminders"
+    /// `tray_tooltip_template` filled in, or "TouchGrass - next break in
+    /// 12 minutes" without one. On top of the message placeholders,
+    /// `{status}` is `format_next` and `{count}` is the same as `{breaks}`.
+    fn tooltip(&self, status: &StatusSnapshot) -> String {
+        let next = format_next(status, Utc::now());
+        match self.state.tray_tooltip_template() {
+            Some(template) => app_state::render_message(
+                &template
+                    .replace("{status}", &next)
+                    .replace("{count}", "{breaks}"),
+                &self.state.message_context_for(status),
+            ),
+            None if next.starts_with("in ") => format!("{TRAY_TOOLTIP} - next break {next}"),
+            None => format!("{TRAY_TOOLTIP} - {next}"),
+        }
+    }
+}
+
+impl TrayItems {
+    fn sync(&self, status: &StatusSnapshot) {
+        // The pause item stays enabled in every state so reminders can always be resumed.
+        let paused = status.paused;
+        let label = if paused {
+            &self.labels.resume
+        } else {
+            &self.labels.pause
+        };
+        let _ = self.pause_item.set_checked(paused);
+        let _ = self.pause_item.set_text(label);
+
+        let snoozed_until = status.snoozed_until.filter(|until| *until > Utc::now());
+        for (item, base_label) in &self.snooze_items {
+            let _ = item.set_enabled(!paused);
+            let _ = match snoozed_until {
+                Some(until) => item.set_text(format!(
+                    "{base_label} - {} {}",
+                    self.labels.snoozed_until,
+                    until.with_timezone(&Local).format("%H:%M")
+                )),
+                None => item.set_text(base_label),
             };
-            let _ = check_item.set_checked(paused);
-            let _ = check_item.set_text(label);
         }
     }
 }
 
-pub fn setup_tray(app: &AppHandle<Wry>, state: Arc<AppState>) -> tauri::Result<()> {
-    let menu = MenuBuilder::new(app)
-        .text(MENU_OPEN, "Open TouchGrass")
-        .separator()
-        .check(MENU_PAUSE, "Pause reminders")
-        .separator()
-        .text(MENU_SNOOZE_5, "Snooze 5 minutes")
-        .text(MENU_SNOOZE_15, "Snooze 15 minutes")
+fn build_menu(app: &AppHandle<Wry>, labels: &TrayLabels) -> tauri::Result<(Menu<Wry>, TrayItems)> {
+    let pause_item =
+        CheckMenuItem::with_id(app, MENU_PAUSE, &labels.pause, true, false, None::<&str>)?;
+    let snooze_items = [
+        (MENU_SNOOZE_5, &labels.snooze_5),
+        (MENU_SNOOZE_15, &labels.snooze_15),
+        (MENU_SNOOZE_SMART, &labels.snooze_smart),
+        (MENU_SNOOZE_AFTERNOON, &labels.snooze_afternoon),
+        (MENU_SNOOZE_EVENING, &labels.snooze_evening),
+        (MENU_SNOOZE_TOMORROW, &labels.snooze_tomorrow),
+    ]
+    .into_iter()
+    .map(|(id, label)| {
+        MenuItem::with_id(app, id, label, true, None::<&str>).map(|item| (item, label.clone()))
+    })
+    .collect::<tauri::Result<Vec<_>>>()?;
+
+    let mut menu = MenuBuilder::new(app)
+        .text(MENU_OPEN, &labels.open)
         .separator()
-        .text(MENU_QUIT, "Quit")
-        .build()?;
+        .item(&pause_item)
+        .text(MENU_BREAK_NOW, &labels.break_now)
+        .separator();
+    for (item, _) in &snooze_items {
+        menu = menu.item(item);
+    }
+    let menu = menu.separator().text(MENU_QUIT, &labels.quit).build()?;
 
-    let tray_state = TrayState::new(menu.clone());
-    app.manage(tray_state.clone());
+    Ok((
+        menu,
+        TrayItems {
+            labels: labels.clone(),
+            pause_item,
+            snooze_items,
+        },
+    ))
+}
+
+pub fn setup_tray(app: &AppHandle<Wry>, state: Arc<AppState>) -> tauri::Result<()> {
+    let (menu, items) = build_menu(app, &state.preferences().tray_labels)?;
+    items.sync(&state.status());
+    app.manage(TrayState {
+        items: Mutex::new(items),
+        app: app.clone(),
+        state: state.clone(),
+        attention: AtomicBool::new(false),
+    });
 
     let mut builder = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
-        .tooltip("TouchGrass");
+        .tooltip(TRAY_TOOLTIP);
 
     if let Some(icon) = app.default_window_icon().cloned() {
         builder = builder.icon(icon);
@@ -70,19 +208,153 @@ pub fn setup_tray(app: &AppHandle<Wry>, state: Arc<AppState>) -> tauri::Result<(
         })
         .build(app)?;
 
-    tray_state.sync(&state.status());
+    app.state::<TrayState>().sync(&state.status());
+    Ok(())
+}
+
+/// When the next reminder is due, in words: "in 12 minutes", "paused",
+/// "snoozed until 2:30 PM". Shared by the tray and `next_reminder_label` so
+/// both say it the same way.
+pub fn format_next(status: &StatusSnapshot, now: DateTime<Utc>) -> String {
+    if let Some(until) = status.paused_until {
+        return format!(
+            "paused until {}",
+            until.with_timezone(&Local).format("%-I:%M %p")
+        );
+    }
+    if status.paused {
+        return "paused".into();
+    }
+    let held = status.pause_reason.filter(|_| !status.strict_mode);
+    match (held, status.blocking_app.as_deref()) {
+        (Some(PauseReason::ScreenLocked), _) => return "on hold while the screen is locked".into(),
+        (Some(PauseReason::DisplayOff), _) => return "on hold while the display is off".into(),
+        (Some(PauseReason::LidClosed), _) => return "on hold while the lid is closed".into(),
+        (Some(PauseReason::AppRunning), Some(app)) => return format!("on hold while {app} runs"),
+        (Some(PauseReason::BluetoothCall), _) => return "on hold during your call".into(),
+        (Some(PauseReason::DayOff), _) => return "off today".into(),
+        (Some(PauseReason::BusyFlag), _) => return "on hold while you're busy".into(),
+        _ => {}
+    }
+    if let Some(until) = status.snoozed_until.filter(|until| *until > now) {
+        return format!(
+            "snoozed until {}",
+            until.with_timezone(&Local).format("%-I:%M %p")
+        );
+    }
+    let Some(next) = status.next_trigger_at else {
+        return "waiting for you to come back".into();
+    };
+
+    let minutes = ((next - now).num_seconds().max(0) as u64).div_ceil(60);
+    match minutes {
+        0 => "any moment now".into(),
+        1 => "in 1 minute".into(),
+        2..=59 => format!("in {minutes} minutes"),
+        _ => {
+            let (hours, minutes) = (minutes / 60, minutes % 60);
+            let hours = if hours == 1 {
+                "1 hour".to_string()
+            } else {
+                format!("{hours} hours")
+            };
+            match minutes {
+                0 => format!("in {hours}"),
+                1 => format!("in {hours} 1 minute"),
+                _ => format!("in {hours} {minutes} minutes"),
+            }
+        }
+    }
+}
+
+/// Whether the tray icon was created.
+pub fn exists(app: &AppHandle<Wry>) -> bool {
+    app.tray_by_id(TRAY_ID).is_some()
+}
 
+/// Fallback for when a reminder couldn't be shown as a notification: puts
+/// the message in the tray tooltip (and title, where the platform has one).
+pub fn show_attention(app: &AppHandle<Wry>, message: &str) {
+    if let Some(tray_state) = app.try_state::<TrayState>() {
+        tray_state.attention.store(true, Ordering::Relaxed);
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("{TRAY_TOOLTIP} - {message}")));
+        let _ = tray.set_title(Some("!"));
+    }
+}
+
+/// Marks the tray like `show_attention` for a few seconds, as an extra cue
+/// next to a notification that did get through.
+pub fn flash(app: &AppHandle<Wry>, message: &str) {
+    show_attention(app, message);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(FLASH_SECS)).await;
+        clear_attention(&app);
+    });
+}
+
+pub fn clear_attention(app: &AppHandle<Wry>) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_title(None::<&str>);
+    }
+    if let Some(tray_state) = app.try_state::<TrayState>() {
+        tray_state.attention.store(false, Ordering::Relaxed);
+        tray_state.sync(&tray_state.state.status());
+    }
+}
+
+/// Rebuilds the tray menu with new labels, keeping item state in sync.
+pub fn rebuild_menu(
+    app: &AppHandle<Wry>,
+    labels: &TrayLabels,
+    status: &StatusSnapshot,
+) -> tauri::Result<()> {
+    let (Some(tray), Some(tray_state)) = (app.tray_by_id(TRAY_ID), app.try_state::<TrayState>())
+    else {
+        return Ok(());
+    };
+
+    let (menu, items) = build_menu(app, labels)?;
+    items.sync(status);
+    tray.set_menu(Some(menu))?;
+    *tray_state.items.lock().unwrap() = items;
     Ok(())
 }
 
+/// Brings the main window back from the tray, minimized, or behind others.
+pub fn focus_main_window(app: &AppHandle<Wry>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Brings the main window up on `section` of the settings, which must be
+/// one of `SETTINGS_SECTIONS`.
+pub fn open_settings(app: &AppHandle<Wry>, section: Option<&str>) -> Result<(), String> {
+    if let Some(section) = section.filter(|section| !SETTINGS_SECTIONS.contains(section)) {
+        return Err(format!(
+            "unknown settings section {section:?}; expected one of {}",
+            SETTINGS_SECTIONS.join(", ")
+        ));
+    }
+    focus_main_window(app);
+    app.emit(
+        events::NAVIGATE_EVENT,
+        events::NavigatePayload {
+            section: section.map(str::to_string),
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
 fn handle_menu_event(app: &AppHandle<Wry>, state: &Arc<AppState>, event: tauri::menu::MenuEvent) {
     match event.id().as_ref() {
         MENU_OPEN => {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.unminimize();
-                let _ = window.show();
-                let _ = window.set_focus();
-            }
+            let _ = open_settings(app, None);
         }
         MENU_PAUSE => {
             let paused = state.status().paused;
@@ -95,6 +367,12 @@ fn handle_menu_event(app: &AppHandle<Wry>, state: &Arc<AppState>, event: tauri::
                 }
             });
         }
+        MENU_BREAK_NOW => {
+            let state = Arc::clone(state);
+            tauri::async_runtime::spawn(async move {
+                state.trigger_preview().await;
+            });
+        }
         MENU_SNOOZE_5 => {
             let state = Arc::clone(state);
             tauri::async_runtime::spawn(async move {
@@ -107,6 +385,37 @@ fn handle_menu_event(app: &AppHandle<Wry>, state: &Arc<AppState>, event: tauri::
                 state.snooze(15).await;
             });
         }
+        MENU_SNOOZE_SMART => {
+            let state = Arc::clone(state);
+            tauri::async_runtime::spawn(async move {
+                if state.preferences().escalating_snooze {
+                    state.snooze_escalating().await;
+                } else {
+                    let minutes = state.smart_default_snooze();
+                    state.snooze(minutes).await;
+                }
+            });
+        }
+        MENU_SNOOZE_AFTERNOON => {
+            let state = Arc::clone(state);
+            tauri::async_runtime::spawn(async move {
+                state.snooze_to_boundary(SnoozeBoundary::Afternoon).await;
+            });
+        }
+        MENU_SNOOZE_EVENING => {
+            let state = Arc::clone(state);
+            tauri::async_runtime::spawn(async move {
+                state.snooze_to_boundary(SnoozeBoundary::Evening).await;
+            });
+        }
+        MENU_SNOOZE_TOMORROW => {
+            let state = Arc::clone(state);
+            tauri::async_runtime::spawn(async move {
+                state
+                    .snooze_to_boundary(SnoozeBoundary::TomorrowMorning)
+                    .await;
+            });
+        }
         MENU_QUIT => {
             app.exit(0);
         }
@@ -0,0 +1,67 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Wry};
+use tauri_plugin_updater::UpdaterExt;
+
+use crate::events::{self, UpdatePayload};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub current_version: String,
+    pub version: Option<String>,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+pub async fn check(app: &AppHandle<Wry>) -> tauri_plugin_updater::Result<UpdateInfo> {
+    let update = app.updater()?.check().await?;
+    let current_version = app.package_info().version.to_string();
+
+    Ok(match update {
+        Some(update) => UpdateInfo {
+            available: true,
+            current_version,
+            version: Some(update.version),
+            notes: update.body,
+            date: update.date.map(|date| date.to_string()),
+        },
+        None => UpdateInfo {
+            available: false,
+            current_version,
+            version: None,
+            notes: None,
+            date: None,
+        },
+    })
+}
+
+/// Downloads and installs the latest update, then restarts into it. Returns
+/// `false` if there was nothing to install.
+pub async fn install(app: &AppHandle<Wry>) -> tauri_plugin_updater::Result<bool> {
+    let Some(update) = app.updater()?.check().await? else {
+        return Ok(false);
+    };
+
+    let mut downloaded_bytes = 0u64;
+    update
+        .download_and_install(
+            |chunk, total_bytes| {
+                downloaded_bytes += chunk as u64;
+                let _ = app.emit(
+                    events::UPDATE_EVENT,
+                    UpdatePayload::Downloading {
+                        downloaded_bytes,
+                        total_bytes,
+                    },
+                );
+            },
+            || {
+                let _ = app.emit(events::UPDATE_EVENT, UpdatePayload::Installing);
+            },
+        )
+        .await?;
+
+    let _ = app.emit(events::UPDATE_EVENT, UpdatePayload::Installed);
+    app.restart();
+}